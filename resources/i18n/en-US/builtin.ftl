@@ -4,7 +4,21 @@ move-profile-lower = Move { $item-name } Down
 move-profile-to-bottom = Move { $item-name } to Bottom
 hide-profile = Hide { $item-name }
 hide-app = Hide all profiles of { $app-name }
+launch-mode = { $launch-mode ->
+        [incognito] Incognito
+        [new-window] New Window
+        [new-tab] New Tab
+        [app-window] App Window
+       *[guest] Guest Mode
+    }
+open-in-launch-mode = Open in { launch-mode }
+rule-launch-mode = { $launch-mode ->
+        [normal] Normally
+       *[other] In { launch-mode }
+    }
+rule-launch-mode-button = { rule-launch-mode } ▼
 
 settings-tab-advanced = Advanced
 settings-tab-general = General
 settings-tab-rules = Rules
+settings-tab-launch-options = Launch Options
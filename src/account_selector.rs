@@ -0,0 +1,121 @@
+// Selects a profile by its signed-in account instead of its profile directory, so that rules
+// keep working when a profile is recreated, e.g
+// "madis@acme.com" matches only that account
+// "@acme.com" matches any account of the acme.com domain (Google Workspace or just email domain)
+
+// How well an account matched the selector, better matches first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccountMatch {
+    Email,
+    HostedDomain,
+    EmailDomain,
+}
+
+pub fn account_match(
+    selector: &str,
+    email_maybe: Option<&str>,
+    hosted_domain_maybe: Option<&str>,
+) -> Option<AccountMatch> {
+    let selector = selector.trim().to_lowercase();
+    if selector.is_empty() {
+        return None;
+    }
+
+    if let Some(domain) = selector.strip_prefix('@') {
+        let hosted_domain_matches = hosted_domain_maybe
+            .map(|hosted_domain| hosted_domain.eq_ignore_ascii_case(domain))
+            .unwrap_or(false);
+        if hosted_domain_matches {
+            return Some(AccountMatch::HostedDomain);
+        }
+
+        let email_domain_matches = email_maybe
+            .and_then(|email| email.rsplit_once('@'))
+            .map(|(_, email_domain)| email_domain.eq_ignore_ascii_case(domain))
+            .unwrap_or(false);
+        if email_domain_matches {
+            return Some(AccountMatch::EmailDomain);
+        }
+
+        return None;
+    }
+
+    let email_matches = email_maybe
+        .map(|email| email.eq_ignore_ascii_case(selector.as_str()))
+        .unwrap_or(false);
+    if email_matches {
+        return Some(AccountMatch::Email);
+    }
+
+    return None;
+}
+
+// Index of the best matching candidate (email, hosted domain).
+// When several candidates match equally well, the first one wins,
+// so callers should pass candidates in a stable order (e.g sorted by profile id)
+pub fn find_best_match(
+    selector: &str,
+    candidates: &[(Option<&str>, Option<&str>)],
+) -> Option<usize> {
+    return candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (email_maybe, hosted_domain_maybe))| {
+            account_match(selector, *email_maybe, *hosted_domain_maybe).map(|m| (m, i))
+        })
+        .min()
+        .map(|(_, i)| i);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_selector() {
+        assert_eq!(
+            account_match("Madis@Acme.com", Some("madis@acme.com"), Some("acme.com")),
+            Some(AccountMatch::Email)
+        );
+        assert_eq!(
+            account_match("madis@acme.com", Some("other@acme.com"), Some("acme.com")),
+            None
+        );
+        assert_eq!(account_match("madis@acme.com", None, None), None);
+    }
+
+    #[test]
+    fn test_domain_selector() {
+        assert_eq!(
+            account_match("@acme.com", Some("madis@acme.com"), Some("acme.com")),
+            Some(AccountMatch::HostedDomain)
+        );
+        assert_eq!(
+            account_match("@acme.com", Some("madis@acme.com"), None),
+            Some(AccountMatch::EmailDomain)
+        );
+        assert_eq!(account_match("@acme.com", Some("madis@gmail.com"), None), None);
+        assert_eq!(account_match("", Some("madis@acme.com"), None), None);
+    }
+
+    #[test]
+    fn test_best_match_prefers_better_match() {
+        let candidates = [
+            (Some("shared@acme.com"), None),
+            (Some("madis@acme.com"), Some("acme.com")),
+        ];
+        assert_eq!(find_best_match("@acme.com", &candidates), Some(1));
+        assert_eq!(find_best_match("shared@acme.com", &candidates), Some(0));
+    }
+
+    #[test]
+    fn test_best_match_tie_break_is_first_candidate() {
+        let candidates = [
+            (None, None),
+            (Some("b@acme.com"), Some("acme.com")),
+            (Some("a@acme.com"), Some("acme.com")),
+        ];
+        assert_eq!(find_best_match("@acme.com", &candidates), Some(1));
+        assert_eq!(find_best_match("@other.com", &candidates), None);
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
@@ -8,10 +9,14 @@ use tracing::info;
 use url::form_urlencoded::byte_serialize;
 use url::Url;
 
+use crate::mail_url_parser::AccountLinkFormat;
 use crate::url_rule::UrlGlobMatcher;
+use crate::utils::LaunchMode;
 use crate::{
-    chromium_profiles_parser, firefox_profiles_parser, paths, slack_profiles_parser,
-    slack_url_parser, url_rule, CommonBrowserProfile, InstalledAppProfiles,
+    chromium_profiles_parser, epiphany_profiles_parser, falkon_profiles_parser,
+    firefox_profiles_parser, mail_profiles_parser, mail_url_parser, paths,
+    qutebrowser_profiles_parser, slack_profiles_parser, slack_url_parser,
+    thunderbird_profiles_parser, url_rule, CommonBrowserProfile, InstalledAppProfiles,
     InstalledBrowserProfile,
 };
 
@@ -19,6 +24,7 @@ use crate::{
 // All other apps will be the "default" supported app implementation
 pub struct SupportedAppRepository {
     snap_base: PathBuf,
+    flatpak_base: PathBuf,
     chromium_user_dir_base: PathBuf,
     firefox_user_dir_base: PathBuf,
     supported_apps: HashMap<String, SupportedApp>,
@@ -31,17 +37,29 @@ enum AppOS {
     WINDOWS,
 }
 
+// Config dir of a Chromium or Firefox fork which is not in the repository,
+// guessed once when finding installed apps and cached with them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GuessedAppConfig {
+    kind: AppKind,
+    config_dir: PathBuf,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
-enum AppKind {
+pub(crate) enum AppKind {
     GENERIC,
     CHROMIUM,
     FIREFOX,
     SLACK,
+    EPIPHANY,
+    FALKON,
     LINEAR,
     MIMESTREAM,
     NOTION,
+    QUTEBROWSER,
     SPOTIFY,
     TELEGRAM,
+    THUNDERBIRD,
     WORKFLOWY,
     ZOOM,
 }
@@ -60,7 +78,12 @@ struct AppConfig {
     kind: AppKind,
     id: String,
     config_dir_relative: String,
+    // snap instance name, e.g "chromium"
+    // (older repository files call it `snap_dir`)
+    #[serde(alias = "snap_dir")]
     snap_id: Option<String>,
+    // e.g "com.google.Chrome", if the app is also distributed as flatpak
+    flatpak_id: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -71,6 +94,7 @@ impl Default for AppConfig {
             id: "".to_string(),
             config_dir_relative: "".to_string(),
             snap_id: None,
+            flatpak_id: None,
         }
     }
 }
@@ -79,6 +103,7 @@ impl SupportedAppRepository {
     pub fn new() -> Self {
         let mut repository = Self {
             snap_base: paths::get_snap_root(),
+            flatpak_base: paths::get_flatpak_root(),
             chromium_user_dir_base: paths::get_chrome_user_dir_root(),
             firefox_user_dir_base: paths::get_firefox_user_dir_root(),
             supported_apps: HashMap::new(),
@@ -87,19 +112,119 @@ impl SupportedAppRepository {
         return repository;
     }
 
+    // Apps which are not in the repository are guessed only when finding installed apps
+    // (see `guess_app_config`), as the guess is then cached with the installed app
     pub fn get_or_generate(
         &self,
         app_id_str: &str,
         restricted_domain_patterns: &Vec<String>,
+        guessed_app_config_maybe: Option<&GuessedAppConfig>,
     ) -> SupportedApp {
-        return self
-            .supported_apps
-            .get(app_id_str)
-            .map(|app| app.to_owned())
-            .unwrap_or_else(|| {
-                let app_id = AppIdentifier::new_for_os(app_id_str);
-                Self::generic_app(app_id, restricted_domain_patterns.clone())
-            });
+        if let Some(app) = self.supported_apps.get(app_id_str) {
+            return app.to_owned();
+        }
+
+        let app_id = AppIdentifier::new_for_os(app_id_str);
+        let guessed_app_maybe = guessed_app_config_maybe.and_then(|guessed_app_config| {
+            let config_dir = guessed_app_config.config_dir.clone();
+            match guessed_app_config.kind {
+                AppKind::CHROMIUM => Some(Self::chromium_based_app(
+                    app_id.clone(),
+                    config_dir,
+                    PathBuf::from(""),
+                    PathBuf::from(""),
+                    PathBuf::from(""),
+                )),
+                AppKind::FIREFOX => Some(Self::firefox_based_app(
+                    app_id.clone(),
+                    config_dir,
+                    PathBuf::from(""),
+                    PathBuf::from(""),
+                    PathBuf::from(""),
+                )),
+                _ => None,
+            }
+        });
+
+        return match guessed_app_maybe {
+            Some(mut guessed_app) => {
+                guessed_app.restricted_url_matchers =
+                    Self::generate_restricted_hostname_matchers(restricted_domain_patterns);
+                guessed_app
+            }
+            None => Self::generic_app(app_id, restricted_domain_patterns.clone()),
+        };
+    }
+
+    // custom apps (see `CustomAppConfig`) are commands, so they are never looked up or guessed
+    pub fn generate_custom_app(
+        &self,
+        app_id_str: &str,
+        restricted_domain_patterns: &Vec<String>,
+    ) -> SupportedApp {
+        let app_id = AppIdentifier::new_for_os(app_id_str);
+        return Self::generic_app(app_id, restricted_domain_patterns.clone());
+    }
+
+    // Recognizes forks of Chromium and Firefox which are not in the repository (e.g Thorium, Floorp)
+    // by their config dir, which is usually named after the app.
+    // Guesses are logged, so they can be added to the repository.
+    pub fn guess_app_config(
+        &self,
+        app_id_str: &str,
+        executable_path: &Path,
+        display_name: &str,
+    ) -> Option<GuessedAppConfig> {
+        if self.supported_apps.contains_key(app_id_str) {
+            return None;
+        }
+
+        let config_dir_names = guess_config_dir_names(app_id_str, executable_path, display_name);
+
+        for config_dir_name in &config_dir_names {
+            // "~/.config/thorium", "~/Library/Application Support/Thorium" or
+            // "%LOCALAPPDATA%\Thorium\User Data"
+            let chromium_config_dir = self.chromium_user_dir_base.join(config_dir_name);
+            let chromium_dirs = [
+                chromium_config_dir.clone(),
+                chromium_config_dir.join("User Data"),
+            ];
+            for chromium_dir in chromium_dirs {
+                if chromium_profiles_parser::has_profiles_info_cache(chromium_dir.as_path()) {
+                    info!(
+                        "Guessed that {} is Chromium-based with config dir {}, consider adding it to application-repository.toml",
+                        app_id_str,
+                        chromium_dir.display()
+                    );
+                    return Some(GuessedAppConfig {
+                        kind: AppKind::CHROMIUM,
+                        config_dir: chromium_dir,
+                    });
+                }
+            }
+
+            // "~/.floorp", "~/Library/Application Support/Floorp" or "%APPDATA%\Floorp"
+            let firefox_dirs = [
+                self.firefox_user_dir_base
+                    .join(format!(".{}", config_dir_name)),
+                self.firefox_user_dir_base.join(config_dir_name),
+            ];
+            for firefox_dir in firefox_dirs {
+                if firefox_dir.join("profiles.ini").is_file() {
+                    info!(
+                        "Guessed that {} is Firefox-based with config dir {}, consider adding it to application-repository.toml",
+                        app_id_str,
+                        firefox_dir.display()
+                    );
+                    return Some(GuessedAppConfig {
+                        kind: AppKind::FIREFOX,
+                        config_dir: firefox_dir,
+                    });
+                }
+            }
+        }
+
+        return None;
     }
 
     fn add(&mut self, supported_app: SupportedApp) -> &mut SupportedAppRepository {
@@ -156,6 +281,7 @@ impl SupportedAppRepository {
             app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
             PathBuf::from(""),
+            PathBuf::from(""),
         );
         return app;
     }
@@ -176,6 +302,7 @@ impl SupportedAppRepository {
             app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
             PathBuf::from(""),
+            PathBuf::from(""),
         );
         return app;
     }
@@ -184,6 +311,7 @@ impl SupportedAppRepository {
         &mut self,
         linux_desktop_id: &str,
         linux_snap_id: &str,
+        linux_flatpak_id: &str,
         linux_config_dir_relative: &str,
     ) -> SupportedApp {
         let app_config_dir = AppConfigDir::new_linux(
@@ -194,11 +322,86 @@ impl SupportedAppRepository {
         let snap_app_config_dir_absolute =
             self.snap_config_dir_absolute_path(linux_snap_id, linux_config_dir_relative);
 
+        let flatpak_app_config_dir_absolute =
+            self.flatpak_config_dir_absolute_path(linux_flatpak_id, "", linux_config_dir_relative);
+
         let app_id = AppIdentifier::new_linux(linux_desktop_id);
         let app = Self::firefox_based_app(
             app_id,
             app_config_dir.config_dir_absolute(),
             snap_app_config_dir_absolute.clone(),
+            flatpak_app_config_dir_absolute.clone(),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    fn create_thunderbird_linux(
+        &mut self,
+        linux_desktop_id: &str,
+        linux_snap_id: &str,
+        linux_flatpak_id: &str,
+        linux_config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_linux(
+            self.firefox_user_dir_base.clone(),
+            PathBuf::from(linux_config_dir_relative),
+        );
+
+        let snap_app_config_dir_absolute =
+            self.snap_config_dir_absolute_path(linux_snap_id, linux_config_dir_relative);
+
+        let flatpak_app_config_dir_absolute =
+            self.flatpak_config_dir_absolute_path(linux_flatpak_id, "", linux_config_dir_relative);
+
+        let app_id = AppIdentifier::new_linux(linux_desktop_id);
+        let app = Self::thunderbird_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            snap_app_config_dir_absolute.clone(),
+            flatpak_app_config_dir_absolute.clone(),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    fn create_thunderbird_mac(
+        &mut self,
+        mac_bundle_id: &str,
+        mac_config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_mac(
+            self.firefox_user_dir_base.clone(),
+            PathBuf::from(mac_config_dir_relative),
+        );
+
+        let app_id = AppIdentifier::new_mac(mac_bundle_id);
+        let app = Self::thunderbird_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            PathBuf::from(""),
+            PathBuf::from(""),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    fn create_thunderbird_windows(
+        &mut self,
+        bundle_id: &str,
+        config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_windows(
+            self.firefox_user_dir_base.clone(),
+            PathBuf::from(config_dir_relative),
+        );
+
+        let app_id = AppIdentifier::new_windows(bundle_id);
+        let app = Self::thunderbird_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            PathBuf::from(""),
+            PathBuf::from(""),
             PathBuf::from(""),
         );
         return app;
@@ -216,40 +419,74 @@ impl SupportedAppRepository {
         let snap_id_owned = app_config.snap_id.unwrap_or_default();
         let linux_snap_id = snap_id_owned.as_str();
 
+        let flatpak_id_owned = app_config.flatpak_id.unwrap_or_default();
+        let linux_flatpak_id = flatpak_id_owned.as_str();
+
         let app = match app_config.kind {
             AppKind::GENERIC => {
                 let restricted_domain_patterns = vec![];
                 Self::create_generic_app(app_config.os, app_id, restricted_domain_patterns)
             }
             AppKind::CHROMIUM => match app_config.os {
-                AppOS::LINUX => {
-                    self.create_chromium_based_linux(app_id, linux_snap_id, config_dir_relative)
-                }
+                AppOS::LINUX => self.create_chromium_based_linux(
+                    app_id,
+                    linux_snap_id,
+                    linux_flatpak_id,
+                    config_dir_relative,
+                ),
                 AppOS::MAC => self.create_chromium_based_mac(app_id, config_dir_relative),
                 AppOS::WINDOWS => self.create_chromium_based_windows(app_id, config_dir_relative),
             },
             AppKind::FIREFOX => match app_config.os {
-                AppOS::LINUX => {
-                    self.create_firefox_based_linux(app_id, linux_snap_id, config_dir_relative)
-                }
+                AppOS::LINUX => self.create_firefox_based_linux(
+                    app_id,
+                    linux_snap_id,
+                    linux_flatpak_id,
+                    config_dir_relative,
+                ),
                 AppOS::MAC => self.create_firefox_based_mac(app_id, config_dir_relative),
                 AppOS::WINDOWS => self.create_firefox_based_windows(app_id, config_dir_relative),
             },
+            AppKind::EPIPHANY => match app_config.os {
+                AppOS::LINUX => {
+                    self.create_epiphany_linux(app_id, linux_flatpak_id, config_dir_relative)
+                }
+                _ => Self::create_generic_app(app_config.os, app_id, vec![]),
+            },
+            AppKind::FALKON => match app_config.os {
+                AppOS::LINUX => {
+                    self.create_falkon_linux(app_id, linux_flatpak_id, config_dir_relative)
+                }
+                _ => Self::create_generic_app(app_config.os, app_id, vec![]),
+            },
             AppKind::LINEAR => {
                 let restricted_domain_patterns = vec!["linear.app".to_string()];
                 Self::create_generic_app(app_config.os, app_id, restricted_domain_patterns)
             }
-            AppKind::MIMESTREAM => {
-                let restricted_domain_patterns = vec!["links.mimestream.com".to_string()];
-                Self::create_generic_app(app_config.os, app_id, restricted_domain_patterns)
-            }
+            AppKind::MIMESTREAM => Self::create_mail_app(
+                app_config.os,
+                app_id,
+                &mail_url_parser::MIMESTREAM,
+                convert_mimestream_uri,
+            ),
             AppKind::NOTION => {
                 let restricted_domain_patterns =
                     vec!["notion.so".to_string(), "www.notion.so".to_string()];
                 Self::create_generic_app(app_config.os, app_id, restricted_domain_patterns)
             }
+            AppKind::QUTEBROWSER => match app_config.os {
+                AppOS::LINUX => {
+                    self.create_qutebrowser_linux(app_id, linux_flatpak_id, config_dir_relative)
+                }
+                _ => Self::create_generic_app(app_config.os, app_id, vec![]),
+            },
             AppKind::SLACK => match app_config.os {
-                AppOS::LINUX => self.create_slack_linux(app_id, linux_snap_id, config_dir_relative),
+                AppOS::LINUX => self.create_slack_linux(
+                    app_id,
+                    linux_snap_id,
+                    linux_flatpak_id,
+                    config_dir_relative,
+                ),
                 AppOS::MAC => self.create_slack_mac(app_id, config_dir_relative),
                 AppOS::WINDOWS => self.create_slack_windows(app_id, config_dir_relative),
             },
@@ -266,6 +503,16 @@ impl SupportedAppRepository {
                 let restricted_domain_patterns = vec!["t.me".to_string()];
                 Self::create_generic_app(app_config.os, app_id, restricted_domain_patterns)
             }
+            AppKind::THUNDERBIRD => match app_config.os {
+                AppOS::LINUX => self.create_thunderbird_linux(
+                    app_id,
+                    linux_snap_id,
+                    linux_flatpak_id,
+                    config_dir_relative,
+                ),
+                AppOS::MAC => self.create_thunderbird_mac(app_id, config_dir_relative),
+                AppOS::WINDOWS => self.create_thunderbird_windows(app_id, config_dir_relative),
+            },
             AppKind::WORKFLOWY => {
                 let restricted_domain_patterns = vec!["workflowy.com".to_string()];
                 Self::create_generic_app_with_url(
@@ -294,10 +541,103 @@ impl SupportedAppRepository {
         return app;
     }
 
+    // qutebrowser profiles are basedirs in "~/.local/share/<config dir relative>"
+    fn create_qutebrowser_linux(
+        &mut self,
+        linux_desktop_id: &str,
+        linux_flatpak_id: &str,
+        linux_config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_linux(
+            self.linux_data_dir_base(),
+            PathBuf::from(linux_config_dir_relative),
+        );
+
+        let flatpak_app_config_dir_absolute = self.flatpak_config_dir_absolute_path(
+            linux_flatpak_id,
+            "data",
+            linux_config_dir_relative,
+        );
+
+        let app_id = AppIdentifier::new_linux(linux_desktop_id);
+        let app = Self::qutebrowser_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            PathBuf::from(""),
+            flatpak_app_config_dir_absolute.clone(),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    // GNOME Web default profile is "~/.local/share/<config dir relative>",
+    // its web app profiles are next to it
+    fn create_epiphany_linux(
+        &mut self,
+        linux_desktop_id: &str,
+        linux_flatpak_id: &str,
+        linux_config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_linux(
+            self.linux_data_dir_base(),
+            PathBuf::from(linux_config_dir_relative),
+        );
+
+        let flatpak_app_config_dir_absolute = self.flatpak_config_dir_absolute_path(
+            linux_flatpak_id,
+            "data",
+            linux_config_dir_relative,
+        );
+
+        let app_id = AppIdentifier::new_linux(linux_desktop_id);
+        let app = Self::epiphany_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            PathBuf::from(""),
+            flatpak_app_config_dir_absolute.clone(),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    fn create_falkon_linux(
+        &mut self,
+        linux_desktop_id: &str,
+        linux_flatpak_id: &str,
+        linux_config_dir_relative: &str,
+    ) -> SupportedApp {
+        let app_config_dir = AppConfigDir::new_linux(
+            self.chromium_user_dir_base.clone(),
+            PathBuf::from(linux_config_dir_relative),
+        );
+
+        let flatpak_app_config_dir_absolute = self.flatpak_config_dir_absolute_path(
+            linux_flatpak_id,
+            "config",
+            linux_config_dir_relative,
+        );
+
+        let app_id = AppIdentifier::new_linux(linux_desktop_id);
+        let app = Self::falkon_app(
+            app_id,
+            app_config_dir.config_dir_absolute(),
+            PathBuf::from(""),
+            flatpak_app_config_dir_absolute.clone(),
+            PathBuf::from(""),
+        );
+        return app;
+    }
+
+    // ~/.local/share
+    fn linux_data_dir_base(&self) -> PathBuf {
+        return self.firefox_user_dir_base.join(".local").join("share");
+    }
+
     fn create_slack_linux(
         &mut self,
         linux_desktop_id: &str,
         linux_snap_id: &str,
+        linux_flatpak_id: &str,
         linux_config_dir_relative: &str,
     ) -> SupportedApp {
         let app_config_dir = AppConfigDir::new_linux(
@@ -308,11 +648,18 @@ impl SupportedAppRepository {
         let snap_app_config_dir_absolute =
             self.snap_config_dir_absolute_path(linux_snap_id, linux_config_dir_relative);
 
+        let flatpak_app_config_dir_absolute = self.flatpak_config_dir_absolute_path(
+            linux_flatpak_id,
+            "config",
+            linux_config_dir_relative,
+        );
+
         let app_id = AppIdentifier::new_linux(linux_desktop_id);
         let app = Self::slack_app(
             app_id,
             app_config_dir.config_dir_absolute(),
             snap_app_config_dir_absolute.clone(),
+            flatpak_app_config_dir_absolute.clone(),
             PathBuf::from(""),
         );
 
@@ -347,6 +694,7 @@ impl SupportedAppRepository {
             app_id,
             unsandboxed_app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
+            PathBuf::from(""),
             sandboxed_app_config_dir.config_dir_absolute(),
         );
         return app;
@@ -364,6 +712,7 @@ impl SupportedAppRepository {
             app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
             PathBuf::from(""),
+            PathBuf::from(""),
         );
         return app;
     }
@@ -384,6 +733,7 @@ impl SupportedAppRepository {
             app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
             PathBuf::from(""),
+            PathBuf::from(""),
         );
         return app;
     }
@@ -404,6 +754,7 @@ impl SupportedAppRepository {
             app_config_dir.config_dir_absolute(),
             PathBuf::from(""),
             PathBuf::from(""),
+            PathBuf::from(""),
         );
         return app;
     }
@@ -412,6 +763,7 @@ impl SupportedAppRepository {
         &mut self,
         linux_desktop_id: &str,
         linux_snap_id: &str,
+        linux_flatpak_id: &str,
         linux_config_dir_relative: &str,
     ) -> SupportedApp {
         let app_config_dir = AppConfigDir::new_linux(
@@ -422,11 +774,18 @@ impl SupportedAppRepository {
         let snap_app_config_dir_absolute =
             self.snap_config_dir_absolute_path(linux_snap_id, linux_config_dir_relative);
 
+        let flatpak_app_config_dir_absolute = self.flatpak_config_dir_absolute_path(
+            linux_flatpak_id,
+            "config",
+            linux_config_dir_relative,
+        );
+
         let app_id = AppIdentifier::new_linux(linux_desktop_id);
         let app = Self::chromium_based_app(
             app_id,
             app_config_dir.config_dir_absolute(),
             snap_app_config_dir_absolute.clone(),
+            flatpak_app_config_dir_absolute.clone(),
             PathBuf::from(""),
         );
         return app;
@@ -437,6 +796,10 @@ impl SupportedAppRepository {
         snap_name: &str,
         linux_config_dir_relative: &str,
     ) -> PathBuf {
+        if snap_name.is_empty() {
+            return PathBuf::new();
+        }
+
         let snap_root_path = self.snap_base.clone();
         let snap_linux_config_dir_relative_path = PathBuf::from(snap_name)
             .join("common")
@@ -445,10 +808,31 @@ impl SupportedAppRepository {
         return config_dir_absolute;
     }
 
+    // "~/.var/app/<flatpak id>/<user dir relative>/<config dir relative>", where the user dir is
+    // "config" for apps which use $XDG_CONFIG_HOME (Chromium) and "" for apps which use $HOME (Firefox)
+    fn flatpak_config_dir_absolute_path(
+        &self,
+        flatpak_id: &str,
+        flatpak_user_dir_relative: &str,
+        linux_config_dir_relative: &str,
+    ) -> PathBuf {
+        if flatpak_id.is_empty() {
+            return PathBuf::new();
+        }
+
+        let config_dir_absolute = self
+            .flatpak_base
+            .join(flatpak_id)
+            .join(flatpak_user_dir_relative)
+            .join(linux_config_dir_relative);
+        return config_dir_absolute;
+    }
+
     fn chromium_based_app(
         app_id: AppIdentifier,
         app_config_dir_absolute: PathBuf,
         snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
         macos_sandbox_app_config_dir_absolute: PathBuf,
     ) -> SupportedApp {
         let chromium_url_transform_fn: UrlTransformFn = |_, url| url.to_string();
@@ -457,15 +841,30 @@ impl SupportedAppRepository {
             app_id: app_id,
             app_config_dir_absolute: app_config_dir_absolute,
             snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
             macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
             find_profiles_fn: Some(chromium_profiles_parser::find_chromium_profiles),
+            profiles_index_file: Some("Local State"),
+            sort_profiles_by_name: true,
             restricted_url_matchers: vec![],
             profile_args_fn: |profile_cli_arg_value| {
                 vec![format!("--profile-directory={}", profile_cli_arg_value)]
             },
-            incognito_args: vec!["--incognito".to_string()],
+            user_dir_args_fn: Some(|user_dir, _profile_cli_arg_value| {
+                vec![format!("--user-data-dir={}", user_dir)]
+            }),
+            launch_modes: vec![
+                SupportedLaunchMode::new(LaunchMode::Incognito, &["--incognito"]),
+                SupportedLaunchMode::new(LaunchMode::NewWindow, &["--new-window"]),
+                SupportedLaunchMode::with_url_args(LaunchMode::AppWindow, |app_url| {
+                    vec![format!("--app={}", app_url)]
+                }),
+                SupportedLaunchMode::new(LaunchMode::Guest, &["--guest"]),
+            ],
             url_transform_fn: chromium_url_transform_fn,
+            url_args_fn: |app_url| vec![app_url.to_string()],
             url_as_first_arg: true,
+            account_link_format: None,
         }
     }
 
@@ -473,6 +872,7 @@ impl SupportedAppRepository {
         app_id: AppIdentifier,
         app_config_dir_absolute: PathBuf,
         snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
         macos_sandbox_app_config_dir_absolute: PathBuf,
     ) -> SupportedApp {
         let firefox_url_transform_fn: UrlTransformFn = |common_browser_profile, url| {
@@ -495,15 +895,199 @@ impl SupportedAppRepository {
             app_id: app_id,
             app_config_dir_absolute: app_config_dir_absolute,
             snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
             macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
             find_profiles_fn: Some(firefox_profiles_parser::find_firefox_profiles),
+            profiles_index_file: Some("profiles.ini"),
+            sort_profiles_by_name: true,
             restricted_url_matchers: vec![],
             profile_args_fn: |profile_cli_arg_value| {
-                vec!["-P".to_string(), profile_cli_arg_value.to_string()]
+                // profiles from profile groups are not in profiles.ini, so they are given by path
+                if profile_cli_arg_value.is_empty() {
+                    vec![]
+                } else if Path::new(profile_cli_arg_value).is_absolute() {
+                    vec!["--profile".to_string(), profile_cli_arg_value.to_string()]
+                } else {
+                    vec!["-P".to_string(), profile_cli_arg_value.to_string()]
+                }
             },
-            incognito_args: vec!["--private-window".to_string()],
+            // profiles are launched by path, which already selects the profiles root;
+            // without profiles (placeholder), the profiles root itself is used as the profile
+            user_dir_args_fn: Some(|user_dir, profile_cli_arg_value| {
+                if profile_cli_arg_value.is_empty() {
+                    vec!["--profile".to_string(), user_dir.to_string()]
+                } else {
+                    vec![]
+                }
+            }),
+            launch_modes: vec![
+                SupportedLaunchMode::new(LaunchMode::Incognito, &["--private-window"]),
+                // the url must come right after these, so they are given with the url
+                SupportedLaunchMode::with_url_args(LaunchMode::NewWindow, |app_url| {
+                    vec!["-new-window".to_string(), app_url.to_string()]
+                }),
+                SupportedLaunchMode::with_url_args(LaunchMode::NewTab, |app_url| {
+                    vec!["-new-tab".to_string(), app_url.to_string()]
+                }),
+            ],
             url_transform_fn: firefox_url_transform_fn,
+            url_args_fn: |app_url| vec![app_url.to_string()],
+            url_as_first_arg: true,
+            account_link_format: None,
+        }
+    }
+
+    fn thunderbird_app(
+        app_id: AppIdentifier,
+        app_config_dir_absolute: PathBuf,
+        snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
+        macos_sandbox_app_config_dir_absolute: PathBuf,
+    ) -> SupportedApp {
+        // only offer Thunderbird for mailto: links
+        let restricted_url_patterns = vec!["mailto://**".to_string()];
+        let restricted_url_matchers =
+            Self::generate_restricted_hostname_matchers(&restricted_url_patterns);
+
+        SupportedApp {
+            app_id: app_id,
+            app_config_dir_absolute: app_config_dir_absolute,
+            snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
+            macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
+            find_profiles_fn: Some(thunderbird_profiles_parser::find_thunderbird_profiles),
+            profiles_index_file: Some("profiles.ini"),
+            sort_profiles_by_name: true,
+            restricted_url_matchers: restricted_url_matchers,
+            profile_args_fn: |profile_cli_arg_value| {
+                vec!["-P".to_string(), profile_cli_arg_value.to_string()]
+            },
+            user_dir_args_fn: None,
+            launch_modes: vec![],
+            url_transform_fn: convert_mailto_compose_fields,
+            url_args_fn: |app_url| vec!["-compose".to_string(), app_url.to_string()],
+            url_as_first_arg: true,
+            account_link_format: None,
+        }
+    }
+
+    fn qutebrowser_app(
+        app_id: AppIdentifier,
+        app_config_dir_absolute: PathBuf,
+        snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
+        macos_sandbox_app_config_dir_absolute: PathBuf,
+    ) -> SupportedApp {
+        SupportedApp {
+            app_id: app_id,
+            app_config_dir_absolute: app_config_dir_absolute,
+            snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
+            macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
+            find_profiles_fn: Some(qutebrowser_profiles_parser::find_qutebrowser_profiles),
+            profiles_index_file: None,
+            sort_profiles_by_name: true,
+            restricted_url_matchers: vec![],
+            profile_args_fn: |profile_cli_arg_value| {
+                // default instance has no basedir
+                if profile_cli_arg_value.is_empty() {
+                    vec![]
+                } else {
+                    vec!["--basedir".to_string(), profile_cli_arg_value.to_string()]
+                }
+            },
+            user_dir_args_fn: None,
+            launch_modes: vec![
+                SupportedLaunchMode::new(LaunchMode::Incognito, &["--target", "private-window"]),
+                SupportedLaunchMode::new(LaunchMode::NewWindow, &["--target", "window"]),
+                SupportedLaunchMode::new(LaunchMode::NewTab, &["--target", "tab"]),
+            ],
+            url_transform_fn: |_, url| url.to_string(),
+            url_args_fn: |app_url| vec![app_url.to_string()],
+            url_as_first_arg: true,
+            account_link_format: None,
+        }
+    }
+
+    fn epiphany_app(
+        app_id: AppIdentifier,
+        app_config_dir_absolute: PathBuf,
+        snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
+        macos_sandbox_app_config_dir_absolute: PathBuf,
+    ) -> SupportedApp {
+        SupportedApp {
+            app_id: app_id,
+            app_config_dir_absolute: app_config_dir_absolute,
+            snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
+            macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
+            find_profiles_fn: Some(epiphany_profiles_parser::find_epiphany_profiles),
+            profiles_index_file: None,
+            sort_profiles_by_name: true,
+            restricted_url_matchers: vec![],
+            profile_args_fn: |profile_cli_arg_value| {
+                let profile_dir = Path::new(profile_cli_arg_value);
+                // default profile is used without `--profile`
+                if profile_cli_arg_value.is_empty() {
+                    vec![]
+                } else if epiphany_profiles_parser::is_web_app_profile_dir(profile_dir) {
+                    vec![
+                        "--application-mode".to_string(),
+                        format!("--profile={}", profile_cli_arg_value),
+                    ]
+                } else {
+                    vec![format!("--profile={}", profile_cli_arg_value)]
+                }
+            },
+            user_dir_args_fn: None,
+            launch_modes: vec![
+                SupportedLaunchMode::new(LaunchMode::Incognito, &["--incognito-mode"]),
+                SupportedLaunchMode::new(LaunchMode::NewWindow, &["--new-window"]),
+                SupportedLaunchMode::new(LaunchMode::NewTab, &["--new-tab"]),
+            ],
+            url_transform_fn: |_, url| url.to_string(),
+            url_args_fn: |app_url| vec![app_url.to_string()],
             url_as_first_arg: true,
+            account_link_format: None,
+        }
+    }
+
+    fn falkon_app(
+        app_id: AppIdentifier,
+        app_config_dir_absolute: PathBuf,
+        snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
+        macos_sandbox_app_config_dir_absolute: PathBuf,
+    ) -> SupportedApp {
+        SupportedApp {
+            app_id: app_id,
+            app_config_dir_absolute: app_config_dir_absolute,
+            snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
+            macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
+            find_profiles_fn: Some(falkon_profiles_parser::find_falkon_profiles),
+            profiles_index_file: Some("profiles.ini"),
+            sort_profiles_by_name: false,
+            restricted_url_matchers: vec![],
+            profile_args_fn: |profile_cli_arg_value| {
+                // placeholder profile starts the profile from profiles.ini
+                if profile_cli_arg_value.is_empty() {
+                    vec![]
+                } else {
+                    vec!["-p".to_string(), profile_cli_arg_value.to_string()]
+                }
+            },
+            user_dir_args_fn: None,
+            launch_modes: vec![
+                SupportedLaunchMode::new(LaunchMode::Incognito, &["--private-browsing"]),
+                SupportedLaunchMode::new(LaunchMode::NewWindow, &["--new-window"]),
+                SupportedLaunchMode::new(LaunchMode::NewTab, &["--new-tab"]),
+            ],
+            url_transform_fn: |_, url| url.to_string(),
+            url_args_fn: |app_url| vec![app_url.to_string()],
+            url_as_first_arg: true,
+            account_link_format: None,
         }
     }
 
@@ -536,6 +1120,25 @@ impl SupportedAppRepository {
         return app;
     }
 
+    // Mail client which scopes its links to an account; its accounts are not stored in
+    // a documented place, so they come from the config (see `find_mail_account_profiles`)
+    fn create_mail_app(
+        os: AppOS,
+        app_id: &str,
+        link_format: &'static AccountLinkFormat,
+        url_transform_fn: UrlTransformFn,
+    ) -> SupportedApp {
+        let restricted_domain_patterns = vec![link_format.web_host.to_string()];
+        let mut app = Self::create_generic_app_with_url(
+            os,
+            app_id,
+            restricted_domain_patterns,
+            url_transform_fn,
+        );
+        app.account_link_format = Some(link_format);
+        return app;
+    }
+
     fn create_app_identifier(os: AppOS, app_id: &str) -> AppIdentifier {
         match os {
             AppOS::LINUX => AppIdentifier::new_linux(app_id),
@@ -560,13 +1163,19 @@ impl SupportedAppRepository {
             app_id: app_id,
             app_config_dir_absolute: PathBuf::new(),
             snap_app_config_dir_absolute: PathBuf::new(),
+            flatpak_app_config_dir_absolute: PathBuf::new(),
             macos_sandbox_app_config_dir_absolute: PathBuf::new(),
             find_profiles_fn: None,
+            profiles_index_file: None,
+            sort_profiles_by_name: true,
             restricted_url_matchers: restricted_url_matchers,
             profile_args_fn: |_profile_cli_arg_value| vec![],
-            incognito_args: vec![],
+            user_dir_args_fn: None,
+            launch_modes: vec![],
             url_transform_fn: url_transform_fn,
+            url_args_fn: |app_url| vec![app_url.to_string()],
             url_as_first_arg: false,
+            account_link_format: None,
         }
     }
 
@@ -589,12 +1198,14 @@ impl SupportedAppRepository {
         app_id: AppIdentifier,
         app_config_dir_absolute: PathBuf,
         snap_app_config_dir_absolute: PathBuf,
+        flatpak_app_config_dir_absolute: PathBuf,
         macos_sandbox_app_config_dir_absolute: PathBuf,
     ) -> SupportedApp {
         // todo: filter only specific profiles? But per profile?
         let restricted_domain_patterns = vec![
             "*.slack.com".to_string(),
             "*.enterprise.slack.com".to_string(),
+            "*.slack-gov.com".to_string(),
         ];
         let restricted_url_matchers =
             Self::generate_restricted_hostname_matchers(&restricted_domain_patterns);
@@ -603,13 +1214,19 @@ impl SupportedAppRepository {
             app_id: app_id,
             app_config_dir_absolute: app_config_dir_absolute,
             snap_app_config_dir_absolute: snap_app_config_dir_absolute,
+            flatpak_app_config_dir_absolute: flatpak_app_config_dir_absolute,
             macos_sandbox_app_config_dir_absolute: macos_sandbox_app_config_dir_absolute,
             find_profiles_fn: Some(slack_profiles_parser::find_slack_profiles),
+            profiles_index_file: Some("storage/root-state.json"),
+            sort_profiles_by_name: true,
             restricted_url_matchers: restricted_url_matchers,
             profile_args_fn: |_profile_cli_arg_value| vec![],
-            incognito_args: vec![],
+            user_dir_args_fn: None,
+            launch_modes: vec![],
             url_transform_fn: convert_slack_uri,
+            url_args_fn: |app_url| vec![app_url.to_string()],
             url_as_first_arg: false,
+            account_link_format: None,
         }
     }
 }
@@ -619,23 +1236,66 @@ pub struct SupportedApp {
     app_id: AppIdentifier,
     app_config_dir_absolute: PathBuf,
     snap_app_config_dir_absolute: PathBuf,
+    flatpak_app_config_dir_absolute: PathBuf,
     macos_sandbox_app_config_dir_absolute: PathBuf,
     restricted_url_matchers: Vec<UrlGlobMatcher>,
-    find_profiles_fn: Option<
-        fn(
-            app_config_dir_absolute: &Path,
-            binary_path: &Path,
-            app_id: &str,
-        ) -> Vec<InstalledBrowserProfile>,
-    >,
+    find_profiles_fn: Option<FindProfilesFn>,
+    // file in the app config dir which lists the profiles, e.g "Local State"
+    profiles_index_file: Option<&'static str>,
+    // false if the profiles are already in the app's own order, e.g the default profile first
+    sort_profiles_by_name: bool,
     profile_args_fn: fn(profile_cli_arg_value: &str) -> Vec<String>,
-    incognito_args: Vec<String>,
+    // arguments which make the app use another user dir (see `AppInstanceConfig`),
+    // None if the app doesn't support it
+    user_dir_args_fn: Option<fn(user_dir: &str, profile_cli_arg_value: &str) -> Vec<String>>,
+    // e.g incognito or new window, besides opening the link the default way
+    launch_modes: Vec<SupportedLaunchMode>,
     url_transform_fn: UrlTransformFn,
+    // arguments which pass the (transformed) url to the app
+    url_args_fn: fn(app_url: &str) -> Vec<String>,
     url_as_first_arg: bool,
+    // mail apps which scope their links to an account, so that the configured accounts
+    // (see `MailAccountConfig`) are shown as profiles; None for other apps
+    account_link_format: Option<&'static AccountLinkFormat>,
+}
+
+#[derive(Clone)]
+struct SupportedLaunchMode {
+    launch_mode: LaunchMode,
+    args: Vec<String>,
+    // replaces the url args, when the url is part of the launch mode arguments, e.g `--app=<url>`
+    url_args_fn: Option<fn(app_url: &str) -> Vec<String>>,
+}
+
+impl SupportedLaunchMode {
+    fn new(launch_mode: LaunchMode, args: &[&str]) -> Self {
+        Self {
+            launch_mode: launch_mode,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            url_args_fn: None,
+        }
+    }
+
+    fn with_url_args(
+        launch_mode: LaunchMode,
+        url_args_fn: fn(app_url: &str) -> Vec<String>,
+    ) -> Self {
+        Self {
+            launch_mode: launch_mode,
+            args: vec![],
+            url_args_fn: Some(url_args_fn),
+        }
+    }
 }
 
 pub type UrlTransformFn = fn(&CommonBrowserProfile, url: &str) -> String;
 
+type FindProfilesFn = fn(
+    app_config_dir_absolute: &Path,
+    binary_path: &Path,
+    app_id: &str,
+) -> Vec<InstalledBrowserProfile>;
+
 //
 // profile_cli_arg_value: workspace.id.to_string(),
 // profile_cli_container_name: Some(workspace.domain.to_string()),
@@ -650,9 +1310,9 @@ impl SupportedApp {
         return self.app_id.app_id();
     }
 
-    pub fn get_app_config_dir_abs(&self, is_snap: bool, is_macos_sandbox: bool) -> &Path {
-        return if is_snap {
-            &self.snap_app_config_dir_absolute.as_path()
+    pub fn get_app_config_dir_abs(&self, is_flatpak: bool, is_macos_sandbox: bool) -> &Path {
+        return if is_flatpak {
+            &self.flatpak_app_config_dir_absolute.as_path()
         } else if is_macos_sandbox {
             &self.macos_sandbox_app_config_dir_absolute.as_path()
         } else {
@@ -660,6 +1320,71 @@ impl SupportedApp {
         };
     }
 
+    // It's not always possible to tell from the desktop entry if the app is a snap,
+    // so both snap dir "~/snap/<snap instance name>/common/..." and the native dir are tried;
+    // the first one which has profiles wins (detected snaps try the snap dir first)
+    pub fn find_snap_or_native_app_config_dir_abs(
+        &self,
+        snap_root: &Path,
+        snap_instance_name_maybe: Option<&str>,
+    ) -> PathBuf {
+        let native_dir = self.app_config_dir_absolute.clone();
+        let snap_dir_maybe = snap_instance_name_maybe
+            .map(|snap_instance_name| {
+                self.snap_instance_app_config_dir_abs(snap_root, snap_instance_name)
+            })
+            .unwrap_or_else(|| Some(self.snap_app_config_dir_absolute.clone()))
+            .filter(|snap_dir| !snap_dir.as_os_str().is_empty());
+
+        let mut candidate_dirs: Vec<PathBuf> = Vec::new();
+        if snap_instance_name_maybe.is_some() {
+            candidate_dirs.extend(snap_dir_maybe);
+            candidate_dirs.push(native_dir);
+        } else {
+            candidate_dirs.push(native_dir);
+            candidate_dirs.extend(snap_dir_maybe);
+        }
+
+        let dir_with_profiles_maybe = candidate_dirs
+            .iter()
+            .find(|candidate_dir| self.has_profiles_index(candidate_dir.as_path()))
+            .cloned();
+
+        return dir_with_profiles_maybe.unwrap_or_else(|| candidate_dirs.remove(0));
+    }
+
+    // snap dir of a parallel install, e.g "~/snap/firefox_work/common/.mozilla/firefox"
+    // for configured "~/snap/firefox/common/.mozilla/firefox"
+    fn snap_instance_app_config_dir_abs(
+        &self,
+        snap_root: &Path,
+        snap_instance_name: &str,
+    ) -> Option<PathBuf> {
+        let snap_dir_relative = self
+            .snap_app_config_dir_absolute
+            .strip_prefix(snap_root)
+            .ok()?;
+
+        // skip the configured snap name
+        let mut components = snap_dir_relative.components();
+        components.next()?;
+
+        return Some(
+            snap_root
+                .join(snap_instance_name)
+                .join(components.as_path()),
+        );
+    }
+
+    fn has_profiles_index(&self, app_config_dir_abs: &Path) -> bool {
+        return self
+            .profiles_index_file
+            .map(|profiles_index_file| app_config_dir_abs.join(profiles_index_file))
+            .and_then(|profiles_index_path| fs::metadata(profiles_index_path).ok())
+            .map(|metadata| metadata.is_file() && metadata.len() > 0)
+            .unwrap_or(false);
+    }
+
     pub fn get_restricted_hostname_matchers(&self) -> &Vec<UrlGlobMatcher> {
         return &self.restricted_url_matchers;
     }
@@ -668,12 +1393,60 @@ impl SupportedApp {
         &self,
         binary_path: &Path,
         app_config_dir_abs: &Path,
+    ) -> InstalledAppProfiles {
+        return self.find_profiles_with_icons_dir_name(
+            binary_path,
+            app_config_dir_abs,
+            self.get_app_id(),
+        );
+    }
+
+    // profiles of an app instance with its own user dir; profile icons are kept apart from the
+    // app's own, as both can have profiles with the same dir name (e.g "Default")
+    pub fn find_app_instance_profiles(
+        &self,
+        binary_path: &Path,
+        user_dir: &Path,
+        instance_name: &str,
+    ) -> InstalledAppProfiles {
+        let instance_name: String = instance_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let icons_dir_name = format!("{}-{}", self.get_app_id(), instance_name);
+        return self.find_profiles_with_icons_dir_name(binary_path, user_dir, &icons_dir_name);
+    }
+
+    // profiles of a mail app for its configured accounts (see `MailAccountConfig`),
+    // None if the app doesn't scope its links to an account
+    pub fn find_mail_account_profiles(&self, accounts: &[String]) -> Option<InstalledAppProfiles> {
+        let link_format = self.account_link_format?;
+        let mut browser_profiles =
+            mail_profiles_parser::find_mail_account_profiles(link_format, accounts);
+
+        if self.sort_profiles_by_name {
+            browser_profiles.sort_by_key(|p| p.profile_name.clone());
+        }
+        return if browser_profiles.is_empty() {
+            Some(InstalledAppProfiles::new_placeholder())
+        } else {
+            Some(InstalledAppProfiles::new_real(browser_profiles))
+        };
+    }
+
+    fn find_profiles_with_icons_dir_name(
+        &self,
+        binary_path: &Path,
+        app_config_dir_abs: &Path,
+        icons_dir_name: &str,
     ) -> InstalledAppProfiles {
         return if let Some(find_profiles_fn) = self.find_profiles_fn {
             let mut browser_profiles: Vec<InstalledBrowserProfile> =
-                find_profiles_fn(app_config_dir_abs, binary_path, self.get_app_id());
+                find_profiles_fn(app_config_dir_abs, binary_path, icons_dir_name);
 
-            browser_profiles.sort_by_key(|p| p.profile_name.clone());
+            if self.sort_profiles_by_name {
+                browser_profiles.sort_by_key(|p| p.profile_name.clone());
+            }
             if browser_profiles.is_empty() {
                 InstalledAppProfiles::new_placeholder()
             } else {
@@ -688,12 +1461,61 @@ impl SupportedApp {
         return (self.profile_args_fn)(profile_cli_arg_value);
     }
 
+    pub fn supports_custom_user_dir(&self) -> bool {
+        return self.user_dir_args_fn.is_some();
+    }
+
+    pub fn get_user_dir_args(&self, user_dir: &str, profile_cli_arg_value: &str) -> Vec<String> {
+        return self
+            .user_dir_args_fn
+            .map(|user_dir_args_fn| user_dir_args_fn(user_dir, profile_cli_arg_value))
+            .unwrap_or_default();
+    }
+
     pub fn supports_incognito(&self) -> bool {
-        return !self.incognito_args.is_empty();
+        return self.supports_launch_mode(LaunchMode::Incognito);
+    }
+
+    pub fn supports_launch_mode(&self, launch_mode: LaunchMode) -> bool {
+        return self.find_launch_mode(launch_mode).is_some();
+    }
+
+    // launch modes the app itself declares (e.g Desktop Actions in Linux),
+    // used only for the ones which are not in the repository
+    pub fn add_missing_launch_modes(&mut self, launch_modes: &[(LaunchMode, Vec<String>)]) {
+        for (launch_mode, args) in launch_modes {
+            if self.supports_launch_mode(*launch_mode) {
+                continue;
+            }
+            self.launch_modes.push(SupportedLaunchMode {
+                launch_mode: *launch_mode,
+                args: args.clone(),
+                url_args_fn: None,
+            });
+        }
+    }
+
+    pub fn get_launch_modes(&self) -> Vec<LaunchMode> {
+        return self
+            .launch_modes
+            .iter()
+            .map(|supported_launch_mode| supported_launch_mode.launch_mode)
+            .collect();
+    }
+
+    // arguments of the launch mode, empty if the app doesn't support it
+    pub fn get_launch_mode_args(&self, launch_mode_maybe: Option<LaunchMode>) -> Vec<String> {
+        return launch_mode_maybe
+            .and_then(|launch_mode| self.find_launch_mode(launch_mode))
+            .map(|supported_launch_mode| supported_launch_mode.args.clone())
+            .unwrap_or_default();
     }
 
-    pub fn get_incognito_args(&self) -> &Vec<String> {
-        return &self.incognito_args;
+    fn find_launch_mode(&self, launch_mode: LaunchMode) -> Option<&SupportedLaunchMode> {
+        return self
+            .launch_modes
+            .iter()
+            .find(|supported_launch_mode| supported_launch_mode.launch_mode == launch_mode);
     }
 
     pub fn get_transformed_url(
@@ -704,6 +1526,22 @@ impl SupportedApp {
         return (self.url_transform_fn)(common_browser_profile, url);
     }
 
+    pub fn get_url_args(
+        &self,
+        app_url: &str,
+        launch_mode_maybe: Option<LaunchMode>,
+    ) -> Vec<String> {
+        let launch_mode_url_args_fn_maybe = launch_mode_maybe
+            .and_then(|launch_mode| self.find_launch_mode(launch_mode))
+            .and_then(|supported_launch_mode| supported_launch_mode.url_args_fn);
+
+        return if let Some(launch_mode_url_args_fn) = launch_mode_url_args_fn_maybe {
+            launch_mode_url_args_fn(app_url)
+        } else {
+            (self.url_args_fn)(app_url)
+        };
+    }
+
     pub fn is_url_as_first_arg(&self) -> bool {
         return self.url_as_first_arg;
     }
@@ -823,19 +1661,73 @@ impl AppConfigDir {
     }
 }
 
-// TODO: support
-// "https://links.mimestream.com/g/madis@qminderapp.com/t/18f06bace4319301"
-// "mimestream:///open/g/madis@qminderapp.com/t/18f06bace4319301"
-// "https://links.mimestream.com/LINK" to "mimestream:///open/LINK"
-fn convert_mimestream_uri(_: &CommonBrowserProfile, url_str: &str) -> String {
+// Possible config dir names of an unknown app, based on its id, executable and display name, e.g
+// "thorium-browser", "/usr/bin/thorium-browser" and "Thorium" to
+// ["thorium-browser", "thorium", "Thorium"]
+fn guess_config_dir_names(
+    app_id_str: &str,
+    executable_path: &Path,
+    display_name: &str,
+) -> Vec<String> {
+    // reverse domain name ids, e.g "org.mozilla.floorp" to "floorp"
+    let app_name = app_id_str.rsplit('.').next().unwrap_or(app_id_str);
+    // "unknown" is used when the executable path of an installed app could not be found
+    let executable_name = executable_path
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .filter(|_| executable_path != Path::new("unknown"))
+        .unwrap_or("");
+
+    let mut config_dir_names: Vec<String> = Vec::new();
+    for name in [executable_name, app_name, display_name] {
+        let name = name.trim();
+        let name_without_suffix = ["-browser", " browser", "_browser"]
+            .iter()
+            .find_map(|suffix| {
+                let suffix_start = name.len().checked_sub(suffix.len())?;
+                let name_suffix = name.get(suffix_start..)?;
+                name_suffix
+                    .eq_ignore_ascii_case(suffix)
+                    .then(|| name[..suffix_start].to_string())
+            })
+            .unwrap_or_else(|| name.to_string());
+
+        let candidates = [
+            name.to_string(),
+            name.to_lowercase(),
+            name.to_lowercase().replace(' ', "-"),
+            name_without_suffix.clone(),
+            name_without_suffix.to_lowercase(),
+        ];
+        for candidate in candidates {
+            let is_valid = !candidate.is_empty()
+                && !candidate.starts_with('.')
+                && !candidate.contains(['/', '\\']);
+            if is_valid && !config_dir_names.contains(&candidate) {
+                config_dir_names.push(candidate);
+            }
+        }
+    }
+
+    return config_dir_names;
+}
+
+// The profile is a configured account (see `MailAccountConfig`), or the placeholder profile
+// with an empty account; links without an account are opened in the profile's account
+fn convert_mimestream_uri(common_browser_profile: &CommonBrowserProfile, url_str: &str) -> String {
     let result = Url::parse(url_str);
     if result.is_err() {
         return "".to_string();
     }
-    let mut url = result.unwrap();
-    let _ = url.set_scheme("mimestream");
-
-    return url.as_str().to_string();
+    let url = result.unwrap();
+    let profile_account: &str = &common_browser_profile.profile_cli_arg_value;
+
+    return mail_url_parser::convert_account_uri(
+        &mail_url_parser::MIMESTREAM,
+        profile_account,
+        &url,
+    )
+    .unwrap_or_else(|| url_str.to_string());
 }
 
 fn convert_slack_uri(common_browser_profile: &CommonBrowserProfile, url_str: &str) -> String {
@@ -852,7 +1744,28 @@ fn convert_slack_uri(common_browser_profile: &CommonBrowserProfile, url_str: &st
     }
     let url = result.unwrap();
 
-    return slack_url_parser::convert_slack_uri(profile_team_id, profile_team_domain, &url);
+    let profile_enterprise_domain_maybe = common_browser_profile
+        .profile_enterprise_domain
+        .as_ref()
+        .map(|enterprise_domain| enterprise_domain.as_str());
+
+    return slack_url_parser::convert_slack_uri(
+        profile_team_id,
+        profile_team_domain,
+        profile_enterprise_domain_maybe,
+        &url,
+    );
+}
+
+// "mailto:madis@qminderapp.com?subject=Hi" to "to='madis@qminderapp.com',subject='Hi'"
+fn convert_mailto_compose_fields(_: &CommonBrowserProfile, url_str: &str) -> String {
+    let result = Url::parse(url_str);
+    if result.is_err() {
+        return "".to_string();
+    }
+    let url = result.unwrap();
+
+    return mail_url_parser::to_compose_fields(&url).unwrap_or_else(|| "".to_string());
 }
 
 fn convert_workflowy_uri(_: &CommonBrowserProfile, url_str: &str) -> String {
@@ -894,3 +1807,525 @@ fn convert_spotify_uri(_: &CommonBrowserProfile, url_str: &str) -> String {
 
     return uri_maybe.unwrap_or_else(|| unknown);
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{test_utils, InstalledAppProfilesType};
+
+    // fixture home dir with flatpak apps in "<home>/.var/app",
+    // which is removed when the returned TempDir is dropped
+    fn create_repository(test_name: &str) -> (TempDir, SupportedAppRepository, PathBuf) {
+        let temp_dir =
+            test_utils::create_temp_dir(format!("browser-repository-{}", test_name).as_str());
+        let home_dir = temp_dir.path().to_path_buf();
+
+        let repository = SupportedAppRepository {
+            snap_base: home_dir.join("snap"),
+            flatpak_base: home_dir.join(".var").join("app"),
+            chromium_user_dir_base: home_dir.join(".config"),
+            firefox_user_dir_base: home_dir.clone(),
+            supported_apps: HashMap::new(),
+        };
+        return (temp_dir, repository, home_dir);
+    }
+
+    fn flatpak_app_config(kind: AppKind, flatpak_id: &str, config_dir_relative: &str) -> AppConfig {
+        return AppConfig {
+            os: AppOS::LINUX,
+            kind: kind,
+            id: flatpak_id.to_string(),
+            config_dir_relative: config_dir_relative.to_string(),
+            snap_id: None,
+            flatpak_id: Some(flatpak_id.to_string()),
+        };
+    }
+
+    #[test]
+    fn test_flatpak_chromium_profiles() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("chromium");
+        let chrome_dir = home_dir.join(".var/app/com.google.Chrome/config/google-chrome");
+        fs::create_dir_all(chrome_dir.as_path()).unwrap();
+        fs::write(
+            chrome_dir.join("Local State"),
+            r#"{"profile": {"info_cache": {
+                "Default": {"name": "Personal"},
+                "Profile 1": {"name": "Work"}
+            }}}"#,
+        )
+        .unwrap();
+
+        let app_config =
+            flatpak_app_config(AppKind::CHROMIUM, "com.google.Chrome", "google-chrome");
+        let app = repository.create_app_from_app_config(app_config);
+
+        let app_config_dir_abs = app.get_app_config_dir_abs(true, false);
+        assert_eq!(app_config_dir_abs, chrome_dir.as_path());
+
+        let profiles = app.find_profiles(Path::new("com.google.Chrome"), app_config_dir_abs);
+        assert_eq!(profiles.profiles_type, InstalledAppProfilesType::RealProfiles);
+        assert_eq!(profiles.profiles.len(), 2);
+        assert_eq!(profiles.profiles[0].profile_name, "Personal");
+        assert_eq!(profiles.profiles[1].profile_name, "Work");
+        assert_eq!(
+            app.get_profile_args(profiles.profiles[1].profile_cli_arg_value.as_str()),
+            vec!["--profile-directory=Profile 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_flatpak_firefox_profiles() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("firefox");
+        let firefox_dir = home_dir.join(".var/app/org.mozilla.firefox/.mozilla/firefox");
+        fs::create_dir_all(firefox_dir.join("abc.default-release")).unwrap();
+        fs::write(
+            firefox_dir.join("profiles.ini"),
+            "[Profile0]\nName=default-release\nIsRelative=1\nPath=abc.default-release\nDefault=1\n",
+        )
+        .unwrap();
+
+        let app_config =
+            flatpak_app_config(AppKind::FIREFOX, "org.mozilla.firefox", ".mozilla/firefox");
+        let app = repository.create_app_from_app_config(app_config);
+
+        let app_config_dir_abs = app.get_app_config_dir_abs(true, false);
+        assert_eq!(app_config_dir_abs, firefox_dir.as_path());
+
+        let profiles = app.find_profiles(Path::new("org.mozilla.firefox"), app_config_dir_abs);
+        assert_eq!(profiles.profiles_type, InstalledAppProfilesType::RealProfiles);
+        assert_eq!(profiles.profiles.len(), 1);
+        assert_eq!(profiles.profiles[0].profile_name, "default-release");
+        assert_eq!(
+            app.get_profile_args(profiles.profiles[0].profile_cli_arg_value.as_str()),
+            vec![
+                "--profile".to_string(),
+                firefox_dir
+                    .join("abc.default-release")
+                    .display()
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_flatpak_id() {
+        let (_temp_dir, mut repository, _) = create_repository("without-flatpak-id");
+        let mut app_config =
+            flatpak_app_config(AppKind::CHROMIUM, "google-chrome", "google-chrome");
+        app_config.flatpak_id = None;
+        let app = repository.create_app_from_app_config(app_config);
+
+        assert_eq!(app.get_app_config_dir_abs(true, false), Path::new(""));
+    }
+
+    #[test]
+    fn test_chromium_app_instance_profiles() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("chromium-instance");
+        let user_data_dir = home_dir.join("chrome-acme");
+        fs::create_dir_all(user_data_dir.as_path()).unwrap();
+        fs::write(
+            user_data_dir.join("Local State"),
+            r#"{"profile": {"info_cache": {"Default": {"name": "Acme"}}}}"#,
+        )
+        .unwrap();
+
+        let app_config =
+            flatpak_app_config(AppKind::CHROMIUM, "com.google.Chrome", "google-chrome");
+        let app = repository.create_app_from_app_config(app_config);
+        assert!(app.supports_custom_user_dir());
+
+        let profiles = app.find_app_instance_profiles(
+            Path::new("/usr/bin/google-chrome"),
+            user_data_dir.as_path(),
+            "Acme",
+        );
+        assert_eq!(profiles.profiles_type, InstalledAppProfilesType::RealProfiles);
+        assert_eq!(profiles.profiles.len(), 1);
+        assert_eq!(profiles.profiles[0].profile_name, "Acme");
+        let user_data_dir = user_data_dir.to_str().unwrap();
+        assert_eq!(
+            app.get_user_dir_args(user_data_dir, "Default"),
+            vec![format!("--user-data-dir={}", user_data_dir)]
+        );
+    }
+
+    #[test]
+    fn test_app_instance_user_dir_args() {
+        let (_temp_dir, mut repository, _) = create_repository("instance-args");
+        let firefox_app = repository.create_app_from_app_config(flatpak_app_config(
+            AppKind::FIREFOX,
+            "org.mozilla.firefox",
+            ".mozilla/firefox",
+        ));
+        assert!(firefox_app.supports_custom_user_dir());
+        let profile_dir = "/home/madis/acme/abcd1234.default";
+        assert!(
+            firefox_app
+                .get_user_dir_args("/home/madis/acme", profile_dir)
+                .is_empty()
+        );
+        assert_eq!(
+            firefox_app.get_profile_args(profile_dir),
+            vec!["--profile".to_string(), profile_dir.to_string()]
+        );
+        // placeholder profile
+        assert_eq!(
+            firefox_app.get_user_dir_args("/home/madis/acme", ""),
+            vec!["--profile".to_string(), "/home/madis/acme".to_string()]
+        );
+        assert!(firefox_app.get_profile_args("").is_empty());
+
+        let generic_app = repository.get_or_generate("com.spotify.Client", &vec![], None);
+        assert!(!generic_app.supports_custom_user_dir());
+    }
+
+    #[test]
+    fn test_epiphany_profile_args() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("epiphany");
+        let app_config = flatpak_app_config(AppKind::EPIPHANY, "org.gnome.Epiphany", "epiphany");
+        let app = repository.create_app_from_app_config(app_config);
+
+        assert_eq!(
+            app.get_app_config_dir_abs(false, false),
+            home_dir.join(".local/share/epiphany")
+        );
+        assert_eq!(
+            app.get_app_config_dir_abs(true, false),
+            home_dir.join(".var/app/org.gnome.Epiphany/data/epiphany")
+        );
+
+        assert!(app.get_profile_args("").is_empty());
+        assert_eq!(
+            app.get_profile_args("/home/madis/.local/share/org.gnome.Epiphany.WebApp_abc"),
+            vec![
+                "--application-mode".to_string(),
+                "--profile=/home/madis/.local/share/org.gnome.Epiphany.WebApp_abc".to_string(),
+            ]
+        );
+        assert_eq!(
+            app.get_profile_args("/home/madis/epiphany-work"),
+            vec!["--profile=/home/madis/epiphany-work".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_qutebrowser_and_falkon_profile_args() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("qutebrowser-falkon");
+        let mut qutebrowser_config = flatpak_app_config(
+            AppKind::QUTEBROWSER,
+            "org.qutebrowser.qutebrowser",
+            "qutebrowser-profiles",
+        );
+        qutebrowser_config.flatpak_id = None;
+        let qutebrowser = repository.create_app_from_app_config(qutebrowser_config);
+        assert_eq!(
+            qutebrowser.get_app_config_dir_abs(false, false),
+            home_dir.join(".local/share/qutebrowser-profiles")
+        );
+        assert!(qutebrowser.get_profile_args("").is_empty());
+        assert_eq!(
+            qutebrowser.get_profile_args("/home/madis/.local/share/qutebrowser-profiles/work"),
+            vec![
+                "--basedir".to_string(),
+                "/home/madis/.local/share/qutebrowser-profiles/work".to_string(),
+            ]
+        );
+
+        let falkon_config =
+            flatpak_app_config(AppKind::FALKON, "org.kde.falkon", "falkon/profiles");
+        let falkon = repository.create_app_from_app_config(falkon_config);
+        assert_eq!(
+            falkon.get_app_config_dir_abs(true, false),
+            home_dir.join(".var/app/org.kde.falkon/config/falkon/profiles")
+        );
+        assert_eq!(
+            falkon.get_profile_args("work"),
+            vec!["-p".to_string(), "work".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_launch_mode_args() {
+        let (_temp_dir, mut repository, _) = create_repository("launch-modes");
+        let chromium = repository.create_app_from_app_config(flatpak_app_config(
+            AppKind::CHROMIUM,
+            "org.chromium.Chromium",
+            "chromium",
+        ));
+        assert_eq!(
+            chromium.get_launch_modes(),
+            vec![
+                LaunchMode::Incognito,
+                LaunchMode::NewWindow,
+                LaunchMode::AppWindow,
+                LaunchMode::Guest,
+            ]
+        );
+        assert_eq!(
+            chromium.get_launch_mode_args(Some(LaunchMode::Guest)),
+            vec!["--guest".to_string()]
+        );
+        // url is given in `--app=<url>` instead
+        assert!(
+            chromium
+                .get_launch_mode_args(Some(LaunchMode::AppWindow))
+                .is_empty()
+        );
+
+        let firefox = repository.create_app_from_app_config(flatpak_app_config(
+            AppKind::FIREFOX,
+            "org.mozilla.firefox",
+            ".mozilla/firefox",
+        ));
+        assert!(firefox.supports_incognito());
+        assert!(firefox.supports_launch_mode(LaunchMode::NewTab));
+        assert!(!firefox.supports_launch_mode(LaunchMode::AppWindow));
+        assert!(
+            firefox
+                .get_launch_mode_args(Some(LaunchMode::AppWindow))
+                .is_empty()
+        );
+        assert!(firefox.get_launch_mode_args(None).is_empty());
+
+        let generic_app = repository.get_or_generate("com.spotify.Client", &vec![], None);
+        assert!(generic_app.get_launch_modes().is_empty());
+        assert!(!generic_app.supports_incognito());
+    }
+
+    #[test]
+    fn test_add_missing_launch_modes() {
+        let (_temp_dir, mut repository, _) = create_repository("missing-launch-modes");
+        let desktop_action_launch_modes = vec![
+            (LaunchMode::NewWindow, vec!["-w".to_string()]),
+            (LaunchMode::Incognito, vec!["-pb".to_string()]),
+        ];
+
+        let mut generic_app = repository.get_or_generate("org.example.Browser", &vec![], None);
+        generic_app.add_missing_launch_modes(&desktop_action_launch_modes);
+        assert!(generic_app.supports_incognito());
+        assert_eq!(
+            generic_app.get_launch_mode_args(Some(LaunchMode::Incognito)),
+            vec!["-pb".to_string()]
+        );
+
+        // launch modes of the repository are kept
+        let mut firefox = repository.create_app_from_app_config(flatpak_app_config(
+            AppKind::FIREFOX,
+            "org.mozilla.firefox",
+            ".mozilla/firefox",
+        ));
+        firefox.add_missing_launch_modes(&desktop_action_launch_modes);
+        assert_eq!(
+            firefox.get_launch_mode_args(Some(LaunchMode::Incognito)),
+            vec!["--private-window".to_string()]
+        );
+    }
+
+    fn snap_firefox_app(repository: &mut SupportedAppRepository) -> SupportedApp {
+        let app_config = AppConfig {
+            os: AppOS::LINUX,
+            kind: AppKind::FIREFOX,
+            id: "firefox_firefox".to_string(),
+            config_dir_relative: ".mozilla/firefox".to_string(),
+            snap_id: Some("firefox".to_string()),
+            flatpak_id: None,
+        };
+        return repository.create_app_from_app_config(app_config);
+    }
+
+    fn create_profiles_ini(firefox_profiles_dir: &Path) {
+        fs::create_dir_all(firefox_profiles_dir).unwrap();
+        fs::write(
+            firefox_profiles_dir.join("profiles.ini"),
+            "[Profile0]\nName=default\nIsRelative=1\nPath=abc.default\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_detected_snap_prefers_snap_dir() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("snap-detected");
+        let snap_dir = home_dir.join("snap/firefox/common/.mozilla/firefox");
+        create_profiles_ini(snap_dir.as_path());
+        create_profiles_ini(home_dir.join(".mozilla/firefox").as_path());
+
+        let app = snap_firefox_app(&mut repository);
+        let snap_root = home_dir.join("snap");
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), Some("firefox")),
+            snap_dir
+        );
+    }
+
+    #[test]
+    fn test_undetected_snap_falls_back_to_snap_dir() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("snap-undetected");
+        let snap_dir = home_dir.join("snap/firefox/common/.mozilla/firefox");
+        create_profiles_ini(snap_dir.as_path());
+
+        let app = snap_firefox_app(&mut repository);
+        let snap_root = home_dir.join("snap");
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), None),
+            snap_dir
+        );
+    }
+
+    #[test]
+    fn test_detected_snap_falls_back_to_native_dir() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("snap-native");
+        let native_dir = home_dir.join(".mozilla/firefox");
+        create_profiles_ini(native_dir.as_path());
+
+        let app = snap_firefox_app(&mut repository);
+        let snap_root = home_dir.join("snap");
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), Some("firefox")),
+            native_dir
+        );
+    }
+
+    #[test]
+    fn test_snap_parallel_install() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("snap-parallel");
+        let instance_dir = home_dir.join("snap/firefox_work/common/.mozilla/firefox");
+        create_profiles_ini(instance_dir.as_path());
+        create_profiles_ini(
+            home_dir
+                .join("snap/firefox/common/.mozilla/firefox")
+                .as_path(),
+        );
+
+        let app = snap_firefox_app(&mut repository);
+        let snap_root = home_dir.join("snap");
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), Some("firefox_work")),
+            instance_dir
+        );
+    }
+
+    #[test]
+    fn test_without_profiles_uses_preferred_dir() {
+        let (_temp_dir, mut repository, home_dir) = create_repository("snap-without-profiles");
+        let app = snap_firefox_app(&mut repository);
+        let snap_root = home_dir.join("snap");
+
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), Some("firefox")),
+            home_dir.join("snap/firefox/common/.mozilla/firefox")
+        );
+        assert_eq!(
+            app.find_snap_or_native_app_config_dir_abs(snap_root.as_path(), None),
+            home_dir.join(".mozilla/firefox")
+        );
+    }
+
+    #[test]
+    fn test_guess_config_dir_names() {
+        assert_eq!(
+            guess_config_dir_names(
+                "thorium-browser",
+                Path::new("/usr/bin/thorium-browser"),
+                "Thorium Browser"
+            ),
+            vec![
+                "thorium-browser",
+                "thorium",
+                "Thorium Browser",
+                "thorium browser",
+                "Thorium",
+            ]
+        );
+        assert_eq!(
+            guess_config_dir_names("org.mozilla.floorp", Path::new("unknown"), "Floorp"),
+            vec!["floorp", "Floorp"]
+        );
+    }
+
+    #[test]
+    fn test_guess_chromium_fork() {
+        let (_temp_dir, repository, home_dir) = create_repository("guess-chromium");
+        let thorium_dir = home_dir.join(".config/thorium");
+        fs::create_dir_all(thorium_dir.as_path()).unwrap();
+        fs::write(
+            thorium_dir.join("Local State"),
+            r#"{"profile": {"info_cache": {"Default": {"name": "Person 1"}}}}"#,
+        )
+        .unwrap();
+
+        let guessed_app_config_maybe = repository.guess_app_config(
+            "thorium-browser",
+            Path::new("/usr/bin/thorium-browser"),
+            "Thorium",
+        );
+        let app = repository.get_or_generate(
+            "thorium-browser",
+            &vec![],
+            guessed_app_config_maybe.as_ref(),
+        );
+        assert_eq!(app.get_app_config_dir_abs(false, false), thorium_dir.as_path());
+        assert_eq!(
+            app.get_profile_args("Default"),
+            vec!["--profile-directory=Default".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_guess_firefox_fork() {
+        let (_temp_dir, repository, home_dir) = create_repository("guess-firefox");
+        let mercury_dir = home_dir.join(".mercury");
+        create_profiles_ini(mercury_dir.as_path());
+
+        let guessed_app_config_maybe = repository.guess_app_config(
+            "mercury-browser",
+            Path::new("/usr/bin/mercury-browser"),
+            "Mercury",
+        );
+        let app = repository.get_or_generate(
+            "mercury-browser",
+            &vec![],
+            guessed_app_config_maybe.as_ref(),
+        );
+        assert_eq!(app.get_app_config_dir_abs(false, false), mercury_dir.as_path());
+        assert_eq!(
+            app.get_profile_args("default"),
+            vec!["-P".to_string(), "default".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_guessed_app_keeps_restricted_domains() {
+        let (_temp_dir, repository, home_dir) = create_repository("guess-restricted");
+        create_profiles_ini(home_dir.join(".mercury").as_path());
+
+        let guessed_app_config_maybe =
+            repository.guess_app_config("mercury-browser", Path::new("unknown"), "Mercury");
+        let app = repository.get_or_generate(
+            "mercury-browser",
+            &vec!["example.com".to_string()],
+            guessed_app_config_maybe.as_ref(),
+        );
+        assert_eq!(app.get_restricted_hostname_matchers().len(), 1);
+    }
+
+    #[test]
+    fn test_electron_app_is_not_guessed() {
+        let (_temp_dir, repository, home_dir) = create_repository("guess-electron");
+        let electron_app_dir = home_dir.join(".config/discord");
+        fs::create_dir_all(electron_app_dir.as_path()).unwrap();
+        fs::write(
+            electron_app_dir.join("Local State"),
+            r#"{"os_crypt": {"encrypted_key": "abc"}}"#,
+        )
+        .unwrap();
+
+        let guessed_app_config_maybe =
+            repository.guess_app_config("discord", Path::new("/usr/bin/discord"), "Discord");
+        assert!(guessed_app_config_maybe.is_none());
+        let app = repository.get_or_generate("discord", &vec![], guessed_app_config_maybe.as_ref());
+        assert_eq!(app.get_app_config_dir_abs(false, false), Path::new(""));
+        assert!(app.get_profile_args("Default").is_empty());
+    }
+}
@@ -27,6 +27,12 @@ pub fn find_chromium_profiles(
     let info_cache_map = ChromeInfoCacheMap::new_from_local_state(local_state_file.as_path());
     let profiles = info_cache_map.parse_chrome_local_state_profiles();
     for profile in profiles {
+        // e.g "System Profile" and guest profiles, which Chromium doesn't show in its own picker
+        if profile.is_omitted {
+            info!("Skipping omitted profile {}", profile.profile_dir_name);
+            continue;
+        }
+
         let profile_name = profile.name;
 
         //let profile_dir = chromium_user_dir.join(profile.profile_dir_name.as_str());
@@ -63,18 +69,37 @@ pub fn find_chromium_profiles(
             .flatten();
 
         let profile_dir_name = profile.profile_dir_name;
+
         browser_profiles.push(InstalledBrowserProfile {
             profile_cli_arg_value: profile_dir_name.to_string(),
             profile_cli_container_name: None,
             profile_name: profile_name,
             profile_icon: profile_icon_path,
             profile_restricted_url_patterns: vec![],
+            profile_email: profile.email,
+            profile_hosted_domain: profile.hosted_domain,
+            profile_enterprise_domain: None,
+            profile_last_active: profile.last_active,
+            profile_legacy_cli_arg_value: None,
         })
     }
 
     return browser_profiles;
 }
 
+// Chromium-based browsers keep their profiles in `profile.info_cache` of "Local State",
+// other Chromium-based apps (e.g Electron apps) have "Local State" without it
+pub fn has_profiles_info_cache(chromium_user_dir: &Path) -> bool {
+    let local_state_file = chromium_user_dir.join("Local State");
+    let local_state_maybe: Option<Value> = File::open(local_state_file)
+        .ok()
+        .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok());
+
+    return local_state_maybe
+        .map(|local_state| local_state["profile"]["info_cache"].is_object())
+        .unwrap_or(false);
+}
+
 pub struct ChromeInfoCacheMap {
     info_cache_map: Map<String, Value>,
 }
@@ -227,6 +252,10 @@ impl ChromeProfileAttributesEntry {
             profile_dir_name: self.profile_dir.to_string(),
             name: best_name.to_string(),
             avatar_file_path_relative_to_config: profile_avatar_file_path,
+            email: self.get_user_name(),
+            hosted_domain: self.get_hosted_domain(),
+            last_active: self.get_active_time(),
+            is_omitted: self.is_ephemeral() || self.is_omitted_from_profile_list(),
         };
     }
 
@@ -433,6 +462,38 @@ impl ChromeProfileAttributesEntry {
         self.profile["avatar_icon"].as_str().map(|a| a.to_string())
     }
 
+    // email of the signed-in account, e.g "madis@qminderapp.com"
+    fn get_user_name(&self) -> Option<String> {
+        self.profile["user_name"]
+            .as_str()
+            .filter(|a| !a.is_empty())
+            .map(|a| a.to_string())
+    }
+
+    // domain of a Google Workspace account, e.g "qminderapp.com"
+    // consumer accounts have "NO_HOSTED_DOMAIN" instead
+    fn get_hosted_domain(&self) -> Option<String> {
+        self.profile["hosted_domain"]
+            .as_str()
+            .filter(|a| !a.is_empty() && *a != "NO_HOSTED_DOMAIN")
+            .map(|a| a.to_string())
+    }
+
+    // seconds since unix epoch, e.g 1713440041.508719
+    fn get_active_time(&self) -> Option<i64> {
+        self.profile["active_time"].as_f64().map(|a| a as i64)
+    }
+
+    fn is_ephemeral(&self) -> bool {
+        self.profile["is_ephemeral"].as_bool().unwrap_or(false)
+    }
+
+    fn is_omitted_from_profile_list(&self) -> bool {
+        self.profile["is_omitted_from_profile_list"]
+            .as_bool()
+            .unwrap_or(false)
+    }
+
     // "default_avatar_fill_color": -15189734,
     // "profile_highlight_color": -15189734,
 }
@@ -450,4 +511,92 @@ pub struct ChromeProfilePreferences {
     pub profile_dir_name: String,
     pub name: String,
     pub avatar_file_path_relative_to_config: Option<PathBuf>,
+    pub email: Option<String>,
+    pub hosted_domain: Option<String>,
+    pub last_active: Option<i64>,
+    // ephemeral or omitted from profile list (e.g "System Profile")
+    pub is_omitted: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils;
+
+    fn parse(info_cache: Value) -> Vec<ChromeProfilePreferences> {
+        let info_cache_map = ChromeInfoCacheMap {
+            info_cache_map: info_cache.as_object().unwrap().clone(),
+        };
+        return info_cache_map.parse_chrome_local_state_profiles();
+    }
+
+    #[test]
+    fn test_account_metadata() {
+        let profiles = parse(json!({
+            "Default": {
+                "name": "Work",
+                "user_name": "madis@qminderapp.com",
+                "hosted_domain": "qminderapp.com",
+                "active_time": 1713440041.508719
+            },
+            "Profile 1": {
+                "name": "Personal",
+                "user_name": "",
+                "hosted_domain": "NO_HOSTED_DOMAIN"
+            }
+        }));
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].name, "Personal");
+        assert_eq!(profiles[0].email, None);
+        assert_eq!(profiles[0].hosted_domain, None);
+        assert_eq!(profiles[0].last_active, None);
+
+        assert_eq!(profiles[1].name, "Work");
+        assert_eq!(profiles[1].email, Some("madis@qminderapp.com".to_string()));
+        assert_eq!(profiles[1].hosted_domain, Some("qminderapp.com".to_string()));
+        assert_eq!(profiles[1].last_active, Some(1713440041));
+        assert!(!profiles[1].is_omitted);
+    }
+
+    #[test]
+    fn test_omitted_and_ephemeral_profiles() {
+        let profiles = parse(json!({
+            "System Profile": {
+                "name": "System Profile",
+                "is_omitted_from_profile_list": true
+            },
+            "Guest Profile": {
+                "name": "Guest",
+                "is_ephemeral": true
+            }
+        }));
+
+        assert!(profiles.iter().all(|p| p.is_omitted));
+    }
+
+    #[test]
+    fn test_has_profiles_info_cache() {
+        let temp_dir = test_utils::create_temp_dir("chromium-profiles");
+        let test_dir = temp_dir.path().to_path_buf();
+        let browser_dir = test_dir.join("browser");
+        let electron_app_dir = test_dir.join("electron-app");
+        fs::create_dir_all(browser_dir.as_path()).unwrap();
+        fs::create_dir_all(electron_app_dir.as_path()).unwrap();
+
+        let browser_local_state = json!({"profile": {"info_cache": {"Default": {"name": "Work"}}}});
+        fs::write(browser_dir.join("Local State"), browser_local_state.to_string()).unwrap();
+        let electron_app_local_state = json!({"os_crypt": {"encrypted_key": "abc"}});
+        fs::write(
+            electron_app_dir.join("Local State"),
+            electron_app_local_state.to_string(),
+        )
+        .unwrap();
+
+        assert!(has_profiles_info_cache(browser_dir.as_path()));
+        assert!(!has_profiles_info_cache(electron_app_dir.as_path()));
+        assert!(!has_profiles_info_cache(test_dir.join("missing").as_path()));
+    }
 }
@@ -0,0 +1,233 @@
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::utils::expand_home_dir;
+
+// An opener which is not an installed app, but a command to run, e.g
+// {"name": "Play in mpv", "command": ["mpv", "--force-window", "%u"],
+//  "restricted_url_patterns": ["youtube.com", "www.youtube.com"]}
+// It is shown, ordered, hidden and used in rules like any other app.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct CustomAppConfig {
+    // used in `hidden_apps`, `profile_order` and rules, defaults to `name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    // path of a png or jpeg icon, "~/" is expanded to the home dir
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    // program and its arguments, which can have placeholders:
+    // %u (or %U) - url
+    // %e - url, percent-encoded (e.g to pass it as a query parameter)
+    // %h - host of the url
+    // %% - literal %
+    // url is added as the last argument if there are no placeholders
+    pub command: Vec<String>,
+    // only offered for links matching these, e.g ["youtube.com", "*.vimeo.com"]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restricted_url_patterns: Vec<String>,
+    // url given to the command instead of the link, with the same placeholders,
+    // e.g "https://web.archive.org/web/%u"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_transform: Option<String>,
+}
+
+const PLACEHOLDERS: [char; 4] = ['u', 'U', 'e', 'h'];
+
+// same as encodeURIComponent, so the url can be a query parameter or a path segment
+const URL_COMPONENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+impl CustomAppConfig {
+    // unique among apps, as it's used as the executable path of the app
+    pub fn get_unique_app_id(&self) -> String {
+        let id = self
+            .id
+            .as_ref()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or(&self.name);
+        return format!("custom-app:{}", id.trim());
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return !self.name.trim().is_empty()
+            && self
+                .command
+                .first()
+                .map(|program| !program.trim().is_empty())
+                .unwrap_or(false);
+    }
+
+    pub fn get_icon_path(&self) -> String {
+        return self
+            .icon
+            .as_ref()
+            .filter(|icon| !icon.trim().is_empty())
+            .map(|icon| expand_home_dir(icon).display().to_string())
+            .unwrap_or_default();
+    }
+
+    pub fn get_transformed_url(&self, url: &str) -> String {
+        return self
+            .url_transform
+            .as_ref()
+            .map(|url_transform| expand_placeholders(url_transform, url))
+            .unwrap_or(url.to_string());
+    }
+
+    // the command with placeholders replaced
+    pub fn get_command(&self, url: &str) -> Vec<String> {
+        let app_url = self.get_transformed_url(url);
+        let mut command: Vec<String> = self
+            .command
+            .iter()
+            .map(|arg| expand_placeholders(arg, app_url.as_str()))
+            .collect();
+
+        let has_placeholder = self.command.iter().skip(1).any(|arg| has_placeholder(arg));
+        if !has_placeholder {
+            command.push(app_url);
+        }
+
+        return command;
+    }
+}
+
+// "%%u" is a literal "%u", not a placeholder
+fn has_placeholder(template: &str) -> bool {
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some(placeholder) if PLACEHOLDERS.contains(&placeholder) => return true,
+            _ => {}
+        }
+    }
+    return false;
+}
+
+fn expand_placeholders(template: &str, url: &str) -> String {
+    let mut expanded = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('u') | Some('U') => expanded.push_str(url),
+            Some('e') => expanded.extend(utf8_percent_encode(url, URL_COMPONENT_ENCODE_SET)),
+            Some('h') => {
+                let host_maybe = Url::parse(url)
+                    .ok()
+                    .and_then(|url| url.host_str().map(|host| host.to_string()));
+                expanded.push_str(host_maybe.unwrap_or_default().as_str());
+            }
+            Some('%') => expanded.push('%'),
+            // unknown placeholders are kept as they are
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    return expanded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_app(command: Vec<&str>) -> CustomAppConfig {
+        return CustomAppConfig {
+            id: None,
+            name: "Play in mpv".to_string(),
+            icon: None,
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+            restricted_url_patterns: vec![],
+            url_transform: None,
+        };
+    }
+
+    #[test]
+    fn test_get_command() {
+        let app = custom_app(vec!["mpv", "--force-window", "%u"]);
+        assert_eq!(
+            app.get_command("https://www.youtube.com/watch?v=abc"),
+            vec![
+                "mpv",
+                "--force-window",
+                "https://www.youtube.com/watch?v=abc"
+            ]
+        );
+
+        let app = custom_app(vec!["reading-list", "--url=%e", "--site=%h", "--progress=100%%"]);
+        assert_eq!(
+            app.get_command("https://example.com/a b?x=1&y=2"),
+            vec![
+                "reading-list",
+                "--url=https%3A%2F%2Fexample.com%2Fa%20b%3Fx%3D1%26y%3D2",
+                "--site=example.com",
+                "--progress=100%",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_command_without_placeholder() {
+        let app = custom_app(vec!["yt-dlp"]);
+        assert_eq!(
+            app.get_command("https://vimeo.com/123"),
+            vec!["yt-dlp", "https://vimeo.com/123"]
+        );
+    }
+
+    #[test]
+    fn test_get_command_with_escaped_placeholder() {
+        let app = custom_app(vec!["notify-send", "100%%u"]);
+        assert_eq!(
+            app.get_command("https://vimeo.com/123"),
+            vec!["notify-send", "100%u", "https://vimeo.com/123"]
+        );
+    }
+
+    #[test]
+    fn test_url_transform() {
+        let mut app = custom_app(vec!["xdg-open", "%u"]);
+        app.url_transform = Some("https://web.archive.org/web/%u".to_string());
+        assert_eq!(
+            app.get_command("https://example.com/"),
+            vec![
+                "xdg-open",
+                "https://web.archive.org/web/https://example.com/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_unique_app_id() {
+        let mut app = custom_app(vec!["mpv"]);
+        assert_eq!(app.get_unique_app_id(), "custom-app:Play in mpv");
+
+        app.id = Some("mpv".to_string());
+        assert_eq!(app.get_unique_app_id(), "custom-app:mpv");
+
+        assert!(app.is_valid());
+        app.command = vec![];
+        assert!(!app.is_valid());
+    }
+}
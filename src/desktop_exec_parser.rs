@@ -0,0 +1,534 @@
+use std::path::Path;
+
+use url::Url;
+
+use crate::flatpak_command_parser;
+
+// Exec value of a desktop entry, see
+// https://specifications.freedesktop.org/desktop-entry-spec/latest/exec-variables.html
+// Parsed into arguments, with field codes (e.g %U) kept as they are;
+// %i, %c and %k are expanded when the desktop entry is read (see `expand_desktop_entry_fields`),
+// file and url field codes when the app is launched (see `expand_exec`).
+
+// Values of the desktop entry itself
+pub struct DesktopEntryFields<'a> {
+    // `Icon` key, e.g "firefox"
+    pub icon: Option<&'a str>,
+    // translated `Name` key
+    pub name: &'a str,
+    pub desktop_file_path: &'a Path,
+}
+
+// Splits the Exec value into arguments; None if a quote is not closed
+pub fn parse_exec(exec: &str) -> Option<Vec<String>> {
+    // escape sequences of string values are applied before the quoting rules
+    let exec = unescape_string_value(exec);
+    return split_arguments(exec.as_str());
+}
+
+// Splits by the quoting rules of Exec, which are those of a POSIX shell (without expansions),
+// e.g `firejail --name="client work" --private` is 3 arguments; None if a quote is not closed
+pub fn split_arguments(line: &str) -> Option<Vec<String>> {
+    let mut arguments: Vec<String> = Vec::new();
+    let mut argument = String::new();
+    let mut in_argument = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_argument = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        // only these can be escaped in a quoted argument, other backslashes are literal
+                        '\\' => match chars.next()? {
+                            escaped @ ('"' | '`' | '$' | '\\') => argument.push(escaped),
+                            other => {
+                                argument.push('\\');
+                                argument.push(other);
+                            }
+                        },
+                        quoted => argument.push(quoted),
+                    }
+                }
+            }
+            // not in the spec, but used in the wild, e.g `sh -c 'exec foo'`
+            '\'' => {
+                in_argument = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        quoted => argument.push(quoted),
+                    }
+                }
+            }
+            // not in the spec either, reserved characters should be quoted instead
+            '\\' => {
+                in_argument = true;
+                if let Some(escaped) = chars.next() {
+                    argument.push(escaped);
+                }
+            }
+            ' ' | '\t' | '\n' => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut argument));
+                    in_argument = false;
+                }
+            }
+            _ => {
+                in_argument = true;
+                argument.push(c);
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(argument);
+    }
+
+    return Some(arguments);
+}
+
+// \s, \n, \t, \r and \\; other backslashes are left for the quoting rules
+fn unescape_string_value(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            _ => {
+                unescaped.push('\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    return unescaped;
+}
+
+// Expands %i, %c and %k, which don't depend on the launched url;
+// other field codes (and %%) are kept for `expand_exec`
+pub fn expand_desktop_entry_fields(command: &[String], fields: &DesktopEntryFields) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for argument in command {
+        if argument == "%i" {
+            if let Some(icon) = fields.icon.filter(|icon| !icon.is_empty()) {
+                expanded.push("--icon".to_string());
+                expanded.push(escape_field_codes(icon));
+            }
+            continue;
+        }
+
+        let desktop_file_path = fields.desktop_file_path.to_string_lossy();
+        let expanded_argument = replace_field_codes(argument, |field_code| match field_code {
+            'c' => Some(escape_field_codes(fields.name)),
+            'k' => Some(escape_field_codes(&desktop_file_path)),
+            // %i is only valid as a separate argument
+            'i' => Some("".to_string()),
+            _ => None,
+        });
+        expanded.push(expanded_argument);
+    }
+    return expanded;
+}
+
+// Command to launch, with `app_args` (e.g profile arguments) right after the actual app
+// and url arguments in place of the url or file field codes, or at the end if there are none
+pub fn expand_exec(command: &[String], app_args: &[String], url_args: &[String]) -> Vec<String> {
+    let app_args_index = find_app_args_index(command);
+    let file_args: Vec<String> = url_args.iter().map(|arg| to_file_arg(arg)).collect();
+    let url = url_args.last().map(|url| url.as_str()).unwrap_or("");
+    let file = file_args.last().map(|file| file.as_str()).unwrap_or("");
+
+    let mut expanded: Vec<String> = Vec::new();
+    let mut has_url_field_code = false;
+    for (i, argument) in command.iter().enumerate() {
+        if i == app_args_index {
+            expanded.extend(app_args.iter().cloned());
+        }
+
+        match argument.as_str() {
+            "%u" | "%U" => {
+                has_url_field_code = true;
+                expanded.extend(url_args.iter().cloned());
+            }
+            "%f" | "%F" => {
+                has_url_field_code = true;
+                expanded.extend(file_args.iter().cloned());
+            }
+            // expanded when the desktop entry was read, or deprecated
+            "%i" | "%c" | "%k" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+            _ => {
+                let expanded_argument =
+                    replace_field_codes(argument, |field_code| match field_code {
+                        'u' | 'U' => {
+                            has_url_field_code = true;
+                            Some(url.to_string())
+                        }
+                        'f' | 'F' => {
+                            has_url_field_code = true;
+                            Some(file.to_string())
+                        }
+                        '%' => Some("%".to_string()),
+                        _ => Some("".to_string()),
+                    });
+                expanded.push(expanded_argument);
+            }
+        }
+    }
+    if app_args_index >= command.len() {
+        expanded.extend(app_args.iter().cloned());
+    }
+
+    // non-browser apps might not have the field code
+    if !has_url_field_code {
+        expanded.extend(url_args.iter().cloned());
+    }
+
+    return expanded;
+}
+
+// Index of the first argument of the actual app, skipping launchers in front of it, e.g
+// "env BAMF_DESKTOP_FILE_HINT=.. /snap/bin/firefox %u" is 3
+// "flatpak run --branch=stable com.google.Chrome @@u %U @@" is 4
+// "snap run chromium %U" is 3
+pub fn find_app_args_index(command: &[String]) -> usize {
+    if let Some(flatpak_run) = flatpak_command_parser::parse_flatpak_run(command) {
+        return flatpak_run.app_id_index + 1;
+    }
+
+    let mut index = 0;
+    if is_program(command.get(index), "env") {
+        index += 1;
+        while let Some(argument) = command.get(index) {
+            if ["-u", "--unset", "-C", "--chdir"].contains(&argument.as_str()) {
+                // option with a separate value
+                index += 2;
+            } else if argument.starts_with('-') || argument.contains('=') {
+                index += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    if is_program(command.get(index), "snap")
+        && command.get(index + 1).map(|argument| argument.as_str()) == Some("run")
+    {
+        index += 2;
+        while let Some(argument) = command.get(index) {
+            if !argument.starts_with('-') {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    return (index + 1).min(command.len());
+}
+
+// Arguments which `other_command` (e.g the Exec value of a Desktop Action) adds to the app of
+// `command`, without field codes; None if it runs another app or passes the url inside an argument
+// "firefox %u" and "firefox --private-window %u" is ["--private-window"]
+pub fn find_added_app_args(command: &[String], other_command: &[String]) -> Option<Vec<String>> {
+    let app_args_index = find_app_args_index(command);
+    let other_app_args_index = find_app_args_index(other_command);
+    let app_maybe = command.get(app_args_index.wrapping_sub(1));
+    let other_app_maybe = other_command.get(other_app_args_index.wrapping_sub(1));
+    let is_same_app = match (app_maybe, other_app_maybe) {
+        (Some(app), Some(other_app)) => {
+            Path::new(app).file_name() == Path::new(other_app).file_name()
+        }
+        _ => false,
+    };
+    if !is_same_app {
+        return None;
+    }
+
+    let app_args = &command[app_args_index..];
+    let mut added_app_args: Vec<String> = Vec::new();
+    for argument in &other_command[other_app_args_index..] {
+        let is_field_code = argument.len() == 2 && argument.starts_with('%');
+        // file forwarding of flatpak, see `flatpak_command_parser`
+        let is_flatpak_marker = argument == "@@" || argument == "@@u";
+        if is_field_code || is_flatpak_marker || app_args.contains(argument) {
+            continue;
+        }
+        if argument.replace("%%", "").contains('%') {
+            return None;
+        }
+        added_app_args.push(argument.replace("%%", "%"));
+    }
+    return Some(added_app_args);
+}
+
+fn is_program(argument_maybe: Option<&String>, program_name: &str) -> bool {
+    return argument_maybe
+        .and_then(|argument| Path::new(argument).file_name())
+        .map(|file_name| file_name == program_name)
+        .unwrap_or(false);
+}
+
+// file field codes take local paths, so file urls are converted
+fn to_file_arg(url_arg: &str) -> String {
+    return Url::parse(url_arg)
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok())
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(url_arg.to_string());
+}
+
+// `replacement_fn` returns None to keep the field code as it is
+fn replace_field_codes<F>(argument: &str, mut replacement_fn: F) -> String
+where
+    F: FnMut(char) -> Option<String>,
+{
+    let mut replaced = String::new();
+    let mut chars = argument.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            replaced.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some(field_code) => match replacement_fn(field_code) {
+                Some(replacement) => replaced.push_str(replacement.as_str()),
+                None => {
+                    replaced.push('%');
+                    replaced.push(field_code);
+                }
+            },
+            None => replaced.push('%'),
+        }
+    }
+    return replaced;
+}
+
+fn escape_field_codes(value: &str) -> String {
+    return value.replace('%', "%%");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(arguments: &[&str]) -> Vec<String> {
+        return arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn test_parse_exec() {
+        let cases: Vec<(&str, Vec<&str>)> = vec![
+            (
+                "/usr/bin/google-chrome-stable %U",
+                vec!["/usr/bin/google-chrome-stable", "%U"],
+            ),
+            (
+                "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop /snap/bin/firefox %u",
+                vec![
+                    "env",
+                    "BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop",
+                    "/snap/bin/firefox",
+                    "%u",
+                ],
+            ),
+            (
+                "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=/app/bin/chrome --file-forwarding com.google.Chrome @@u %U @@",
+                vec![
+                    "/usr/bin/flatpak",
+                    "run",
+                    "--branch=stable",
+                    "--arch=x86_64",
+                    "--command=/app/bin/chrome",
+                    "--file-forwarding",
+                    "com.google.Chrome",
+                    "@@u",
+                    "%U",
+                    "@@",
+                ],
+            ),
+            (
+                "qutebrowser --untrusted-args %u",
+                vec!["qutebrowser", "--untrusted-args", "%u"],
+            ),
+            (
+                r#""/opt/My Browser/browser" --class="a \"quoted\" \$name" %u"#,
+                vec![
+                    "/opt/My Browser/browser",
+                    r#"--class=a "quoted" $name"#,
+                    "%u",
+                ],
+            ),
+            // "\\\\" in the file is a literal backslash in a quoted argument
+            (
+                r#"wine "C:\\\\Program Files\\\\Browser.exe" %u"#,
+                vec!["wine", r"C:\Program Files\Browser.exe", "%u"],
+            ),
+            (r#"browser\sbeta "" 100%%"#, vec!["browser", "beta", "", "100%%"]),
+            (
+                "sh -c 'exec browser \"$1\"' sh %u",
+                vec!["sh", "-c", "exec browser \"$1\"", "sh", "%u"],
+            ),
+        ];
+
+        for (exec, expected) in cases {
+            assert_eq!(parse_exec(exec), Some(to_strings(&expected)), "{}", exec);
+        }
+
+        assert_eq!(parse_exec(r#"browser "unterminated %u"#), None);
+    }
+
+    #[test]
+    fn test_expand_desktop_entry_fields() {
+        let command = parse_exec("firefox %i --name=%c --desktop=%k 100%% %u").unwrap();
+        let fields = DesktopEntryFields {
+            icon: Some("firefox"),
+            name: "Firefox 100%",
+            desktop_file_path: Path::new("/usr/share/applications/firefox.desktop"),
+        };
+        assert_eq!(
+            expand_desktop_entry_fields(&command, &fields),
+            to_strings(&[
+                "firefox",
+                "--icon",
+                "firefox",
+                "--name=Firefox 100%%",
+                "--desktop=/usr/share/applications/firefox.desktop",
+                "100%%",
+                "%u",
+            ])
+        );
+
+        let fields = DesktopEntryFields {
+            icon: None,
+            name: "Firefox",
+            desktop_file_path: Path::new("/usr/share/applications/firefox.desktop"),
+        };
+        assert_eq!(
+            expand_desktop_entry_fields(&command[..2], &fields),
+            to_strings(&["firefox"])
+        );
+    }
+
+    #[test]
+    fn test_expand_exec() {
+        let profile_args = ["--profile-directory=Profile 1"];
+        let url = "https://example.com/";
+
+        // Exec value, and the expected command
+        let cases: Vec<(&str, Vec<&str>)> = vec![
+            (
+                "/usr/bin/google-chrome-stable %U",
+                vec![
+                    "/usr/bin/google-chrome-stable",
+                    "--profile-directory=Profile 1",
+                    url,
+                ],
+            ),
+            (
+                "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop /snap/bin/firefox %u",
+                vec![
+                    "env",
+                    "BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop",
+                    "/snap/bin/firefox",
+                    "--profile-directory=Profile 1",
+                    url,
+                ],
+            ),
+            (
+                "env -u GTK_IM_MODULE MOZ_ENABLE_WAYLAND=1 snap run --experimental-gdbserver firefox %u",
+                vec![
+                    "env",
+                    "-u",
+                    "GTK_IM_MODULE",
+                    "MOZ_ENABLE_WAYLAND=1",
+                    "snap",
+                    "run",
+                    "--experimental-gdbserver",
+                    "firefox",
+                    "--profile-directory=Profile 1",
+                    url,
+                ],
+            ),
+            (
+                "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=/app/bin/chrome --file-forwarding com.google.Chrome @@u %U @@",
+                vec![
+                    "/usr/bin/flatpak",
+                    "run",
+                    "--branch=stable",
+                    "--arch=x86_64",
+                    "--command=/app/bin/chrome",
+                    "--file-forwarding",
+                    "com.google.Chrome",
+                    "--profile-directory=Profile 1",
+                    "@@u",
+                    url,
+                    "@@",
+                ],
+            ),
+            (
+                "qutebrowser --untrusted-args %u",
+                vec![
+                    "qutebrowser",
+                    "--profile-directory=Profile 1",
+                    "--untrusted-args",
+                    url,
+                ],
+            ),
+            (
+                "browser --open=%u --progress=100%% %d",
+                vec![
+                    "browser",
+                    "--profile-directory=Profile 1",
+                    "--open=https://example.com/",
+                    "--progress=100%",
+                ],
+            ),
+            // non-browser apps might not have a field code
+            ("spotify", vec!["spotify", "--profile-directory=Profile 1", url]),
+        ];
+
+        for (exec, expected) in cases {
+            let command = parse_exec(exec).unwrap();
+            assert_eq!(
+                expand_exec(&command, &to_strings(&profile_args), &to_strings(&[url])),
+                to_strings(&expected),
+                "{}",
+                exec
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_exec_with_files() {
+        let command = parse_exec("viewer %F").unwrap();
+        assert_eq!(
+            expand_exec(&command, &[], &to_strings(&["file:///home/user/My%20File.pdf"])),
+            to_strings(&["viewer", "/home/user/My File.pdf"])
+        );
+
+        // multiple url arguments, e.g `-new-tab <url>`
+        let command = parse_exec("firefox %u").unwrap();
+        assert_eq!(
+            expand_exec(
+                &command,
+                &to_strings(&["-P", "Work"]),
+                &to_strings(&["-new-tab", "https://example.com/"])
+            ),
+            to_strings(&["firefox", "-P", "Work", "-new-tab", "https://example.com/"])
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "linux")]
+use url::Url;
+
+use crate::InstalledBrowserProfile;
+#[cfg(target_os = "linux")]
+use crate::desktop_exec_parser;
+
+const WEB_APP_PROFILE_DIR_PREFIX: &str = "org.gnome.Epiphany.WebApp_";
+
+// GNOME Web (Epiphany) keeps the default profile in ~/.local/share/epiphany and each web app
+// in its own profile dir next to it, e.g ~/.local/share/org.gnome.Epiphany.WebApp_<id>/,
+// which are launched with `--application-mode --profile=<dir>`.
+// Web app desktop files are either in the profile dir (older versions) or installed through
+// the portal (~/.local/share/xdg-desktop-portal/applications/org.gnome.Epiphany.WebApp_<id>.desktop)
+pub fn find_epiphany_profiles(
+    epiphany_profile_dir: &Path,
+    _binary_path: &Path,
+    _app_id: &str,
+) -> Vec<InstalledBrowserProfile> {
+    let mut browser_profiles: Vec<InstalledBrowserProfile> = Vec::new();
+    if epiphany_profile_dir.is_dir() {
+        // default profile is used without `--profile`
+        browser_profiles.push(epiphany_profile("", "Default".to_string(), None, vec![]));
+    }
+
+    let data_dir = match epiphany_profile_dir.parent() {
+        Some(data_dir) => data_dir,
+        None => return browser_profiles,
+    };
+    let entries = match fs::read_dir(data_dir) {
+        Ok(entries) => entries,
+        Err(_) => return browser_profiles,
+    };
+
+    for entry in entries.flatten() {
+        let web_app_profile_dir = entry.path();
+        if !is_web_app_profile_dir(web_app_profile_dir.as_path()) || !web_app_profile_dir.is_dir() {
+            continue;
+        }
+
+        let web_app_id = entry.file_name().to_string_lossy().to_string();
+        let desktop_entry_maybe =
+            find_web_app_desktop_file(data_dir, web_app_profile_dir.as_path())
+                .and_then(|desktop_file| WebAppDesktopEntry::parse(desktop_file.as_path()));
+
+        let web_app_name = desktop_entry_maybe
+            .as_ref()
+            .and_then(|desktop_entry| desktop_entry.name.clone())
+            .unwrap_or_else(|| {
+                web_app_id
+                    .trim_start_matches(WEB_APP_PROFILE_DIR_PREFIX)
+                    .to_string()
+            });
+
+        let legacy_icon_path = web_app_profile_dir.join("app-icon.png");
+        let web_app_icon_maybe = desktop_entry_maybe
+            .as_ref()
+            .and_then(|desktop_entry| desktop_entry.icon.clone())
+            .filter(|icon| Path::new(icon).is_file())
+            .or_else(|| {
+                legacy_icon_path
+                    .is_file()
+                    .then(|| legacy_icon_path.to_str().unwrap().to_string())
+            });
+
+        // web app is only offered for links of its own site
+        let restricted_url_patterns = desktop_entry_maybe
+            .as_ref()
+            .and_then(|desktop_entry| desktop_entry.url_host.clone())
+            .map(|url_host| vec![url_host])
+            .unwrap_or_default();
+
+        browser_profiles.push(epiphany_profile(
+            web_app_profile_dir.to_str().unwrap(),
+            web_app_name,
+            web_app_icon_maybe,
+            restricted_url_patterns,
+        ));
+    }
+
+    return browser_profiles;
+}
+
+pub fn is_web_app_profile_dir(profile_dir: &Path) -> bool {
+    return profile_dir
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .map(|file_name| file_name.starts_with(WEB_APP_PROFILE_DIR_PREFIX))
+        .unwrap_or(false);
+}
+
+fn find_web_app_desktop_file(data_dir: &Path, web_app_profile_dir: &Path) -> Option<PathBuf> {
+    let web_app_id = web_app_profile_dir.file_name()?.to_str()?;
+    let desktop_file_name = format!("{}.desktop", web_app_id);
+
+    let candidates = vec![
+        web_app_profile_dir.join(desktop_file_name.as_str()),
+        data_dir
+            .join("xdg-desktop-portal")
+            .join("applications")
+            .join(desktop_file_name.as_str()),
+    ];
+    return candidates.into_iter().find(|candidate| candidate.is_file());
+}
+
+fn epiphany_profile(
+    profile_dir: &str,
+    profile_name: String,
+    profile_icon: Option<String>,
+    profile_restricted_url_patterns: Vec<String>,
+) -> InstalledBrowserProfile {
+    return InstalledBrowserProfile {
+        profile_cli_arg_value: profile_dir.to_string(),
+        profile_cli_container_name: None,
+        profile_name: profile_name,
+        profile_icon: profile_icon,
+        profile_restricted_url_patterns: profile_restricted_url_patterns,
+        profile_email: None,
+        profile_hosted_domain: None,
+        profile_enterprise_domain: None,
+        profile_last_active: None,
+        profile_legacy_cli_arg_value: None,
+    };
+}
+
+// the few keys of a web app desktop file which are needed, e.g
+// Name=Gmail
+// Exec=epiphany --application-mode "--profile=/home/madis/.local/share/org.gnome.Epiphany.WebApp_abc" https://mail.google.com/
+// Icon=/home/madis/.local/share/org.gnome.Epiphany.WebApp_abc/app-icon.png
+struct WebAppDesktopEntry {
+    name: Option<String>,
+    icon: Option<String>,
+    // host of the web app url in `Exec`
+    url_host: Option<String>,
+}
+
+impl WebAppDesktopEntry {
+    #[cfg(target_os = "linux")]
+    fn parse(desktop_file: &Path) -> Option<Self> {
+        let content = fs::read_to_string(desktop_file).ok()?;
+        let locales = freedesktop_desktop_entry::get_languages_from_env();
+        let desktop_entry = freedesktop_desktop_entry::DesktopEntry::from_str(
+            desktop_file,
+            &content,
+            Some(&locales),
+        )
+        .ok()?;
+
+        let url_host = desktop_entry
+            .exec()
+            .and_then(desktop_exec_parser::parse_exec)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|argument| Url::parse(argument).ok())
+            .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+            .find_map(|url| url.host_str().map(|host| host.to_string()));
+
+        return Some(WebAppDesktopEntry {
+            name: desktop_entry.name(&locales).map(|name| name.to_string()),
+            icon: desktop_entry.icon().map(|icon| icon.to_string()),
+            url_host: url_host,
+        });
+    }
+
+    // GNOME Web is only on Linux
+    #[cfg(not(target_os = "linux"))]
+    fn parse(_desktop_file: &Path) -> Option<Self> {
+        return None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    // web app desktop files are only read on Linux
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_epiphany_profiles() {
+        let temp_dir = test_utils::create_temp_dir("epiphany-profiles");
+        let data_dir = temp_dir.path().to_path_buf();
+        let epiphany_profile_dir = data_dir.join("epiphany");
+        fs::create_dir_all(epiphany_profile_dir.as_path()).unwrap();
+
+        // web app with the desktop file in its profile dir
+        let gmail_dir = data_dir.join("org.gnome.Epiphany.WebApp_gmail123");
+        fs::create_dir_all(gmail_dir.as_path()).unwrap();
+        fs::write(gmail_dir.join("app-icon.png"), "").unwrap();
+        fs::write(
+            gmail_dir.join("org.gnome.Epiphany.WebApp_gmail123.desktop"),
+            format!(
+                "[Desktop Entry]\nName=Gmail\nExec=epiphany --application-mode \"--profile={}\" https://mail.google.com/\nType=Application\n",
+                gmail_dir.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+
+        // web app installed through the portal
+        let calendar_dir = data_dir.join("org.gnome.Epiphany.WebApp_calendar456");
+        fs::create_dir_all(calendar_dir.as_path()).unwrap();
+        let portal_dir = data_dir.join("xdg-desktop-portal").join("applications");
+        fs::create_dir_all(portal_dir.as_path()).unwrap();
+        fs::write(
+            portal_dir.join("org.gnome.Epiphany.WebApp_calendar456.desktop"),
+            "[Desktop Entry]\nName=Calendar\nExec=epiphany --application-mode --profile=/x https://calendar.google.com/r\n\n[Desktop Action new]\nName=New Window\n",
+        )
+        .unwrap();
+
+        // web app without a desktop file
+        fs::create_dir_all(data_dir.join("org.gnome.Epiphany.WebApp_orphan789")).unwrap();
+        // other app data
+        fs::create_dir_all(data_dir.join("org.gnome.Maps")).unwrap();
+
+        let binary_path = Path::new("/usr/bin/epiphany");
+        let mut profiles = find_epiphany_profiles(
+            epiphany_profile_dir.as_path(),
+            binary_path,
+            "org.gnome.Epiphany",
+        );
+        profiles.sort_by_key(|p| p.profile_name.clone());
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Calendar", "Default", "Gmail", "orphan789"]);
+
+        let calendar = &profiles[0];
+        assert_eq!(calendar.profile_cli_arg_value, calendar_dir.to_str().unwrap());
+        assert_eq!(
+            calendar.profile_restricted_url_patterns,
+            vec!["calendar.google.com".to_string()]
+        );
+        assert_eq!(calendar.profile_icon, None);
+
+        let default_profile = &profiles[1];
+        assert_eq!(default_profile.profile_cli_arg_value, "");
+        assert!(default_profile.profile_restricted_url_patterns.is_empty());
+
+        let gmail = &profiles[2];
+        assert_eq!(
+            gmail.profile_icon,
+            Some(gmail_dir.join("app-icon.png").to_str().unwrap().to_string())
+        );
+        assert_eq!(
+            gmail.profile_restricted_url_patterns,
+            vec!["mail.google.com".to_string()]
+        );
+
+        assert!(profiles[3].profile_restricted_url_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_is_web_app_profile_dir() {
+        assert!(is_web_app_profile_dir(Path::new(
+            "/home/madis/.local/share/org.gnome.Epiphany.WebApp_abc"
+        )));
+        assert!(!is_web_app_profile_dir(Path::new(
+            "/home/madis/.local/share/epiphany"
+        )));
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::Path;
+
+use configparser::ini::{Ini, IniDefault};
+
+use crate::InstalledBrowserProfile;
+
+// Falkon starts this profile when profiles.ini doesn't name one
+const DEFAULT_START_PROFILE: &str = "default";
+
+// Falkon keeps each profile in its own dir, named after the profile, e.g
+// ~/.config/falkon/profiles/default/
+// ~/.config/falkon/profiles/work/
+// profiles.ini next to them only remembers the profile to start with, e.g
+// [Profiles]
+// startProfile=work
+// Falkon lists the profiles by name, so the same order is kept, with the start profile first
+pub fn find_falkon_profiles(
+    falkon_profiles_dir: &Path,
+    _binary_path: &Path,
+    _app_id: &str,
+) -> Vec<InstalledBrowserProfile> {
+    let entries = match fs::read_dir(falkon_profiles_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut profile_names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    profile_names.sort();
+
+    let start_profile = find_start_profile(falkon_profiles_dir);
+    if let Some(index) = profile_names.iter().position(|name| name == &start_profile) {
+        let start_profile_name = profile_names.remove(index);
+        profile_names.insert(0, start_profile_name);
+    }
+
+    return profile_names
+        .into_iter()
+        .map(|profile_name| InstalledBrowserProfile {
+            profile_cli_arg_value: profile_name.to_string(),
+            profile_cli_container_name: None,
+            profile_name: profile_name,
+            profile_icon: None,
+            profile_restricted_url_patterns: vec![],
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: None,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: None,
+        })
+        .collect();
+}
+
+fn find_start_profile(falkon_profiles_dir: &Path) -> String {
+    let mut ini_default = IniDefault::default();
+    ini_default.case_sensitive = true;
+    let mut profiles_ini = Ini::new_from_defaults(ini_default);
+
+    return profiles_ini
+        .load(falkon_profiles_dir.join("profiles.ini"))
+        .ok()
+        .and_then(|_| profiles_ini.get("Profiles", "startProfile"))
+        .filter(|start_profile| !start_profile.is_empty())
+        .unwrap_or_else(|| DEFAULT_START_PROFILE.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    const PROFILES_INI: &str = r#"[Profiles]
+startProfile=Work Stuff
+"#;
+
+    #[test]
+    fn test_find_falkon_profiles() {
+        let temp_dir = test_utils::create_temp_dir("falkon-profiles");
+        let dir = temp_dir.path().to_path_buf();
+        fs::write(dir.join("profiles.ini"), PROFILES_INI).unwrap();
+        fs::create_dir_all(dir.join("default")).unwrap();
+        fs::create_dir_all(dir.join("Work Stuff")).unwrap();
+        fs::create_dir_all(dir.join("archive")).unwrap();
+
+        let binary_path = Path::new("/usr/bin/falkon");
+        let profiles = find_falkon_profiles(dir.as_path(), binary_path, "org.kde.falkon");
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Work Stuff", "archive", "default"]);
+        assert_eq!(profiles[0].profile_cli_arg_value, "Work Stuff");
+    }
+
+    #[test]
+    fn test_find_falkon_profiles_without_profiles_ini() {
+        let temp_dir = test_utils::create_temp_dir("falkon-profiles");
+        let dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(dir.join("default")).unwrap();
+        fs::create_dir_all(dir.join("archive")).unwrap();
+
+        let binary_path = Path::new("/usr/bin/falkon");
+        let profiles = find_falkon_profiles(dir.as_path(), binary_path, "org.kde.falkon");
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["default", "archive"]);
+    }
+
+    #[test]
+    fn test_find_falkon_profiles_without_profiles_dir() {
+        let temp_dir = test_utils::create_temp_dir("falkon-profiles");
+        let dir = temp_dir.path().join("profiles");
+
+        let binary_path = Path::new("/usr/bin/falkon");
+        let profiles = find_falkon_profiles(dir.as_path(), binary_path, "org.kde.falkon");
+        assert!(profiles.is_empty());
+    }
+}
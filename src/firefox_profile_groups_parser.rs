@@ -0,0 +1,366 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags};
+use tracing::{debug, info, warn};
+
+// Newer Firefox versions have a profile switcher ("selectable profiles"), which keeps its
+// profiles in "<profiles dir>/Profile Groups/<store id>.sqlite"; profiles.ini lists only the
+// default profile of each group
+pub(crate) fn find_profile_group_profiles(firefox_profiles_dir: &Path) -> Vec<ProfileGroupProfile> {
+    let mut profile_group_profiles: Vec<ProfileGroupProfile> = Vec::new();
+
+    let profile_groups_dir = firefox_profiles_dir.join("Profile Groups");
+    let read_dir_result = fs::read_dir(profile_groups_dir.as_path());
+    if read_dir_result.is_err() {
+        debug!(
+            "Skipping profile groups, because '{}' does not exist",
+            profile_groups_dir.display()
+        );
+        return profile_group_profiles;
+    }
+
+    let mut database_paths: Vec<PathBuf> = read_dir_result
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|a| a == "sqlite").unwrap_or(false))
+        .collect();
+    database_paths.sort();
+
+    for database_path in database_paths {
+        let profiles_result = read_profiles_table(database_path.as_path());
+        if let Err(error) = profiles_result {
+            warn!("Could not read {}: {}", database_path.display(), error);
+            continue;
+        }
+
+        for profile_row in profiles_result.unwrap() {
+            let profile_dir_maybe = resolve_profile_dir(firefox_profiles_dir, &profile_row.path);
+            if profile_dir_maybe.is_none() {
+                info!(
+                    "Skipping profile group profile '{}', because its directory does not exist",
+                    profile_row.path
+                );
+                continue;
+            }
+
+            profile_group_profiles.push(ProfileGroupProfile {
+                name: profile_row.name,
+                dir: profile_dir_maybe.unwrap(),
+                avatar: profile_row.avatar,
+                theme_fg: parse_hex_color(profile_row.theme_fg.as_str()),
+                theme_bg: parse_hex_color(profile_row.theme_bg.as_str()),
+            });
+        }
+    }
+
+    return profile_group_profiles;
+}
+
+pub(crate) struct ProfileGroupProfile {
+    pub name: String,
+    // absolute path of the profile directory, which is used for `--profile <dir>`
+    pub dir: PathBuf,
+    // name of the built-in avatar, e.g "briefcase"
+    pub avatar: String,
+    // text color of the profile theme
+    pub theme_fg: Option<[u8; 3]>,
+    // background color of the profile theme
+    pub theme_bg: Option<[u8; 3]>,
+}
+
+struct ProfilesTableRow {
+    path: String,
+    name: String,
+    avatar: String,
+    theme_fg: String,
+    theme_bg: String,
+}
+
+fn read_profiles_table(database_path: &Path) -> rusqlite::Result<Vec<ProfilesTableRow>> {
+    // Firefox might have the database open, so only read it
+    let connection = Connection::open_with_flags(
+        database_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+
+    let mut statement = connection
+        .prepare("SELECT path, name, avatar, themeFg, themeBg FROM Profiles ORDER BY id")?;
+    let rows = statement.query_map([], |row| {
+        Ok(ProfilesTableRow {
+            path: row.get(0)?,
+            name: row.get(1)?,
+            avatar: row.get(2)?,
+            theme_fg: row.get(3)?,
+            theme_bg: row.get(4)?,
+        })
+    })?;
+
+    return rows.collect();
+}
+
+// path is relative to the default profiles root, which is the profiles dir itself in linux,
+// but its "Profiles" subdirectory in macOS and Windows
+fn resolve_profile_dir(firefox_profiles_dir: &Path, profile_path: &str) -> Option<PathBuf> {
+    let path = Path::new(profile_path);
+    if path.is_absolute() {
+        return Some(path.to_path_buf()).filter(|dir| dir.exists());
+    }
+
+    return [
+        firefox_profiles_dir.join(path),
+        firefox_profiles_dir.join("Profiles").join(path),
+    ]
+    .into_iter()
+    .find(|dir| dir.exists());
+}
+
+// 16x16 glyph of a built-in avatar ("#" is drawn), which is drawn on the profile icon;
+// custom avatars (images chosen by the user) are not supported
+pub(crate) fn avatar_glyph(avatar: &str) -> Option<[&'static str; 16]> {
+    return match avatar {
+        "book" => Some([
+            "................",
+            "................",
+            ".#####....#####.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            ".######..######.",
+            "..#####..#####..",
+            ".......##.......",
+            "................",
+            "................",
+            "................",
+        ]),
+        "briefcase" => Some([
+            "................",
+            "................",
+            "......####......",
+            ".....#....#.....",
+            ".....#....#.....",
+            ".##############.",
+            ".##############.",
+            ".##############.",
+            ".######..######.",
+            ".##############.",
+            ".##############.",
+            ".##############.",
+            ".##############.",
+            "................",
+            "................",
+            "................",
+        ]),
+        "flower" => Some([
+            "................",
+            "......####......",
+            ".....######.....",
+            "..##..####..##..",
+            ".####..##..####.",
+            ".#####....#####.",
+            "..####.##.####..",
+            "......####......",
+            "..####.##.####..",
+            ".#####....#####.",
+            ".####..##..####.",
+            "..##..####..##..",
+            ".....######.....",
+            "......####......",
+            "................",
+            "................",
+        ]),
+        "heart" => Some([
+            "................",
+            "................",
+            "...####..####...",
+            "..############..",
+            ".##############.",
+            ".##############.",
+            ".##############.",
+            "..############..",
+            "...##########...",
+            "....########....",
+            ".....######.....",
+            "......####......",
+            ".......##.......",
+            "................",
+            "................",
+            "................",
+        ]),
+        "shopping" => Some([
+            "................",
+            "......####......",
+            ".....#....#.....",
+            ".....#....#.....",
+            "...##########...",
+            "...##########...",
+            "...##########...",
+            "..############..",
+            "..############..",
+            "..############..",
+            "..############..",
+            ".##############.",
+            ".##############.",
+            "................",
+            "................",
+            "................",
+        ]),
+        "star" => Some([
+            "................",
+            ".......##.......",
+            ".......##.......",
+            "......####......",
+            "......####......",
+            ".##############.",
+            "..############..",
+            "...##########...",
+            "....########....",
+            "....########....",
+            "...####..####...",
+            "...###....###...",
+            "..###......###..",
+            "..##........##..",
+            "................",
+            "................",
+        ]),
+        _ => None,
+    };
+}
+
+// "#1c1b22" to [28, 27, 34]
+fn parse_hex_color(hex_color: &str) -> Option<[u8; 3]> {
+    let hex = hex_color.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let r = u8::from_str_radix(hex.get(0..2)?, 16).ok()?;
+    let g = u8::from_str_radix(hex.get(2..4)?, 16).ok()?;
+    let b = u8::from_str_radix(hex.get(4..6)?, 16).ok()?;
+    return Some([r, g, b]);
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_utils;
+
+    fn create_profiles_dir(test_name: &str) -> TempDir {
+        let temp_dir = test_utils::create_temp_dir(test_name);
+        fs::create_dir_all(temp_dir.path().join("Profile Groups")).unwrap();
+        return temp_dir;
+    }
+
+    fn create_database(profiles_dir: &Path, store_id: &str, rows: &[(&str, &str, &str, &str)]) {
+        let database_path = profiles_dir
+            .join("Profile Groups")
+            .join(format!("{}.sqlite", store_id));
+        let connection = Connection::open(database_path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE Profiles (
+                    id INTEGER NOT NULL,
+                    path TEXT NOT NULL UNIQUE,
+                    name TEXT NOT NULL,
+                    avatar TEXT NOT NULL,
+                    themeId TEXT NOT NULL,
+                    themeFg TEXT NOT NULL,
+                    themeBg TEXT NOT NULL,
+                    PRIMARY KEY(id)
+                );
+                CREATE TABLE SharedPrefs (
+                    id INTEGER NOT NULL,
+                    name TEXT NOT NULL UNIQUE,
+                    value BLOB,
+                    isBoolean INTEGER,
+                    PRIMARY KEY(id)
+                );",
+            )
+            .unwrap();
+
+        for (path, name, avatar, theme_bg) in rows {
+            connection
+                .execute(
+                    "INSERT INTO Profiles (path, name, avatar, themeId, themeFg, themeBg)
+                     VALUES (?1, ?2, ?3, 'default-theme@mozilla.org', '#15141a', ?4)",
+                    [path, name, avatar, theme_bg],
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_find_profile_group_profiles() {
+        let temp_dir = create_profiles_dir("firefox-profile-groups");
+        let profiles_dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(profiles_dir.join("abc.Work")).unwrap();
+        fs::create_dir_all(profiles_dir.join("Profiles").join("def.Personal")).unwrap();
+        create_database(
+            profiles_dir.as_path(),
+            "a1b2c3",
+            &[
+                ("abc.Work", "Work", "briefcase", "#0060df"),
+                ("def.Personal", "Personal", "heart", "not a color"),
+            ],
+        );
+
+        let profiles = find_profile_group_profiles(profiles_dir.as_path());
+        assert_eq!(profiles.len(), 2);
+
+        assert_eq!(profiles[0].name, "Work");
+        assert_eq!(profiles[0].dir, profiles_dir.join("abc.Work"));
+        assert_eq!(profiles[0].avatar, "briefcase");
+        assert_eq!(profiles[0].theme_bg, Some([0, 96, 223]));
+
+        assert_eq!(profiles[1].name, "Personal");
+        assert_eq!(
+            profiles[1].dir,
+            profiles_dir.join("Profiles").join("def.Personal")
+        );
+        assert_eq!(profiles[1].theme_bg, None);
+    }
+
+    #[test]
+    fn test_skips_missing_profile_dirs() {
+        let temp_dir = create_profiles_dir("firefox-profile-groups");
+        let profiles_dir = temp_dir.path().to_path_buf();
+        create_database(
+            profiles_dir.as_path(),
+            "a1b2c3",
+            &[("deleted.Old", "Old", "star", "#ffffff")],
+        );
+
+        assert!(find_profile_group_profiles(profiles_dir.as_path()).is_empty());
+    }
+
+    #[test]
+    fn test_without_profile_groups() {
+        let temp_dir = test_utils::create_temp_dir("firefox-profile-groups");
+        let profiles_dir = temp_dir.path().join("does-not-exist");
+        assert!(find_profile_group_profiles(profiles_dir.as_path()).is_empty());
+    }
+
+    #[test]
+    fn test_avatar_glyph() {
+        for avatar in ["book", "briefcase", "flower", "heart", "shopping", "star"] {
+            let glyph = avatar_glyph(avatar).unwrap();
+            assert!(glyph.iter().all(|row| row.len() == 16));
+            assert!(glyph.iter().any(|row| row.contains('#')));
+        }
+        assert!(avatar_glyph("custom").is_none());
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#1c1b22"), Some([28, 27, 34]));
+        assert_eq!(parse_hex_color("1c1b22"), None);
+        assert_eq!(parse_hex_color("#fff"), None);
+    }
+}
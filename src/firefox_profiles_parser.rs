@@ -1,29 +1,194 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use configparser::ini::{Ini, IniDefault};
 use naive_cityhash::cityhash64;
 use serde_json::Value;
 use tracing::{debug, info};
 
-use crate::InstalledBrowserProfile;
+use crate::{firefox_profile_groups_parser, paths, utils, InstalledBrowserProfile};
 
 pub fn find_firefox_profiles(
     firefox_profiles_dir: &Path,
     binary_path: &Path,
-    _app_id: &str,
+    app_id: &str,
 ) -> Vec<InstalledBrowserProfile> {
-    let binary_dir = binary_path.parent().and_then(|p| p.to_str()).unwrap_or("");
-    // binary_dir is the path where binary is (without trailing slash)
-    let install_dir_hash = hash_firefox_install_dir(binary_dir);
-    //run_arg = r#"open -b com.google.Chrome -n --args --profile-directory="Profile 1" https://www.google.com"#
+    let mut browser_profiles: Vec<InstalledBrowserProfile> = Vec::new();
 
-    info!("Dir {}", binary_path.to_str().unwrap());
-    info!("Dir `{}` has hash {}", binary_dir, install_dir_hash.as_str());
+    let profiles_ini_profiles = find_profiles_ini_profiles(firefox_profiles_dir, binary_path);
+    let profile_group_profiles =
+        firefox_profile_groups_parser::find_profile_group_profiles(firefox_profiles_dir);
 
-    let mut browser_profiles: Vec<InstalledBrowserProfile> = Vec::new();
+    for profiles_ini_profile in &profiles_ini_profiles {
+        // default profile of a profile group is listed in both
+        let in_profile_group = profile_group_profiles
+            .iter()
+            .any(|p| is_same_dir(p.dir.as_path(), profiles_ini_profile.dir.as_path()));
+        if in_profile_group {
+            continue;
+        }
+
+        // profiles used to be launched by name (`-P <name>`)
+        add_profile_and_containers(
+            &mut browser_profiles,
+            profiles_ini_profile.dir.as_path(),
+            profiles_ini_profile.name.as_str(),
+            Some(profiles_ini_profile.name.as_str()),
+            None,
+        );
+    }
+
+    for profile_group_profile in profile_group_profiles {
+        let profile_icon_path = save_profile_group_icon(app_id, &profile_group_profile);
+
+        add_profile_and_containers(
+            &mut browser_profiles,
+            profile_group_profile.dir.as_path(),
+            profile_group_profile.name.as_str(),
+            None,
+            profile_icon_path,
+        );
+    }
+
+    return browser_profiles;
+}
+
+// Profiles are launched by their absolute path (`--profile <dir>`), which is unambiguous even
+// if profiles share a name, and works also for profiles which are not in profiles.ini
+fn add_profile_and_containers(
+    browser_profiles: &mut Vec<InstalledBrowserProfile>,
+    profile_dir: &Path,
+    profile_name: &str,
+    legacy_cli_arg_value: Option<&str>,
+    profile_icon: Option<String>,
+) {
+    let profile_cli_arg_value = profile_dir.to_str().unwrap();
+    let legacy_cli_arg_value = legacy_cli_arg_value.map(|a| a.to_string());
+
+    let mut containers = Vec::new();
+
+    let mut open_url_in_container_extension_installed = false;
+    let extensions_json_file = profile_dir.join("extensions.json");
+    if !extensions_json_file.exists() {
+        info!(
+            "Skipping containers for profile '{}', because it does not have extensions.json file",
+            profile_dir.display()
+        );
+    } else {
+        open_url_in_container_extension_installed =
+            has_open_url_in_container_extension_installed(extensions_json_file.as_path());
+    }
+
+    if open_url_in_container_extension_installed {
+        let containers_json_file = profile_dir.join("containers.json");
+        if !containers_json_file.exists() {
+            info!(
+                "Skipping containers for profile '{}', because it does not have containers.json file",
+                profile_dir.display()
+            );
+        } else {
+            // containers for this profile
+            info!(
+                "Checking containers from {}",
+                containers_json_file.as_path().to_str().unwrap()
+            );
+            containers = containers_json_map(containers_json_file.as_path());
+        }
+    }
+
+    // Even if profile has containers, also add a non-container option
+    browser_profiles.push(InstalledBrowserProfile {
+        profile_cli_arg_value: profile_cli_arg_value.to_string(),
+        profile_cli_container_name: None,
+        profile_name: profile_name.to_string(),
+        profile_icon: profile_icon.clone(),
+        profile_restricted_url_patterns: vec![],
+        profile_email: None,
+        profile_hosted_domain: None,
+        profile_enterprise_domain: None,
+        profile_last_active: None,
+        profile_legacy_cli_arg_value: legacy_cli_arg_value.clone(),
+    });
+
+    for container in containers {
+        browser_profiles.push(InstalledBrowserProfile {
+            profile_cli_arg_value: profile_cli_arg_value.to_string(),
+            profile_cli_container_name: Some(container.id.to_string()),
+            profile_name: profile_name.to_string() + " " + container.name.as_str(),
+            profile_icon: profile_icon.clone(),
+            profile_restricted_url_patterns: vec![],
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: None,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: legacy_cli_arg_value.clone(),
+        })
+    }
+}
+
+fn is_same_dir(dir1: &Path, dir2: &Path) -> bool {
+    return match (dir1.canonicalize(), dir2.canonicalize()) {
+        (Ok(canonical_dir1), Ok(canonical_dir2)) => canonical_dir1 == canonical_dir2,
+        _ => dir1 == dir2,
+    };
+}
+
+// circle in the profile theme color, similar to the profile switcher in Firefox
+// Avatar drawn with the theme colors of the profile, like in the Firefox profile switcher
+fn save_profile_group_icon(
+    app_id: &str,
+    profile_group_profile: &firefox_profile_groups_parser::ProfileGroupProfile,
+) -> Option<String> {
+    let avatar_glyph_maybe =
+        firefox_profile_groups_parser::avatar_glyph(profile_group_profile.avatar.as_str());
+    if avatar_glyph_maybe.is_none() && profile_group_profile.theme_bg.is_none() {
+        debug!(
+            "Profile group profile '{}' has no theme color and a custom avatar '{}'",
+            profile_group_profile.name, profile_group_profile.avatar
+        );
+        return None;
+    }
+
+    // colors of the default Firefox theme
+    let theme_bg = profile_group_profile.theme_bg.unwrap_or([240, 240, 244]);
+    let theme_fg = profile_group_profile.theme_fg.unwrap_or([21, 20, 26]);
+    let glyph_maybe = avatar_glyph_maybe.map(|avatar_glyph| (avatar_glyph, theme_fg));
+
+    let profiles_icons_root = paths::get_cache_root_dir()
+        .join("icons")
+        .join("profiles")
+        .join(app_id);
+    fs::create_dir_all(profiles_icons_root.as_path()).unwrap();
+
+    let profile_dir_name = profile_group_profile.dir.file_name()?.to_str()?;
+    let png_file_path = profiles_icons_root.join(profile_dir_name.to_string() + ".png");
+    utils::save_as_colored_circle(theme_bg, glyph_maybe, png_file_path.as_path());
+
+    return png_file_path.to_str().map(|path| path.to_string());
+}
+
+// Profiles listed in profiles.ini, which is shared by Firefox and other Mozilla based apps
+// (e.g Thunderbird)
+pub(crate) fn find_profiles_ini_profiles(
+    firefox_profiles_dir: &Path,
+    binary_path: &Path,
+) -> Vec<ProfilesIniProfile> {
+    // hashes of the directories, where this installation might think its binary is
+    let install_dir_hashes: Vec<String> = find_install_dirs(binary_path, firefox_profiles_dir)
+        .iter()
+        .map(|install_dir| {
+            let install_dir_hash = hash_firefox_install_dir(install_dir.as_str());
+            info!("Dir `{}` has hash {}", install_dir, install_dir_hash.as_str());
+            install_dir_hash
+        })
+        .collect();
+    //run_arg = r#"open -b com.google.Chrome -n --args --profile-directory="Profile 1" https://www.google.com"#
+
+    let mut profiles_ini_profiles: Vec<ProfilesIniProfile> = Vec::new();
 
     let profiles_ini_path = firefox_profiles_dir.join("profiles.ini");
     debug!("profiles_ini_path: {:?}", profiles_ini_path);
@@ -31,16 +196,14 @@ pub fn find_firefox_profiles(
     // When user has installed Firefox, but never ran it, then the profiles.ini does not yet exist
     if !profiles_ini_path.exists() {
         info!(
-            "Skipping Firefox, because profiles.ini does not exist at '{}'",
+            "Skipping profiles, because profiles.ini does not exist at '{}'",
             profiles_ini_path.display()
         );
 
-        return browser_profiles;
+        return profiles_ini_profiles;
     }
 
-    let mut ini_default = IniDefault::default();
-    ini_default.case_sensitive = true;
-    let mut profiles_ini_config = Ini::new_from_defaults(ini_default);
+    let mut profiles_ini_config = Ini::new_from_defaults(ini_default_case_sensitive());
 
     // You can easily load a file to get a clone of the map:
     let profiles_ini_map = profiles_ini_config.load(&profiles_ini_path).unwrap();
@@ -48,55 +211,30 @@ pub fn find_firefox_profiles(
     // You can also safely not store the reference and access it later with get_map_ref() or get a clone with get_map()
 
     // Make two runs over profiles.ini
-    // if profile has a hash, but it's not our hash then skip it (except when disabling hash feature?)
+    // if profile has a hash, but it's not our hash then skip it
 
-    // profile locked to an firefox instance
-    let mut locked_profile_path_and_hash: HashMap<String, String> = HashMap::new();
-
-    // 1. find all default profiles,
+    // 1. find all default profiles of each installation,
     //    see https://support.mozilla.org/en-US/kb/understanding-depth-profile-installation
-    for (install_key, install_values) in profiles_ini_map.into_iter() {
-        // e.g [Install9F3C89D8F8FDBC89]
-        if !install_key.starts_with("Install") {
-            continue;
-        }
-        // e.g "9F3C89D8F8FDBC89"
-        let installation_dir_hashed = install_key.strip_prefix("Install").unwrap();
-        if !install_values.contains_key("Default") {
-            continue;
+    //    e.g [Install9F3C89D8F8FDBC89] in profiles.ini
+    let mut install_locks = InstallLocks::new();
+    install_locks.add_from_ini_map(profiles_ini_map, "Install");
+
+    //    and the same in installs.ini, e.g [9F3C89D8F8FDBC89]
+    let installs_ini_path = firefox_profiles_dir.join("installs.ini");
+    if installs_ini_path.exists() {
+        let mut installs_ini_config = Ini::new_from_defaults(ini_default_case_sensitive());
+        if let Ok(installs_ini_map) = installs_ini_config.load(&installs_ini_path) {
+            install_locks.add_from_ini_map(installs_ini_map, "");
         }
-        if !install_values.contains_key("Locked") {
-            continue;
-        }
-        // build a map of profile to installation hash?
-
-        /*if installation_dir_hashed != install_dir_hash {
-            // skip if not a rule for this browser
-            continue;
-        }*/
-
-        // can be relative or absolute
-        let default_profile_path = install_values
-            .get("Default")
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .unwrap()
-            .to_string();
-
-        let default_locked = install_values
-            .get("Locked")
-            .as_ref()
-            .unwrap()
-            .as_ref()
-            .unwrap()
-            .to_string();
-        let is_profile_locked = default_locked == "1";
+    }
 
-        if is_profile_locked {
-            locked_profile_path_and_hash
-                .insert(default_profile_path.clone(), installation_dir_hashed.to_string());
-        }
+    // If the hash of this installation is not known (e.g it's never been run, or it's in some
+    // unexpected location), then it's safer to show all profiles than none
+    let is_own_install_known = install_dir_hashes
+        .iter()
+        .any(|hash| install_locks.install_hashes.contains(hash));
+    if !is_own_install_known {
+        info!("Installation not found in profiles.ini or installs.ini, showing all profiles");
     }
 
     let profiles_ini_map = profiles_ini_config.load(&profiles_ini_path).unwrap();
@@ -113,16 +251,17 @@ pub fn find_firefox_profiles(
             .unwrap()
             .to_string();
 
-        let profile_hash_maybe = locked_profile_path_and_hash.get(profile_path.as_str());
+        let profile_hash_maybe = install_locks
+            .locked_profile_path_and_hash
+            .get(profile_path.as_str());
         if let Some(profile_hash) = profile_hash_maybe {
-            // if this profile has some other hash than current firefox, then skip the profile
-            if profile_hash.to_string() != install_dir_hash {
-                // `install_dir_hash`, that we calculate based on binary path,
-                // is not always correct, e.g in case of snap package in linux,
-                // so, let's not use it yet to exclude profiles.
-                // Users can always manually hide these profiles instead.
-                // TODO: https://github.com/Browsers-software/barowsers/issues/81
-                //continue;
+            // if this profile is locked to some other installation, then skip the profile
+            if is_own_install_known && !install_dir_hashes.contains(profile_hash) {
+                info!(
+                    "Skipping profile '{}', because it is locked to installation {}",
+                    profile_path, profile_hash
+                );
+                continue;
             }
         }
 
@@ -139,37 +278,6 @@ pub fn find_firefox_profiles(
             continue;
         }
 
-        let mut containers = Vec::new();
-
-        let mut open_url_in_container_extension_installed = false;
-        let extensions_json_file = profile_dir.join("extensions.json");
-        if !extensions_json_file.exists() {
-            info!(
-                "Skipping containers for profile '{}', because it does not have extensions.json file",
-                profile_dir.display()
-            );
-        } else {
-            open_url_in_container_extension_installed =
-                has_open_url_in_container_extension_installed(extensions_json_file.as_path());
-        }
-
-        if open_url_in_container_extension_installed {
-            let containers_json_file = profile_dir.join("containers.json");
-            if !containers_json_file.exists() {
-                info!(
-                "Skipping containers for profile '{}', because it does not have containers.json file",
-                profile_dir.display()
-            );
-            } else {
-                // containers for this profile
-                info!(
-                    "Checking containers from {}",
-                    containers_json_file.as_path().to_str().unwrap()
-                );
-                containers = containers_json_map(containers_json_file.as_path());
-            }
-        }
-
         let name_maybe = profile_values
             .get("Name")
             .and_then(|a| a.as_ref())
@@ -177,29 +285,151 @@ pub fn find_firefox_profiles(
 
         let profile_name = name_maybe.unwrap();
 
-        // Even if profile has containers, also add a non-container option
-        browser_profiles.push(InstalledBrowserProfile {
-            profile_cli_arg_value: profile_name.to_string(),
-            profile_cli_container_name: None,
-            profile_name: profile_name.to_string(),
-            profile_icon: None,
-            profile_restricted_url_patterns: vec![],
+        profiles_ini_profiles.push(ProfilesIniProfile {
+            name: profile_name,
+            dir: profile_dir,
         });
+    }
+
+    return profiles_ini_profiles;
+}
+
+fn ini_default_case_sensitive() -> IniDefault {
+    let mut ini_default = IniDefault::default();
+    ini_default.case_sensitive = true;
+    return ini_default;
+}
+
+struct InstallLocks {
+    // all installations, which have a default profile
+    install_hashes: HashSet<String>,
+    // profile locked to an firefox installation
+    locked_profile_path_and_hash: HashMap<String, String>,
+}
+
+impl InstallLocks {
+    fn new() -> Self {
+        Self {
+            install_hashes: HashSet::new(),
+            locked_profile_path_and_hash: HashMap::new(),
+        }
+    }
+
+    fn add_from_ini_map(
+        &mut self,
+        ini_map: HashMap<String, HashMap<String, Option<String>>>,
+        section_prefix: &str,
+    ) {
+        for (install_key, install_values) in ini_map.into_iter() {
+            // e.g "9F3C89D8F8FDBC89"
+            let installation_dir_hashed_maybe = install_key.strip_prefix(section_prefix);
+            if installation_dir_hashed_maybe.is_none() {
+                continue;
+            }
+            let installation_dir_hashed = installation_dir_hashed_maybe.unwrap();
+
+            // can be relative or absolute
+            let default_profile_path_maybe = install_values.get("Default").cloned().flatten();
+            if default_profile_path_maybe.is_none() {
+                continue;
+            }
+            let default_profile_path = default_profile_path_maybe.unwrap();
+
+            self.install_hashes
+                .insert(installation_dir_hashed.to_string());
 
-        if !containers.is_empty() {
-            for container in containers {
-                browser_profiles.push(InstalledBrowserProfile {
-                    profile_cli_arg_value: profile_name.to_string(),
-                    profile_cli_container_name: Some(container.id.to_string()),
-                    profile_name: profile_name.to_string() + " " + container.name.as_str(),
-                    profile_icon: None,
-                    profile_restricted_url_patterns: vec![],
-                })
+            let default_locked = install_values.get("Locked").cloned().flatten();
+            let is_profile_locked = default_locked.as_deref() == Some("1");
+            if is_profile_locked {
+                self.locked_profile_path_and_hash
+                    .insert(default_profile_path, installation_dir_hashed.to_string());
             }
         }
     }
+}
 
-    return browser_profiles;
+// Firefox hashes the directory of its binary, but the binary path we know is often a launcher
+// script or a symlink (e.g "/usr/bin/firefox"), or in case of snap and flatpak it is outside
+// of the sandbox, where Firefox sees its binary in another path
+fn find_install_dirs(binary_path: &Path, firefox_profiles_dir: &Path) -> Vec<String> {
+    let mut install_dirs: Vec<String> = Vec::new();
+
+    // binary_dir is the path where binary is (without trailing slash)
+    if let Some(binary_dir) = binary_path.parent().and_then(|p| p.to_str()) {
+        install_dirs.push(binary_dir.to_string());
+    }
+
+    let canonical_binary_dir_maybe = binary_path.canonicalize().ok().and_then(|p| {
+        p.parent()
+            .and_then(|dir| dir.to_str())
+            .map(|a| a.to_string())
+    });
+    if let Some(canonical_binary_dir) = canonical_binary_dir_maybe {
+        install_dirs.push(canonical_binary_dir);
+    }
+
+    if let Some(snap_install_dir) = snap_install_dir(binary_path, firefox_profiles_dir) {
+        install_dirs.push(snap_install_dir);
+    }
+
+    if let Some(flatpak_install_dir) = flatpak_install_dir(binary_path, firefox_profiles_dir) {
+        install_dirs.push(flatpak_install_dir);
+    }
+
+    install_dirs.dedup();
+    return install_dirs;
+}
+
+// "/snap/bin/firefox" or "~/snap/firefox/common/.mozilla/firefox"
+// to "/snap/firefox/current/usr/lib/firefox"
+fn snap_install_dir(binary_path: &Path, firefox_profiles_dir: &Path) -> Option<String> {
+    let snap_name_maybe = binary_path
+        .strip_prefix("/snap/bin")
+        .ok()
+        .or_else(|| binary_path.strip_prefix("/snap").ok())
+        .and_then(|rest| rest.iter().next())
+        .or_else(|| path_component_after(firefox_profiles_dir, "snap"))
+        .and_then(|name| name.to_str());
+
+    return snap_name_maybe
+        .map(|snap_name| format!("/snap/{snap_name}/current/usr/lib/{snap_name}"));
+}
+
+// "/var/lib/flatpak/exports/bin/org.mozilla.firefox" or
+// "~/.var/app/org.mozilla.firefox/.mozilla/firefox" to "/app/lib/firefox"
+fn flatpak_install_dir(binary_path: &Path, firefox_profiles_dir: &Path) -> Option<String> {
+    let is_flatpak_binary = binary_path
+        .to_str()
+        .map(|path| path.contains("/flatpak/exports/bin/"))
+        .unwrap_or(false);
+
+    let flatpak_id_maybe = if is_flatpak_binary {
+        binary_path.file_name()
+    } else {
+        path_component_after(firefox_profiles_dir, "app")
+            .filter(|_| firefox_profiles_dir.iter().any(|c| c == ".var"))
+    };
+
+    // e.g "org.mozilla.firefox" to "firefox"
+    let app_name_maybe = flatpak_id_maybe
+        .and_then(|flatpak_id| flatpak_id.to_str())
+        .and_then(|flatpak_id| flatpak_id.rsplit('.').next())
+        .map(|app_name| app_name.to_lowercase());
+
+    return app_name_maybe.map(|app_name| format!("/app/lib/{app_name}"));
+}
+
+fn path_component_after<'a>(path: &'a Path, component: &str) -> Option<&'a OsStr> {
+    let mut components = path.iter();
+    components.find(|c| *c == component)?;
+    return components.next();
+}
+
+pub(crate) struct ProfilesIniProfile {
+    // value of `Name`, which is also used for `-P <name>`
+    pub name: String,
+    // absolute path of the profile directory
+    pub dir: PathBuf,
 }
 
 // has "open-url-in-container" extension installed, which adds "ext+container" protocol support
@@ -291,6 +521,98 @@ fn hash_firefox_install_dir(ff_binary_dir: &str) -> String {
     let path_as_utf16_slice = path_as_utf16_bytes.as_slice();
 
     let hash_u64: u64 = cityhash64(path_as_utf16_slice);
+    // not zero-padded, same as "%" PRIX64 in Firefox
     let hash_u64_str = format!("{:X}", hash_u64);
     return hash_u64_str;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_firefox_install_dir() {
+        assert_eq!(
+            hash_firefox_install_dir("C:\\Program Files\\Mozilla Firefox"),
+            "308046B0AF4A39CB"
+        );
+        // hash with a leading zero, e.g [Install6AFDA46A1A8AD48] in profiles.ini
+        assert_eq!(hash_firefox_install_dir("/opt/firefox"), "6AFDA46A1A8AD48");
+    }
+
+    #[test]
+    fn test_snap_install_dir() {
+        let no_profiles_dir = Path::new("/home/madis/.mozilla/firefox");
+        assert_eq!(
+            snap_install_dir(Path::new("/snap/bin/firefox"), no_profiles_dir),
+            Some("/snap/firefox/current/usr/lib/firefox".to_string())
+        );
+        assert_eq!(
+            snap_install_dir(
+                Path::new("/usr/bin/firefox"),
+                Path::new("/home/madis/snap/firefox/common/.mozilla/firefox")
+            ),
+            Some("/snap/firefox/current/usr/lib/firefox".to_string())
+        );
+        assert_eq!(
+            snap_install_dir(Path::new("/usr/bin/firefox"), no_profiles_dir),
+            None
+        );
+    }
+
+    #[test]
+    fn test_flatpak_install_dir() {
+        let no_profiles_dir = Path::new("/home/madis/.mozilla/firefox");
+        assert_eq!(
+            flatpak_install_dir(
+                Path::new("/var/lib/flatpak/exports/bin/org.mozilla.firefox"),
+                no_profiles_dir
+            ),
+            Some("/app/lib/firefox".to_string())
+        );
+        assert_eq!(
+            flatpak_install_dir(
+                Path::new("/usr/bin/flatpak"),
+                Path::new("/home/madis/.var/app/org.mozilla.firefox/.mozilla/firefox")
+            ),
+            Some("/app/lib/firefox".to_string())
+        );
+        assert_eq!(
+            flatpak_install_dir(Path::new("/usr/bin/firefox"), no_profiles_dir),
+            None
+        );
+    }
+
+    #[test]
+    fn test_install_locks() {
+        let mut ini = Ini::new_from_defaults(ini_default_case_sensitive());
+        let ini_map = ini
+            .read(
+                "[Install4F96D1932A9F858E]
+Default=Profiles/abc.default-release
+Locked=1
+
+[Install6AFDA46A1A8AD48]
+Default=Profiles/def.default
+
+[Profile0]
+Name=default-release
+Path=Profiles/abc.default-release"
+                    .to_string(),
+            )
+            .unwrap();
+
+        let mut install_locks = InstallLocks::new();
+        install_locks.add_from_ini_map(ini_map, "Install");
+
+        assert_eq!(install_locks.install_hashes.len(), 2);
+        assert!(install_locks.install_hashes.contains("6AFDA46A1A8AD48"));
+        assert_eq!(
+            install_locks
+                .locked_profile_path_and_hash
+                .get("Profiles/abc.default-release"),
+            Some(&"4F96D1932A9F858E".to_string())
+        );
+        assert_eq!(install_locks.locked_profile_path_and_hash.len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::Path;
+
+// Flatpak apps are launched through `flatpak run`, e.g desktop entry
+// "Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=/app/bin/chrome --file-forwarding com.google.Chrome @@u %U @@"
+// Only the arguments after the app id are passed to the app itself,
+// everything before it are options of `flatpak run`
+#[derive(Debug, PartialEq)]
+pub struct FlatpakRun {
+    // e.g "com.google.Chrome"
+    pub app_id: String,
+    // index of the app id in the command
+    pub app_id_index: usize,
+}
+
+pub fn parse_flatpak_run(command_parts: &[String]) -> Option<FlatpakRun> {
+    let flatpak_index = command_parts
+        .iter()
+        .position(|part| is_flatpak_binary(part))?;
+
+    let run_index = flatpak_index + 1;
+    if command_parts.get(run_index).map(|part| part.as_str()) != Some("run") {
+        return None;
+    }
+
+    let app_id_index = command_parts
+        .iter()
+        .enumerate()
+        .skip(run_index + 1)
+        .find(|(_, part)| !part.starts_with('-'))
+        .map(|(i, _)| i)?;
+
+    // app can also be given as a ref, e.g "org.mozilla.firefox//stable"
+    let app_ref = command_parts[app_id_index].as_str();
+    let app_id = app_ref.split('/').next().unwrap_or(app_ref);
+    if app_id.is_empty() {
+        return None;
+    }
+
+    return Some(FlatpakRun {
+        app_id: app_id.to_string(),
+        app_id_index: app_id_index,
+    });
+}
+
+fn is_flatpak_binary(command_part: &str) -> bool {
+    return Path::new(command_part)
+        .file_name()
+        .map(|file_name| file_name == "flatpak")
+        .unwrap_or(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::desktop_exec_parser;
+
+    fn to_command_parts(exec: &str) -> Vec<String> {
+        return desktop_exec_parser::parse_exec(exec).unwrap();
+    }
+
+    #[test]
+    fn test_parse_flatpak_run() {
+        let command_parts = to_command_parts(
+            "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=/app/bin/chrome --file-forwarding com.google.Chrome @@u %U @@",
+        );
+        assert_eq!(
+            parse_flatpak_run(&command_parts),
+            Some(FlatpakRun {
+                app_id: "com.google.Chrome".to_string(),
+                app_id_index: 6,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_flatpak_run_with_env_and_ref() {
+        let command_parts =
+            to_command_parts("env MOZ_ENABLE_WAYLAND=1 flatpak run org.mozilla.firefox//beta %u");
+        assert_eq!(
+            parse_flatpak_run(&command_parts),
+            Some(FlatpakRun {
+                app_id: "org.mozilla.firefox".to_string(),
+                app_id_index: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_flatpak_run_with_quoted_args() {
+        let command_parts = to_command_parts(
+            r#"/usr/bin/flatpak run "--command=/app/bin/my browser" "--env=GREETING=hello world" com.example.Browser --new-window %u"#,
+        );
+        assert_eq!(
+            command_parts[2..4],
+            [
+                "--command=/app/bin/my browser".to_string(),
+                "--env=GREETING=hello world".to_string()
+            ]
+        );
+        assert_eq!(
+            parse_flatpak_run(&command_parts),
+            Some(FlatpakRun {
+                app_id: "com.example.Browser".to_string(),
+                app_id_index: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_flatpak_run() {
+        assert_eq!(parse_flatpak_run(&to_command_parts("/usr/bin/firefox %u")), None);
+        assert_eq!(
+            parse_flatpak_run(&to_command_parts("/usr/bin/flatpak list")),
+            None
+        );
+        assert_eq!(
+            parse_flatpak_run(&to_command_parts("/usr/bin/flatpak run")),
+            None
+        );
+        assert_eq!(
+            parse_flatpak_run(&to_command_parts("/opt/flatpak-viewer/viewer %u")),
+            None
+        );
+    }
+}
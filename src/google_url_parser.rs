@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Google apps open links in the first signed-in Google account (`/u/0/`),
+// unless the account is given either as `authuser` query param, e.g
+// "https://docs.google.com/document/d/1abc/edit?authuser=madis@acme.com"
+// or as path segment, e.g
+// "https://mail.google.com/mail/u/madis@acme.com/#inbox"
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GoogleAuthUserConfig {
+    // add the signed-in account of the Chromium profile to Google links
+    // (a rule can always set the account explicitly)
+    pub use_profile_account: bool,
+    pub hosts: Vec<GoogleHost>,
+}
+
+impl Default for GoogleAuthUserConfig {
+    fn default() -> Self {
+        GoogleAuthUserConfig {
+            use_profile_account: false,
+            hosts: vec![
+                GoogleHost::new("docs.google.com", None),
+                GoogleHost::new("drive.google.com", Some("/drive")),
+                GoogleHost::new("calendar.google.com", Some("/calendar")),
+                GoogleHost::new("meet.google.com", None),
+                GoogleHost::new("mail.google.com", Some("/mail")),
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GoogleHost {
+    pub host: String,
+    // links under this path get the account as "/u/<account>/" segment after it,
+    // e.g "/mail" for "/mail/u/<account>/"; all other links get `authuser` query param
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_prefix: Option<String>,
+}
+
+impl GoogleHost {
+    fn new(host: &str, path_prefix: Option<&str>) -> Self {
+        Self {
+            host: host.to_string(),
+            path_prefix: path_prefix.map(|a| a.to_string()),
+        }
+    }
+}
+
+// Adds (or replaces) the account in a link of one of the configured Google hosts,
+// returns None for all other links
+pub fn add_authuser(config: &GoogleAuthUserConfig, url_str: &str, account: &str) -> Option<String> {
+    let mut url = Url::parse(url_str).ok()?;
+    let host = url.host_str()?.to_lowercase();
+
+    let google_host = config.hosts.iter().find(|h| h.host == host)?;
+
+    let path_prefix_maybe = google_host
+        .path_prefix
+        .as_ref()
+        .map(|prefix| prefix.trim_end_matches('/'))
+        .filter(|prefix| is_under_path_prefix(url.path(), prefix));
+
+    if let Some(path_prefix) = path_prefix_maybe {
+        let path = set_path_account(url.path(), path_prefix, account);
+        url.set_path(path.as_str());
+    } else {
+        let query_pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "authuser")
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(query_pairs)
+            .append_pair("authuser", account);
+    }
+
+    return Some(url.to_string());
+}
+
+fn is_under_path_prefix(path: &str, path_prefix: &str) -> bool {
+    return path == path_prefix || path.starts_with(format!("{}/", path_prefix).as_str());
+}
+
+// "/mail/u/0/" to "/mail/u/<account>/"
+// "/calendar/r/week" to "/calendar/u/<account>/r/week"
+fn set_path_account(path: &str, path_prefix: &str, account: &str) -> String {
+    let rest = path[path_prefix.len()..].trim_start_matches('/');
+    let mut segments: Vec<&str> = rest.split('/').collect();
+
+    if segments.len() >= 2 && segments[0] == "u" {
+        // replace the existing account, e.g "/u/0/"
+        segments.drain(0..2);
+    }
+
+    let rest_without_account = segments.join("/");
+    return format!("{}/u/{}/{}", path_prefix, account, rest_without_account);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(url_str: &str) -> Option<String> {
+        let config = GoogleAuthUserConfig::default();
+        return add_authuser(&config, url_str, "madis@acme.com");
+    }
+
+    #[test]
+    fn test_docs_link() {
+        assert_eq!(
+            add("https://docs.google.com/document/d/1abc/edit?usp=sharing"),
+            Some(
+                "https://docs.google.com/document/d/1abc/edit?usp=sharing&authuser=madis%40acme.com"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_docs_link_replaces_authuser() {
+        assert_eq!(
+            add("https://docs.google.com/spreadsheets/d/1abc/edit?authuser=0#gid=0"),
+            Some(
+                "https://docs.google.com/spreadsheets/d/1abc/edit?authuser=madis%40acme.com#gid=0"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_drive_links() {
+        assert_eq!(
+            add("https://drive.google.com/file/d/1abc/view"),
+            Some("https://drive.google.com/file/d/1abc/view?authuser=madis%40acme.com".to_string())
+        );
+        assert_eq!(
+            add("https://drive.google.com/drive/u/1/folders/1abc"),
+            Some("https://drive.google.com/drive/u/madis@acme.com/folders/1abc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_calendar_links() {
+        assert_eq!(
+            add("https://calendar.google.com/calendar/r/week"),
+            Some("https://calendar.google.com/calendar/u/madis@acme.com/r/week".to_string())
+        );
+        assert_eq!(
+            add("https://calendar.google.com/calendar/event?eid=abc"),
+            Some("https://calendar.google.com/calendar/u/madis@acme.com/event?eid=abc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_meet_link() {
+        assert_eq!(
+            add("https://meet.google.com/abc-defg-hij"),
+            Some("https://meet.google.com/abc-defg-hij?authuser=madis%40acme.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_gmail_links() {
+        assert_eq!(
+            add("https://mail.google.com/mail/u/0/#inbox/FMfcgz"),
+            Some("https://mail.google.com/mail/u/madis@acme.com/#inbox/FMfcgz".to_string())
+        );
+        assert_eq!(
+            add("https://mail.google.com/mail"),
+            Some("https://mail.google.com/mail/u/madis@acme.com/".to_string())
+        );
+    }
+
+    #[test]
+    fn test_other_links_are_not_changed() {
+        assert_eq!(add("https://www.google.com/search?q=rust"), None);
+        assert_eq!(add("https://example.com/mail/u/0/"), None);
+    }
+
+    #[test]
+    fn test_configured_hosts() {
+        let config = GoogleAuthUserConfig {
+            use_profile_account: false,
+            hosts: vec![GoogleHost::new("sites.google.com", None)],
+        };
+        assert_eq!(
+            add_authuser(&config, "https://sites.google.com/view/acme", "a@acme.com"),
+            Some("https://sites.google.com/view/acme?authuser=a%40acme.com".to_string())
+        );
+        assert_eq!(
+            add_authuser(&config, "https://docs.google.com/document/d/1abc", "a@acme.com"),
+            None
+        );
+    }
+}
@@ -22,6 +22,7 @@ use crate::gui::image_controller::UIImageController;
 use crate::gui::ui::{EXIT_APP, UIBrowser, UISettings, UIState};
 use crate::gui::ui_util::ellipsize;
 use crate::gui::{shared, ui_theme};
+use crate::utils::LaunchMode;
 
 pub const COPY_LINK_TO_CLIPBOARD: Selector<()> = Selector::new("browsers.copy_link");
 
@@ -35,9 +36,16 @@ pub const SHOW_SETTINGS_DIALOG: Selector<()> = Selector::new("browsers.show_sett
 pub const SET_BROWSERS_AS_DEFAULT_BROWSER: Selector<()> =
     Selector::new("browsers.set-browsers-as-default-browser");
 
+pub const RESTORE_PREVIOUS_DEFAULT_BROWSER: Selector<()> =
+    Selector::new("browsers.restore-previous-default-browser");
+
 // command to open a link in a selected web browser profile (browser profile index sent via command)
 pub const OPEN_LINK_IN_BROWSER: Selector<usize> = Selector::new("browsers.open_link");
 
+// command to open a link in a selected web browser profile in the given launch mode
+pub const OPEN_LINK_IN_BROWSER_WITH_LAUNCH_MODE: Selector<(usize, LaunchMode)> =
+    Selector::new("browsers.open_link_with_launch_mode");
+
 pub const HIDE_PROFILE: Selector<String> = Selector::new("browsers.hide_profile");
 
 pub const HIDE_ALL_PROFILES: Selector<String> = Selector::new("browsers.hide_all_profiles");
@@ -185,8 +193,27 @@ impl MainWindow {
         })
         .fix_width(OPTIONS_LABEL_SIZE);
 
+        // shown instead of the url, until clicked
+        let launch_error_label =
+            Label::dynamic(|data: &UIState, _| ellipsize(data.launch_error.as_str(), 28))
+                .with_text_size(12.0)
+                .with_text_color(MainWindowTheme::ENV_LAUNCH_ERROR_TEXT_COLOR)
+                .with_line_break_mode(LineBreaking::Clip)
+                .with_text_alignment(TextAlignment::Start)
+                .fix_height(BOTTOM_ROW_HEIGHT)
+                .fix_width(175.0)
+                .on_click(move |_ctx, data: &mut UIState, _env| {
+                    data.launch_error = "".to_string();
+                });
+
+        let url_or_launch_error_label = Either::new(
+            |data: &UIState, _env| data.launch_error.is_empty(),
+            url_label,
+            launch_error_label,
+        );
+
         let bottom_row = Flex::row()
-            .with_child(url_label)
+            .with_child(url_or_launch_error_label)
             .with_flex_spacer(1.0)
             .with_child(options_button);
 
@@ -374,7 +401,7 @@ fn create_browser(
         {
             let profile_label =
                 Label::dynamic(|(_, item): &((bool, UISettings), UIBrowser), _env: &_| {
-                    item.profile_name.clone()
+                    item.get_profile_subtitle()
                 })
                 .with_text_size(MainWindowTheme::ENV_PROFILE_LABEL_SIZE)
                 .with_line_break_mode(LineBreaking::Clip)
@@ -518,6 +545,21 @@ fn make_context_menu(browser: &UIBrowser) -> Menu<UIState> {
     let id = browser.unique_id.clone();
     let app_name = browser.browser_name.to_string();
 
+    for launch_mode in browser.launch_modes.iter() {
+        let launch_mode_arg = launch_mode.get_localization_arg();
+        let open_in_launch_mode_label = LocalizedString::new("open-in-launch-mode")
+            .with_arg("launch-mode", move |_, _| launch_mode_arg.into());
+
+        let open_in_launch_mode_command = OPEN_LINK_IN_BROWSER_WITH_LAUNCH_MODE
+            .with((browser.browser_profile_index, *launch_mode));
+
+        menu = menu
+            .entry(MenuItem::new(open_in_launch_mode_label).command(open_in_launch_mode_command));
+    }
+    if !browser.launch_modes.is_empty() {
+        menu = menu.separator();
+    }
+
     if !browser.has_priority_ordering() {
         let is_visible = !browser.is_first;
         let item_name = browser.get_full_name();
@@ -1,7 +1,9 @@
 use druid::widget::{Button, CrossAxisAlignment, Flex, Label};
 use druid::Widget;
 
-use crate::gui::main_window::{REFRESH, SET_BROWSERS_AS_DEFAULT_BROWSER};
+use crate::gui::main_window::{
+    REFRESH, RESTORE_PREVIOUS_DEFAULT_BROWSER, SET_BROWSERS_AS_DEFAULT_BROWSER,
+};
 use crate::gui::shared;
 use crate::gui::ui::UIState;
 
@@ -12,6 +14,31 @@ pub(crate) fn advanced_content() -> impl Widget<UIState> {
                 ctx.submit_command(SET_BROWSERS_AS_DEFAULT_BROWSER);
             });
 
+    let restore_default_button =
+        Button::from_label(Label::new("Restore Previous Default Browser").with_text_size(14.0))
+            .on_click(|ctx, _data, _env| {
+                ctx.submit_command(RESTORE_PREVIOUS_DEFAULT_BROWSER);
+            });
+
+    let restore_default_status_label = Label::dynamic(|data: &UIState, _| {
+        let status = match data.previous_default_browser_restored {
+            Some(true) => "Restored the previous default browser",
+            Some(false) => "Browsers is not the default browser, nothing to restore",
+            None => "",
+        };
+        return status.to_string();
+    })
+    .with_text_size(12.0);
+
+    let mut default_buttons = Flex::row().with_child(default_button);
+    // previous defaults are only kept in Linux, elsewhere it's changed in system settings
+    if cfg!(target_os = "linux") {
+        default_buttons.add_default_spacer();
+        default_buttons.add_child(restore_default_button);
+        default_buttons.add_default_spacer();
+        default_buttons.add_child(restore_default_status_label);
+    }
+
     let refresh_apps_button =
         Button::from_label(Label::new("Refresh Installed Applications").with_text_size(14.0))
             .on_click(|ctx, _data, _env| {
@@ -20,7 +47,7 @@ pub(crate) fn advanced_content() -> impl Widget<UIState> {
 
     return Flex::column()
         .cross_axis_alignment(CrossAxisAlignment::Start)
-        .with_child(default_button)
+        .with_child(default_buttons)
         .with_default_spacer()
         .with_child(refresh_apps_button)
         .with_default_spacer()
@@ -54,6 +54,23 @@ pub(crate) fn general_content() -> impl Widget<UIState> {
         .with_flex_spacer(1.0)
         .with_child(hotkeys_switch);
 
+    let sort_by_last_active_switch = ControllerHost::new(
+        Switch::new(),
+        rules_view::SubmitCommandOnDataChange {
+            command: save_command.clone(),
+        },
+    )
+    .lens(
+        UIState::ui_settings
+            .then(UISettings::visual_settings)
+            .then(UIVisualSettings::sort_by_last_active),
+    );
+
+    let sort_by_last_active_row = Flex::row()
+        .with_child(Label::new("Order profiles by last use").with_text_size(TEXT_SIZE))
+        .with_flex_spacer(1.0)
+        .with_child(sort_by_last_active_switch);
+
     let label = Label::new("Restore App...").with_text_size(TEXT_SIZE);
 
     let restore_app_button =
@@ -69,6 +86,8 @@ pub(crate) fn general_content() -> impl Widget<UIState> {
         .with_child(theme_radio_row)
         .with_default_spacer()
         .with_child(hotkeys_row)
+        .with_default_spacer()
+        .with_child(sort_by_last_active_row)
         .with_default_spacer();
 
     // Showing this option only for macOS right now,
@@ -114,6 +133,26 @@ pub(crate) fn general_content() -> impl Widget<UIState> {
         .with_child(unwrap_urls_switch);
     col = col.with_child(unwrap_urls_row).with_default_spacer();
 
+    let fallback_on_launch_failure_switch = ControllerHost::new(
+        Switch::new(),
+        rules_view::SubmitCommandOnDataChange {
+            command: SAVE_BEHAVIORAL_SETTINGS.with(()),
+        },
+    )
+    .lens(
+        UIState::ui_settings
+            .then(UISettings::behavioral_settings)
+            .then(UIBehavioralSettings::fallback_on_launch_failure),
+    );
+
+    let fallback_on_launch_failure_row = Flex::row()
+        .with_child(Label::new("Fall back if launching fails").with_text_size(TEXT_SIZE))
+        .with_flex_spacer(1.0)
+        .with_child(fallback_on_launch_failure_switch);
+    col = col
+        .with_child(fallback_on_launch_failure_row)
+        .with_default_spacer();
+
     let tooltip = Label::new(
         "To hide and move applications/profiles, close settings and just right-click on the application in the main dialog"
     )
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use druid::widget::{
+    Button, Container, Controller, ControllerHost, CrossAxisAlignment, Either, EnvScope, Flex,
+    Label, LineBreaking, List, TextBox,
+};
+use druid::{
+    Color, Env, EventCtx, Key, LensExt, LifeCycle, LifeCycleCtx, Menu, MenuItem, Point, Widget,
+    WidgetExt,
+};
+
+use crate::gui::settings_window::rules_view::{FONT, SubmitCommandOnDataChange};
+use crate::gui::ui::{SAVE_LAUNCH_OPTIONS, UIBrowser, UILaunchOptions, UISettings, UIState};
+use crate::gui::ui_theme::SettingsWindowTheme;
+
+const LAUNCH_OPTIONS_INDEX_KEY: Key<u64> = Key::new("LAUNCH_OPTIONS_INDEX");
+
+const CHOOSE_TARGET_LABEL: &str = "Choose App or Profile";
+
+pub(crate) fn launch_options_content(
+    browsers: Arc<Vec<UIBrowser>>,
+) -> impl Widget<UIState> + use<> {
+    let launch_options_list = List::new(move || create_launch_options(&browsers))
+        .lens(UISettings::launch_options)
+        .padding((0.0, 0.0, 15.0, 0.0));
+
+    let hint_str = r#"
+Options of an app apply to all its profiles, options of a profile are added to them.
+Arguments are given one per line, e.g --proxy-server=socks5://localhost:1080
+Environment variables are given as NAME=value per line, or -NAME to unset one.
+Wrappers (Linux only) run the app, one command per line, outermost first,
+e.g systemd-run --user --scope -p MemoryMax=4G; %c is replaced by the whole command,
+e.g distrobox enter work -- sh -c %c
+    "#;
+
+    let hint = Label::new(hint_str)
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_text_size(11.0)
+        .with_text_color(Color::from_hex_str("808080").unwrap())
+        .padding((0.0, 0.0, 15.0, 0.0));
+
+    let launch_options_list = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(launch_options_list)
+        .with_child(hint)
+        .scroll()
+        .vertical()
+        .content_must_fill(true);
+
+    // viewport size is fixed, while scrollable are is full size
+    let launch_options_list = Container::new(launch_options_list).expand_height();
+
+    let add_launch_options_button = Button::from_label(Label::new("Add Launch Options"))
+        .on_click(move |_ctx, data: &mut UISettings, _env| {
+            // same as adding a rule, see AddLaunchOptionsController
+            data.add_empty_launch_options();
+        })
+        .align_right()
+        .padding((10.0, 10.0, 20.0, 0.0));
+
+    let col = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_flex_child(launch_options_list, 1.0)
+        .with_child(add_launch_options_button)
+        .expand_height();
+
+    return col.lens(UIState::ui_settings);
+}
+
+// handles scrolling and saving when Add Launch Options is pressed
+struct AddLaunchOptionsController;
+
+impl<W: Widget<UILaunchOptions>> Controller<UILaunchOptions, W> for AddLaunchOptionsController {
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        launch_options: &UILaunchOptions,
+        env: &Env,
+    ) {
+        if let LifeCycle::ViewContextChanged(_) = event {
+            if !launch_options.deleted && !launch_options.saved {
+                ctx.scroll_to_view();
+                ctx.submit_command(SAVE_LAUNCH_OPTIONS.with(()));
+            }
+        }
+        child.lifecycle(ctx, event, launch_options, env)
+    }
+}
+
+fn create_launch_options(browsers: &Arc<Vec<UIBrowser>>) -> impl Widget<UILaunchOptions> + use<> {
+    let remove_button = Button::from_label(Label::new("➖").with_text_size(5.0))
+        .on_click(move |ctx, data: &mut UILaunchOptions, _env| {
+            data.deleted = true;
+            ctx.submit_command(SAVE_LAUNCH_OPTIONS.with(()));
+        })
+        .fix_size(30.0, 30.0);
+
+    let target_row = Flex::row()
+        .with_child(Label::new("For").with_font(FONT))
+        .with_child(EnvScope::new(
+            |env, launch_options: &UILaunchOptions| {
+                env.set(LAUNCH_OPTIONS_INDEX_KEY.clone(), launch_options.index as u64);
+            },
+            create_target_pop_up_button(browsers),
+        ));
+
+    let args_row = Flex::row()
+        .with_child(Label::new("Arguments").with_font(FONT).fix_width(120.0))
+        .with_child(create_text_box("--enable-features=Vulkan", true).lens(UILaunchOptions::args));
+
+    let env_row = Flex::row()
+        .with_child(Label::new("Environment").with_font(FONT).fix_width(120.0))
+        .with_child(create_text_box("MOZ_ENABLE_WAYLAND=1", true).lens(UILaunchOptions::env));
+
+    let working_dir_row = Flex::row()
+        .with_child(
+            Label::new("Working directory")
+                .with_font(FONT)
+                .fix_width(120.0),
+        )
+        .with_child(create_text_box("~/", false).lens(UILaunchOptions::working_dir));
+
+    let wrappers_row = Flex::row()
+        .with_child(Label::new("Wrappers").with_font(FONT).fix_width(120.0))
+        .with_child(create_text_box("firejail --private", true).lens(UILaunchOptions::wrappers));
+
+    return Either::new(|data: &UILaunchOptions, _env| data.deleted, Flex::column(), {
+        Container::new(
+            Flex::row()
+                .cross_axis_alignment(CrossAxisAlignment::End)
+                .with_child(
+                    Flex::column()
+                        .cross_axis_alignment(CrossAxisAlignment::Start)
+                        .with_child(target_row)
+                        .with_spacer(5.0)
+                        .with_child(args_row)
+                        .with_spacer(5.0)
+                        .with_child(env_row)
+                        .with_spacer(5.0)
+                        .with_child(working_dir_row)
+                        .with_spacer(5.0)
+                        .with_child(wrappers_row),
+                )
+                .with_spacer(10.0)
+                .with_child(remove_button),
+        )
+        .padding(10.0)
+        .background(SettingsWindowTheme::ENV_RULE_BACKGROUND_COLOR)
+        .rounded(10.0)
+        .border(SettingsWindowTheme::ENV_RULE_BORDER_COLOR, 0.5)
+        .padding((0.0, 5.0))
+    })
+    .controller(AddLaunchOptionsController);
+}
+
+fn create_text_box(placeholder: &str, multiline: bool) -> impl Widget<String> + use<> {
+    let text_box = if multiline {
+        TextBox::multiline()
+    } else {
+        TextBox::new()
+    };
+    let text_box = text_box.with_placeholder(placeholder).with_text_size(12.0);
+
+    return ControllerHost::new(
+        text_box,
+        SubmitCommandOnDataChange {
+            command: SAVE_LAUNCH_OPTIONS.with(()),
+        },
+    )
+    .fix_width(300.0);
+}
+
+fn create_target_pop_up_button(
+    browsers: &Arc<Vec<UIBrowser>>,
+) -> impl Widget<UILaunchOptions> + use<> {
+    let browsers_clone = browsers.clone();
+    let browsers_clone2 = browsers.clone();
+
+    return Label::dynamic(move |launch_options: &UILaunchOptions, _| {
+        let target_name = if launch_options.target_id.is_empty() {
+            CHOOSE_TARGET_LABEL.to_string()
+        } else {
+            find_target_name(&browsers_clone, launch_options.target_id.as_str())
+                .unwrap_or("Unknown".to_string())
+        };
+        format!("{target_name} ▼")
+    })
+    .with_font(FONT)
+    .border(Color::rgba(0.5, 0.5, 0.5, 0.9), 0.5)
+    .on_click(
+        move |ctx: &mut EventCtx, _launch_options: &mut UILaunchOptions, env: &Env| {
+            let index = env.get(LAUNCH_OPTIONS_INDEX_KEY.clone()) as usize;
+            let menu: Menu<UIState> = make_targets_menu(browsers_clone2.clone(), index);
+            ctx.show_context_menu(menu, Point::new(0.0, 0.0));
+        },
+    );
+}
+
+// apps with profiles can be chosen as a whole, or by profile
+fn targets(browsers: &Arc<Vec<UIBrowser>>) -> Vec<(String, String)> {
+    let mut targets: Vec<(String, String)> = Vec::new();
+    for b in browsers.iter() {
+        let app_seen = targets.iter().any(|(id, _)| id == &b.unique_app_id);
+        if b.supports_profiles && !app_seen {
+            let app_name = format!("{} (All Profiles)", b.browser_name);
+            targets.push((b.unique_app_id.clone(), app_name));
+        }
+        targets.push((b.unique_id.clone(), b.get_full_name()));
+    }
+    return targets;
+}
+
+fn find_target_name(browsers: &Arc<Vec<UIBrowser>>, target_id: &str) -> Option<String> {
+    return targets(browsers)
+        .into_iter()
+        .find(|(id, _)| id == target_id)
+        .map(|(_, name)| name);
+}
+
+// a target can have only one entry, as options are saved by target;
+// targets of the other entries can't be chosen
+fn item_target(
+    index: usize,
+    target_id: String,
+    target_name: String,
+) -> MenuItem<Arc<Vec<UILaunchOptions>>> {
+    let target_id_clone = target_id.clone();
+    let target_id_clone2 = target_id.clone();
+
+    MenuItem::new(target_name)
+        .selected_if(move |launch_options: &Arc<Vec<UILaunchOptions>>, _env| {
+            launch_options[index].target_id == target_id
+        })
+        .enabled_if(move |launch_options: &Arc<Vec<UILaunchOptions>>, _env| {
+            !is_target_of_other(launch_options, index, target_id_clone.as_str())
+        })
+        .on_activate(
+            move |ctx, launch_options: &mut Arc<Vec<UILaunchOptions>>, _env| {
+                if is_target_of_other(launch_options, index, target_id_clone2.as_str()) {
+                    return;
+                }
+                Arc::make_mut(launch_options)[index].target_id = target_id_clone2.clone();
+                ctx.submit_command(SAVE_LAUNCH_OPTIONS.with(()))
+            },
+        )
+}
+
+fn is_target_of_other(launch_options: &[UILaunchOptions], index: usize, target_id: &str) -> bool {
+    return launch_options.iter().any(|options| {
+        options.index != index && !options.deleted && options.target_id == target_id
+    });
+}
+
+fn make_targets_menu(browsers: Arc<Vec<UIBrowser>>, index: usize) -> Menu<UIState> {
+    let menu = targets(&browsers)
+        .into_iter()
+        .map(|(target_id, target_name)| {
+            let lens = UIState::ui_settings.then(UISettings::launch_options);
+            item_target(index, target_id, target_name).lens(lens)
+        })
+        .fold(Menu::empty(), |acc, e| acc.entry(e));
+
+    menu
+}
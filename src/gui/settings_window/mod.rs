@@ -13,6 +13,7 @@ use crate::gui::ui_theme::{GeneralTheme, SettingsWindowTheme};
 
 mod advanced_view;
 mod general_view;
+mod launch_options_view;
 mod rules_view;
 
 const SIDEBAR_ITEM_WIDTH: f64 = 190.0;
@@ -99,6 +100,10 @@ fn view_switcher(browsers_arc: Arc<Vec<UIBrowser>>) -> ViewSwitcher<UIState, Set
                 "settings-tab-rules",
                 rules_view::rules_content(browsers_arc.clone()),
             ),
+            SettingsTab::LAUNCH_OPTIONS => settings_view_container(
+                "settings-tab-launch-options",
+                launch_options_view::launch_options_content(browsers_arc.clone()),
+            ),
             SettingsTab::ADVANCED => {
                 settings_view_container("settings-tab-advanced", advanced_view::advanced_content())
             }
@@ -137,6 +142,10 @@ fn sidebar_items() -> impl Widget<UISettings> {
         .cross_axis_alignment(CrossAxisAlignment::Fill)
         .with_child(tab_button("settings-tab-general", SettingsTab::GENERAL))
         .with_child(tab_button("settings-tab-rules", SettingsTab::RULES))
+        .with_child(tab_button(
+            "settings-tab-launch-options",
+            SettingsTab::LAUNCH_OPTIONS,
+        ))
         .with_child(tab_button("settings-tab-advanced", SettingsTab::ADVANCED))
         .with_flex_spacer(1.0)
         .fix_width(SIDEBAR_ITEM_WIDTH)
@@ -4,12 +4,12 @@ use std::sync::Arc;
 use druid::lens::Identity;
 use druid::menu::MenuEventCtx;
 use druid::widget::{
-    Button, Checkbox, Container, Controller, ControllerHost, CrossAxisAlignment, Either, EnvScope,
-    Flex, Label, LineBreaking, List, Maybe, TextBox,
+    Button, Container, Controller, ControllerHost, CrossAxisAlignment, Either, EnvScope, Flex,
+    Label, LineBreaking, List, Maybe, TextBox,
 };
 use druid::{
     Color, Command, Data, Env, EventCtx, FontDescriptor, FontFamily, Key, LensExt, LifeCycle,
-    LifeCycleCtx, Menu, MenuItem, Point, UpdateCtx, Widget, WidgetExt,
+    LifeCycleCtx, LocalizedString, Menu, MenuItem, Point, UpdateCtx, Widget, WidgetExt,
 };
 
 use crate::gui::ui::{
@@ -17,6 +17,7 @@ use crate::gui::ui::{
     UISettingsRule, UIState,
 };
 use crate::gui::ui_theme::SettingsWindowTheme;
+use crate::utils::LaunchMode;
 
 pub(crate) const FONT: FontDescriptor = FontDescriptor::new(FontFamily::SYSTEM_UI).with_size(12.0);
 
@@ -95,9 +96,13 @@ fn create_profile_pop_up_button(
             format!("{profile_name} ▼")
         } else {
             let opener = opener.as_ref().unwrap();
-            let browser_maybe = find_browser(&browsers_clone, opener.profile.clone());
-            let profile_name_maybe = browser_maybe.map(|b| b.get_full_name());
-            let profile_name = profile_name_maybe.unwrap_or("Unknown".to_string());
+            let profile_name = if let Some(ref account) = opener.account {
+                format!("Profile signed in as {}", account)
+            } else {
+                let browser_maybe = find_browser(&browsers_clone, opener.profile.clone());
+                let profile_name_maybe = browser_maybe.map(|b| b.get_full_name());
+                profile_name_maybe.unwrap_or("Unknown".to_string())
+            };
 
             format!("{profile_name} ▼")
         }
@@ -126,7 +131,7 @@ fn create_profile_pop_up_button(
     );
 }
 
-fn create_incognito_checkbox(
+fn create_launch_mode_pop_up_button(
     browsers: &Arc<Vec<UIBrowser>>,
     command: Command,
 ) -> impl Widget<Option<UIProfileAndIncognito>> + use<> {
@@ -135,37 +140,66 @@ fn create_incognito_checkbox(
     return Maybe::new(
         move || {
             let browsers_clone4 = browsers_clone3.clone();
+            let browsers_clone5 = browsers_clone3.clone();
             let command1 = command.clone();
 
-            let incognito_either = Either::new(
+            let launch_mode_either = Either::new(
                 move |data: &UIProfileAndIncognito, _env| {
                     let browser_maybe = find_browser(&browsers_clone4, data.profile.clone());
-                    let browser_supports_incognito_maybe =
-                        browser_maybe.map(|p| p.supports_incognito);
-                    let profile_supports_incognito =
-                        browser_supports_incognito_maybe.unwrap_or(false);
-                    profile_supports_incognito
+                    let profile_launch_modes_maybe = browser_maybe.map(|p| &p.launch_modes);
+                    profile_launch_modes_maybe
+                        .map(|launch_modes| !launch_modes.is_empty())
+                        .unwrap_or(false)
                 },
                 {
-                    let incognito_checkbox = ControllerHost::new(
-                        Checkbox::from_label(Label::new("In Incognito").with_font(FONT)),
-                        SubmitCommandOnDataChange {
-                            command: command1.clone(),
+                    let launch_mode_label = Label::new(
+                        LocalizedString::new("rule-launch-mode-button").with_arg(
+                            "launch-mode",
+                            |opener: &UIProfileAndIncognito, _| {
+                                launch_mode_arg(opener.launch_mode).into()
+                            },
+                        ),
+                    )
+                    .with_font(FONT)
+                    .border(Color::rgba(0.5, 0.5, 0.5, 0.9), 0.5)
+                    .on_click(
+                        move |ctx: &mut EventCtx, opener: &mut UIProfileAndIncognito, env: &Env| {
+                            let rule_index_maybe: Option<usize> =
+                                env.try_get(RULE_INDEX_KEY.clone()).ok().map(|a| a as usize);
+
+                            let browser_maybe =
+                                find_browser(&browsers_clone5, opener.profile.clone());
+                            let launch_modes = browser_maybe
+                                .map(|b| b.launch_modes.as_ref().clone())
+                                .unwrap_or_default();
+
+                            let menu: Menu<UIState> = make_launch_modes_menu(
+                                launch_modes,
+                                command1.clone(),
+                                rule_index_maybe,
+                            );
+                            ctx.show_context_menu(menu, Point::new(0.0, 0.0));
                         },
                     )
-                    .lens(UIProfileAndIncognito::incognito)
                     .padding((10.0, 0.0, 0.0, 0.0));
-                    incognito_checkbox
+                    launch_mode_label
                 },
                 Flex::column(),
             );
 
-            incognito_either
+            launch_mode_either
         },
         || Flex::column(),
     );
 }
 
+// selects the label in the `rule-launch-mode` message of builtin.ftl
+fn launch_mode_arg(launch_mode_maybe: Option<LaunchMode>) -> &'static str {
+    return launch_mode_maybe
+        .map(|launch_mode| launch_mode.get_localization_arg())
+        .unwrap_or("normal");
+}
+
 fn create_profile_label() -> Label<Option<UIProfileAndIncognito>> {
     let profile_label = Label::dynamic(|opener, _env| match opener {
         None => "Show".to_string(),
@@ -182,13 +216,13 @@ fn default_app(browsers: &Arc<Vec<UIBrowser>>) -> impl Widget<UISettings> + use<
     let save_profile_command = SAVE_DEFAULT_RULE.with(());
     let selected_profile = create_profile_pop_up_button(browsers, save_profile_command);
 
-    let incognito_save_command = SAVE_DEFAULT_RULE.with(());
-    let incognito_maybe = create_incognito_checkbox(browsers, incognito_save_command);
+    let launch_mode_save_command = SAVE_DEFAULT_RULE.with(());
+    let launch_mode_maybe = create_launch_mode_pop_up_button(browsers, launch_mode_save_command);
 
     let profile_row = Flex::row()
         .with_child(profile_label)
         .with_child(selected_profile)
-        .with_child(incognito_maybe)
+        .with_child(launch_mode_maybe)
         .padding((0.0, 10.0, 0.0, 0.0));
 
     return Container::new(
@@ -271,18 +305,19 @@ fn create_rule(browsers: &Arc<Vec<UIBrowser>>) -> impl Widget<UISettingsRule> +
         create_profile_pop_up_button(browsers, save_profile_command).lens(UISettingsRule::opener),
     );
 
-    let save_incognito_command = SAVE_RULES.with(());
-    let incognito_maybe = EnvScope::new(
+    let save_launch_mode_command = SAVE_RULES.with(());
+    let launch_mode_maybe = EnvScope::new(
         |env, rule: &UISettingsRule| {
             env.set(RULE_INDEX_KEY.clone(), rule.index as u64);
         },
-        create_incognito_checkbox(browsers, save_incognito_command).lens(UISettingsRule::opener),
+        create_launch_mode_pop_up_button(browsers, save_launch_mode_command)
+            .lens(UISettingsRule::opener),
     );
 
     let profile_row = Flex::row()
         .with_child(profile_label)
         .with_child(selected_profile)
-        .with_child(incognito_maybe)
+        .with_child(launch_mode_maybe)
         .padding((0.0, 10.0, 0.0, 0.0));
 
     return Either::new(|data: &UISettingsRule, _env| data.deleted, Flex::column(), {
@@ -346,18 +381,23 @@ fn item_profile(b: &UIBrowser, save_command: Command) -> MenuItem<Option<UIProfi
 
     MenuItem::new(profile_full_name)
         .selected_if(move |opener: &Option<UIProfileAndIncognito>, _env| {
-            opener.is_some() && opener.as_ref().unwrap().profile == profile_id
+            opener.is_some()
+                && opener.as_ref().unwrap().account.is_none()
+                && opener.as_ref().unwrap().profile == profile_id
         })
         .on_activate(
             move |ctx: &mut MenuEventCtx, opener: &mut Option<UIProfileAndIncognito>, _env| {
                 // if it's already an app, then change only the profile
                 if opener.is_some() {
                     opener.as_mut().unwrap().profile = profile_id_clone.clone();
+                    opener.as_mut().unwrap().account = None;
                 } else {
-                    // if it was "<prompt>", then set profile and incognito
+                    // if it was "<prompt>", then set profile and launch mode
                     let option: Option<UIProfileAndIncognito> = Some(UIProfileAndIncognito {
                         profile: profile_id_clone.clone(),
-                        incognito: false,
+                        account: None,
+                        authuser: None,
+                        launch_mode: None,
                     });
                     *opener = option;
                 }
@@ -366,6 +406,59 @@ fn item_profile(b: &UIBrowser, save_command: Command) -> MenuItem<Option<UIProfi
         )
 }
 
+// Accounts which profiles are signed in to, e.g "madis@acme.com", or "@acme.com" for any account
+// of a Google Workspace domain, with the first profile signed in to it (for its launch modes)
+fn find_accounts(browsers: &Arc<Vec<UIBrowser>>) -> Vec<(String, String)> {
+    let mut accounts: Vec<(String, String)> = Vec::new();
+    for b in browsers.iter() {
+        let hosted_domain_account_maybe = b
+            .profile_hosted_domain_maybe
+            .as_ref()
+            .map(|hosted_domain| format!("@{}", hosted_domain));
+        let accounts_of_profile = b
+            .profile_email_maybe
+            .iter()
+            .cloned()
+            .chain(hosted_domain_account_maybe);
+        for account in accounts_of_profile {
+            if !accounts.iter().any(|(existing, _)| existing == &account) {
+                accounts.push((account, b.unique_id.clone()));
+            }
+        }
+    }
+    return accounts;
+}
+
+fn item_account(
+    account: String,
+    profile_id: String,
+    save_command: Command,
+) -> MenuItem<Option<UIProfileAndIncognito>> {
+    let account_clone = account.clone();
+
+    MenuItem::new(format!("Profile signed in as {}", account))
+        .selected_if(move |opener: &Option<UIProfileAndIncognito>, _env| {
+            opener.is_some() && opener.as_ref().unwrap().account.as_ref() == Some(&account)
+        })
+        .on_activate(
+            move |ctx: &mut MenuEventCtx, opener: &mut Option<UIProfileAndIncognito>, _env| {
+                if opener.is_some() {
+                    opener.as_mut().unwrap().profile = profile_id.clone();
+                    opener.as_mut().unwrap().account = Some(account_clone.clone());
+                } else {
+                    let option: Option<UIProfileAndIncognito> = Some(UIProfileAndIncognito {
+                        profile: profile_id.clone(),
+                        account: Some(account_clone.clone()),
+                        authuser: None,
+                        launch_mode: None,
+                    });
+                    *opener = option;
+                }
+                ctx.submit_command(save_command.clone())
+            },
+        )
+}
+
 fn make_profiles_menu(
     browsers: Arc<Vec<UIBrowser>>,
     save_command: Command,
@@ -414,5 +507,84 @@ fn make_profiles_menu(
         })
         .fold(menu_initial, |acc, e| acc.entry(e));
 
+    let accounts = find_accounts(&browsers);
+    if accounts.is_empty() {
+        return menu;
+    }
+
+    // resolved to a profile signed in to the account when the link is opened
+    let menu = accounts
+        .into_iter()
+        .map(|(account, profile_id)| {
+            let item = item_account(account, profile_id, save_command.clone());
+            match rule_index_maybe.is_some() {
+                true => {
+                    let rule_index = rule_index_maybe.unwrap();
+                    let ok = UIState::ui_settings
+                        .then(UISettings::rules)
+                        .then(Identity.index(rule_index).in_arc())
+                        .then(UISettingsRule::opener);
+                    item.lens(ok)
+                }
+                false => {
+                    let ok = UIState::ui_settings.then(UISettings::default_opener);
+                    item.lens(ok)
+                }
+            }
+        })
+        .fold(menu.separator(), |acc, e| acc.entry(e));
+
+    menu
+}
+
+fn item_launch_mode(
+    launch_mode_maybe: Option<LaunchMode>,
+    save_command: Command,
+) -> MenuItem<Option<UIProfileAndIncognito>> {
+    let arg = launch_mode_arg(launch_mode_maybe);
+    let launch_mode_label =
+        LocalizedString::new("rule-launch-mode").with_arg("launch-mode", move |_, _| arg.into());
+    MenuItem::new(launch_mode_label)
+        .selected_if(move |opener: &Option<UIProfileAndIncognito>, _env| {
+            opener.is_some() && opener.as_ref().unwrap().launch_mode == launch_mode_maybe
+        })
+        .on_activate(
+            move |ctx: &mut MenuEventCtx, opener: &mut Option<UIProfileAndIncognito>, _env| {
+                if opener.is_some() {
+                    opener.as_mut().unwrap().launch_mode = launch_mode_maybe;
+                    ctx.submit_command(save_command.clone())
+                }
+            },
+        )
+}
+
+fn make_launch_modes_menu(
+    launch_modes: Vec<LaunchMode>,
+    save_command: Command,
+    rule_index_maybe: Option<usize>,
+) -> Menu<UIState> {
+    // opening the link the default way comes first
+    let launch_modes_maybe = std::iter::once(None).chain(launch_modes.into_iter().map(Some));
+
+    let menu = launch_modes_maybe
+        .map(|launch_mode_maybe| {
+            let item = item_launch_mode(launch_mode_maybe, save_command.clone());
+            match rule_index_maybe.is_some() {
+                true => {
+                    let rule_index = rule_index_maybe.unwrap();
+                    let ok = UIState::ui_settings
+                        .then(UISettings::rules)
+                        .then(Identity.index(rule_index).in_arc())
+                        .then(UISettingsRule::opener);
+                    item.lens(ok)
+                }
+                false => {
+                    let ok = UIState::ui_settings.then(UISettings::default_opener);
+                    item.lens(ok)
+                }
+            }
+        })
+        .fold(Menu::empty(), |acc, e| acc.entry(e));
+
     menu
 }
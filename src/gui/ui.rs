@@ -15,13 +15,16 @@ use url::Url;
 
 use crate::gui::main_window::{
     calculate_window_position, recalculate_window_size, COPY_LINK_TO_CLIPBOARD, HIDE_ALL_PROFILES,
-    HIDE_PROFILE, MOVE_PROFILE, OPEN_LINK_IN_BROWSER, REFRESH, RESTORE_HIDDEN_PROFILE,
+    HIDE_PROFILE, MOVE_PROFILE, OPEN_LINK_IN_BROWSER, OPEN_LINK_IN_BROWSER_WITH_LAUNCH_MODE,
+    REFRESH, RESTORE_HIDDEN_PROFILE, RESTORE_PREVIOUS_DEFAULT_BROWSER,
     SET_BROWSERS_AS_DEFAULT_BROWSER, SET_FOCUSED_INDEX, SHOW_ABOUT_DIALOG, SHOW_SETTINGS_DIALOG,
 };
 use crate::gui::ui::SettingsTab::GENERAL;
 use crate::gui::{about_dialog, main_window, settings_window, ui_theme};
 use crate::url_rule::UrlGlobMatcher;
-use crate::utils::{BehavioralConfig, Config, ConfiguredTheme, ProfileAndOptions, UIConfig};
+use crate::utils::{
+    BehavioralConfig, Config, ConfiguredTheme, LaunchMode, ProfileAndOptions, UIConfig,
+};
 use crate::{CommonBrowserProfile, MessageToMain};
 
 pub struct UI {
@@ -59,12 +62,29 @@ impl UI {
 
         let default_opener = Self::map_as_ui_profile(config.get_default_profile());
 
+        let ui_launch_options = config
+            .get_launch_options()
+            .iter()
+            .enumerate()
+            .map(|(i, (target_id, options))| UILaunchOptions {
+                index: i,
+                saved: true,
+                deleted: false,
+                target_id: target_id.to_string(),
+                args: options.get_args_lines(),
+                env: options.get_env_lines(),
+                working_dir: options.working_dir.clone().unwrap_or_default(),
+                wrappers: options.get_wrappers_lines(),
+            })
+            .collect();
+
         return UISettings {
             tab: GENERAL,
             default_opener: default_opener,
             rules: Arc::new(ui_settings_rules),
             visual_settings: Self::map_as_visual_settings(config.get_ui_config()),
             behavioral_settings: Self::map_as_ui_behavioural_settings(config.get_behavior()),
+            launch_options: Arc::new(ui_launch_options),
         };
     }
     fn map_as_visual_settings(ui_config: &UIConfig) -> UIVisualSettings {
@@ -72,12 +92,15 @@ impl UI {
             show_hotkeys: ui_config.show_hotkeys,
             quit_on_lost_focus: ui_config.quit_on_lost_focus,
             theme: ui_config.theme,
+            sort_by_last_active: ui_config.sort_by_last_active,
         }
     }
 
     fn map_as_ui_behavioural_settings(behavior: &BehavioralConfig) -> UIBehavioralSettings {
         UIBehavioralSettings {
             unwrap_urls: behavior.unwrap_urls,
+            fallback_on_launch_failure: behavior.fallback_on_launch_failure,
+            launch_failure_grace_period_ms: behavior.launch_failure_grace_period_ms,
         }
     }
 
@@ -86,7 +109,9 @@ impl UI {
     ) -> Option<UIProfileAndIncognito> {
         return profile_and_options.as_ref().map(|p| UIProfileAndIncognito {
             profile: p.profile.clone(),
-            incognito: p.incognito,
+            account: p.account.clone(),
+            authuser: p.authuser.clone(),
+            launch_mode: p.get_launch_mode(),
         });
     }
 
@@ -121,10 +146,13 @@ impl UI {
                     .has_real_profiles()
                     .then(|| p.get_profile_name().to_string()),
                 supports_incognito: p.get_browser_common().supports_incognito(),
+                launch_modes: Arc::new(p.get_browser_common().get_launch_modes()),
                 icon_path: p.get_browser_icon_path().to_string(),
                 profile_icon_path: p
                     .get_profile_icon_path()
                     .map_or("".to_string(), |a| a.to_string()),
+                profile_email_maybe: p.get_profile_email().cloned(),
+                profile_hosted_domain_maybe: p.get_profile_hosted_domain().cloned(),
                 unique_id: p.get_unique_id(),
                 unique_app_id: p.get_unique_app_id(),
                 filtered_index: i, // TODO: filter against current url
@@ -191,6 +219,8 @@ impl UI {
             show_set_as_default: self.show_set_as_default,
             ui_settings: self.ui_settings.clone(),
             has_non_main_window_open: false,
+            launch_error: "".to_string(),
+            previous_default_browser_restored: None,
         };
     }
 
@@ -223,6 +253,12 @@ pub struct UIState {
 
     // Has About or Settings dialog or a context menu open (e.g right click or 3-dot menu)
     pub has_non_main_window_open: bool,
+
+    // why the link couldn't be opened, shown instead of the url; empty if there is no error
+    pub(crate) launch_error: String,
+
+    // result of "Restore Previous Default Browser", None until it's clicked
+    pub(crate) previous_default_browser_restored: Option<bool>,
 }
 
 #[derive(Clone, Data, Lens)]
@@ -232,6 +268,7 @@ pub struct UISettings {
     pub rules: Arc<Vec<UISettingsRule>>,
     pub visual_settings: UIVisualSettings,
     pub behavioral_settings: UIBehavioralSettings,
+    pub launch_options: Arc<Vec<UILaunchOptions>>,
 }
 
 #[derive(Clone, Debug, Data, Lens)]
@@ -239,23 +276,40 @@ pub struct UIVisualSettings {
     pub show_hotkeys: bool,
     pub quit_on_lost_focus: bool,
     pub theme: ConfiguredTheme,
+    pub sort_by_last_active: bool,
 }
 
 #[derive(Clone, Debug, Data, Lens)]
 pub struct UIBehavioralSettings {
     pub unwrap_urls: bool,
+    pub fallback_on_launch_failure: bool,
+    // not editable in Settings, but kept when saving
+    pub launch_failure_grace_period_ms: u64,
+}
+
+impl UIBehavioralSettings {
+    pub fn to_behavioral_config(&self) -> BehavioralConfig {
+        return BehavioralConfig {
+            unwrap_urls: self.unwrap_urls,
+            fallback_on_launch_failure: self.fallback_on_launch_failure,
+            launch_failure_grace_period_ms: self.launch_failure_grace_period_ms,
+        };
+    }
 }
 
 #[derive(Clone, Debug, Data, Lens)]
 pub struct UIProfileAndIncognito {
     pub profile: String,
-    pub incognito: bool,
+    pub account: Option<String>,
+    pub authuser: Option<String>,
+    pub launch_mode: Option<LaunchMode>,
 }
 
 #[derive(Clone, PartialEq, Data, Copy)]
 pub enum SettingsTab {
     GENERAL,
     RULES,
+    LAUNCH_OPTIONS,
     ADVANCED,
 }
 
@@ -288,6 +342,36 @@ impl UISettings {
         }
     }
 
+    pub fn add_empty_launch_options(&mut self) -> &UILaunchOptions {
+        info!("add_empty_launch_options called");
+
+        let next_index = self.launch_options.len();
+
+        let launch_options = UILaunchOptions {
+            index: next_index,
+            saved: false,
+            deleted: false,
+            target_id: "".to_string(),
+            args: "".to_string(),
+            env: "".to_string(),
+            working_dir: "".to_string(),
+            wrappers: "".to_string(),
+        };
+
+        let launch_options_mut = Arc::make_mut(&mut self.launch_options);
+        launch_options_mut.push(launch_options);
+        return launch_options_mut.last().unwrap();
+    }
+
+    pub fn mark_launch_options_as_saved(&mut self) {
+        let launch_options_mut = Arc::make_mut(&mut self.launch_options);
+        for launch_options in launch_options_mut.iter_mut() {
+            if !launch_options.deleted {
+                launch_options.saved = true
+            }
+        }
+    }
+
     /*
     pub fn get_rule_by_index(&self, index: usize) -> Option<&UISettingsRule> {
         return self.rules.get(index);
@@ -342,6 +426,25 @@ impl UISettingsRule {
     }
 }
 
+// Launch options of an app or a profile, as edited in Settings (see `LaunchOptionsConfig`)
+#[derive(Clone, Debug, Data, Lens)]
+pub struct UILaunchOptions {
+    pub index: usize,
+    // same as in UISettingsRule
+    pub saved: bool,
+    pub deleted: bool,
+
+    // unique id of the app or profile, empty until chosen
+    pub target_id: String,
+    // one argument per line
+    pub args: String,
+    // "NAME=value" per line, "-NAME" to unset
+    pub env: String,
+    pub working_dir: String,
+    // a shell-quoted wrapper command per line, outermost first
+    pub wrappers: String,
+}
+
 #[derive(Clone, Data, Lens)]
 pub struct UIBrowser {
     // index in not-explicitly-hidden browsers list, used to send message to main event cycle
@@ -355,9 +458,15 @@ pub struct UIBrowser {
     profile_name_maybe: Option<String>,
     pub(crate) supports_profiles: bool,
     pub(crate) supports_incognito: bool,
+    // launch modes the app supports besides opening the link the default way
+    pub(crate) launch_modes: Arc<Vec<LaunchMode>>,
 
     icon_path: String,
     profile_icon_path: String,
+    // email of the signed-in account, shown below the profile name
+    pub(crate) profile_email_maybe: Option<String>,
+    // e.g company domain of a Google Workspace account, used when email is not known
+    pub(crate) profile_hosted_domain_maybe: Option<String>,
     pub unique_id: String,
     pub(crate) unique_app_id: String,
 
@@ -383,6 +492,21 @@ impl UIBrowser {
             self.browser_name.to_string()
         };
     }
+
+    /// Returns profile name + signed-in account, e.g "Work · madis@qminderapp.com"
+    pub fn get_profile_subtitle(&self) -> String {
+        let account_maybe = self
+            .profile_email_maybe
+            .as_ref()
+            .or(self.profile_hosted_domain_maybe.as_ref());
+
+        return match account_maybe {
+            Some(account) if account != &self.profile_name => {
+                format!("{} · {}", self.profile_name, account)
+            }
+            _ => self.profile_name.to_string(),
+        };
+    }
 }
 
 impl UIState {}
@@ -399,12 +523,20 @@ pub const EXIT_APP: Selector<String> = Selector::new("browsers.exit_app");
 pub const OPEN_LINK_IN_BROWSER_COMPLETED: Selector<String> =
     Selector::new("browsers.open_link_completed");
 
+// app failed to launch (and fallback failed too, if enabled), sends the error to show
+pub const OPEN_LINK_IN_BROWSER_FAILED: Selector<String> =
+    Selector::new("browsers.open_link_failed");
+
 pub const NEW_BROWSERS_RECEIVED: Selector<Vec<UIBrowser>> =
     Selector::new("browsers.new_browsers_received");
 
 pub const NEW_HIDDEN_BROWSERS_RECEIVED: Selector<Vec<UIBrowser>> =
     Selector::new("browsers.new_hidden_browsers_received");
 
+// false if Browsers was not the default browser, so there was nothing to restore
+pub const PREVIOUS_DEFAULT_BROWSER_RESTORED: Selector<bool> =
+    Selector::new("browsers.previous_default_browser_restored");
+
 // or save draft?
 // or save rules, but allow "invalid" rules to be saved and handle them?
 pub const SAVE_RULES: Selector<()> = Selector::new("browsers.save_rules");
@@ -413,6 +545,7 @@ pub const SAVE_DEFAULT_RULE: Selector<()> = Selector::new("browsers.save_default
 pub const SAVE_UI_SETTINGS: Selector<()> = Selector::new("browsers.save_ui_settings");
 pub const SAVE_BEHAVIORAL_SETTINGS: Selector<()> =
     Selector::new("browsers.save_behavioral_settings");
+pub const SAVE_LAUNCH_OPTIONS: Selector<()> = Selector::new("browsers.save_launch_options");
 
 pub struct UIDelegate {
     main_sender: Sender<MessageToMain>,
@@ -456,6 +589,18 @@ impl UIDelegate {
             .ok();
     }
 
+    fn save_launch_options(&self, launch_options_arc: &Arc<Vec<UILaunchOptions>>) {
+        let launch_options_vec: Vec<UILaunchOptions> = launch_options_arc
+            .iter()
+            .filter(|o| !o.deleted)
+            .map(|a| a.clone())
+            .collect();
+
+        self.main_sender
+            .send(MessageToMain::SaveConfigLaunchOptions(launch_options_vec))
+            .ok();
+    }
+
     fn open_link_in_filtered_browser(
         &self,
         ctx: &mut DelegateCtx,
@@ -627,10 +772,7 @@ impl AppDelegate<UIState> for UIDelegate {
         } else if cmd.is(OS_URL_OPENED) {
             let url_open_info = cmd.get_unchecked(OS_URL_OPENED);
 
-            let settings = &data.ui_settings.behavioral_settings;
-            let behavioral_config = BehavioralConfig {
-                unwrap_urls: settings.unwrap_urls,
-            };
+            let behavioral_config = data.ui_settings.behavioral_settings.to_behavioral_config();
 
             self.main_sender
                 .send(MessageToMain::UrlPassedToMain(
@@ -643,6 +785,7 @@ impl AppDelegate<UIState> for UIDelegate {
         } else if cmd.is(CLEANED_URL_OPENED) {
             let url_open_info = cmd.get_unchecked(CLEANED_URL_OPENED);
             data.url = url_open_info.url.clone();
+            data.launch_error = "".to_string();
 
             let filtered_browsers = get_filtered_browsers(&data.url, &data.browsers);
             data.filtered_browsers = Arc::new(filtered_browsers);
@@ -695,7 +838,18 @@ impl AppDelegate<UIState> for UIDelegate {
             self.main_sender
                 .send(MessageToMain::OpenLink(
                     *profile_index,
-                    data.incognito_mode,
+                    data.incognito_mode.then(|| LaunchMode::Incognito),
+                    data.url.to_string(),
+                ))
+                .ok();
+            Handled::Yes
+        } else if cmd.is(OPEN_LINK_IN_BROWSER_WITH_LAUNCH_MODE) {
+            let (profile_index, launch_mode) =
+                cmd.get_unchecked(OPEN_LINK_IN_BROWSER_WITH_LAUNCH_MODE);
+            self.main_sender
+                .send(MessageToMain::OpenLink(
+                    *profile_index,
+                    Some(*launch_mode),
                     data.url.to_string(),
                 ))
                 .ok();
@@ -705,6 +859,10 @@ impl AppDelegate<UIState> for UIDelegate {
             sink.submit_command(EXIT_APP, "".to_string(), Target::Global)
                 .unwrap();
             Handled::Yes
+        } else if cmd.is(OPEN_LINK_IN_BROWSER_FAILED) {
+            // stay open, so that another browser can be chosen
+            data.launch_error = cmd.get_unchecked(OPEN_LINK_IN_BROWSER_FAILED).clone();
+            Handled::Yes
         } else if cmd.is(REFRESH) {
             self.main_sender.send(MessageToMain::Refresh).ok();
             Handled::Yes
@@ -749,6 +907,16 @@ impl AppDelegate<UIState> for UIDelegate {
                 .send(MessageToMain::SetBrowsersAsDefaultBrowser)
                 .ok();
             Handled::Yes
+        } else if cmd.is(RESTORE_PREVIOUS_DEFAULT_BROWSER) {
+            data.previous_default_browser_restored = None;
+            self.main_sender
+                .send(MessageToMain::RestorePreviousDefaultBrowser)
+                .ok();
+            Handled::Yes
+        } else if cmd.is(PREVIOUS_DEFAULT_BROWSER_RESTORED) {
+            let restored = cmd.get_unchecked(PREVIOUS_DEFAULT_BROWSER_RESTORED);
+            data.previous_default_browser_restored = Some(*restored);
+            Handled::Yes
         } else if cmd.is(HIDE_ALL_PROFILES) {
             let hideable_app_id = cmd.get_unchecked(HIDE_ALL_PROFILES);
             let app_id = hideable_app_id.clone();
@@ -800,6 +968,10 @@ impl AppDelegate<UIState> for UIDelegate {
         } else if cmd.is(SAVE_BEHAVIORAL_SETTINGS) {
             self.save_behavioral_settings(&data.ui_settings.behavioral_settings);
             Handled::Yes
+        } else if cmd.is(SAVE_LAUNCH_OPTIONS) {
+            self.save_launch_options(&data.ui_settings.launch_options);
+            data.ui_settings.mark_launch_options_as_saved();
+            Handled::Yes
         } else {
             //println!("cmd forwarded: {:?}", cmd);
             Handled::No
@@ -851,7 +1023,8 @@ pub(crate) fn get_filtered_browsers(
         .cloned()
         .filter(|b| {
             return if b.restricted_url_matchers.is_empty() {
-                true
+                // unrestricted apps are web browsers, which only make sense for web links
+                url_maybe.as_ref().map(is_web_url).unwrap_or(true)
             } else {
                 url_maybe
                     .as_ref()
@@ -877,6 +1050,10 @@ pub(crate) fn get_filtered_browsers(
     return filtered;
 }
 
+fn is_web_url(url: &Url) -> bool {
+    return url.scheme() == "http" || url.scheme() == "https";
+}
+
 fn copy_to_clipboard(url: &str) {
     let mut clipboard = Application::global().clipboard();
     clipboard.put_string(url);
@@ -84,6 +84,7 @@ fn get_theme(ui_theme: UITheme) -> Theme {
             hotkey_border_color: Color::rgba(0.4, 0.4, 0.4, 0.9),
             hotkey_text_color: Color::rgb8(128, 128, 128),
             options_button_text_color: Color::rgb8(128, 128, 128),
+            launch_error_text_color: Color::rgb8(255, 110, 100),
         },
         settings: SettingsWindowTheme {
             active_tab_background_color: Color::rgb8(25, 90, 194),
@@ -139,6 +140,7 @@ fn get_theme(ui_theme: UITheme) -> Theme {
             hotkey_border_color: Color::rgba(0.4, 0.4, 0.4, 0.9),
             hotkey_text_color: Color::rgb8(128, 128, 128),
             options_button_text_color: Color::rgb8(128, 128, 128),
+            launch_error_text_color: Color::rgb8(190, 30, 20),
         },
         settings: SettingsWindowTheme {
             active_tab_background_color: Color::rgb8(25, 90, 194),
@@ -206,6 +208,7 @@ pub(crate) struct MainWindowTheme {
     hotkey_border_color: Color,
     hotkey_text_color: Color,
     options_button_text_color: Color,
+    launch_error_text_color: Color,
 }
 
 impl MainWindowTheme {
@@ -239,6 +242,9 @@ impl MainWindowTheme {
     pub const ENV_OPTIONS_BUTTON_TEXT_COLOR: Key<Color> =
         Key::new("software.browsers.theme.main.options_button_text_color");
 
+    pub const ENV_LAUNCH_ERROR_TEXT_COLOR: Key<Color> =
+        Key::new("software.browsers.theme.main.launch_error_text_color");
+
     fn set_env_to_theme(&self, env: &mut Env) {
         env.set(Self::ENV_WINDOW_BACKGROUND_COLOR, self.window_background_color);
         env.set(Self::ENV_WINDOW_BORDER_COLOR, self.window_border_color);
@@ -253,6 +259,7 @@ impl MainWindowTheme {
             Self::ENV_OPTIONS_BUTTON_TEXT_COLOR,
             self.options_button_text_color,
         );
+        env.set(Self::ENV_LAUNCH_ERROR_TEXT_COLOR, self.launch_error_text_color);
     }
 }
 
@@ -0,0 +1,517 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::desktop_exec_parser;
+use crate::utils::expand_home_dir;
+
+// Extra arguments, environment variables and working dir to launch an app or a profile with;
+// configured by app id (as in `hidden_apps`) or profile id (as in `hidden_profiles`), e.g
+// "launch_options": {
+//   "/usr/bin/firefox": {"env": {"MOZ_ENABLE_WAYLAND": "1"}},
+//   "/usr/bin/google-chrome#Profile 3": {"args": ["--proxy-server=socks5://localhost:1080"]}
+// }
+// In Linux, the command can also be run by a chain of wrappers, e.g
+// "wrappers": [
+//   ["systemd-run", "--user", "--scope", "-p", "MemoryMax=4G"],
+//   ["firejail", "--private"]
+// ]
+// runs `systemd-run --user --scope -p MemoryMax=4G firejail --private google-chrome ..`
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct LaunchOptionsConfig {
+    // added after the profile arguments
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // environment variables to set
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // environment variables to unset
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub env_remove: Vec<String>,
+    // "~/" is expanded to the home dir
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    // outermost first, the command is added after the arguments of the last wrapper;
+    // an argument "%c" is replaced by the whole (shell-quoted) command instead,
+    // e.g ["distrobox", "enter", "work", "--", "sh", "-c", "%c"], which is run with `env`
+    // for the environment variables, as a container might not pass them on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wrappers: Vec<Vec<String>>,
+}
+
+const COMMAND_PLACEHOLDER: &str = "%c";
+
+impl LaunchOptionsConfig {
+    // options of the app, extended (and overridden) by the options of the profile;
+    // `inherited_env_remove` are variables of Browsers' own environment not to pass on,
+    // unless the app or profile sets them
+    pub fn resolve(
+        launch_options: &BTreeMap<String, LaunchOptionsConfig>,
+        inherited_env_remove: &[String],
+        app_id: &str,
+        profile_id: &str,
+    ) -> LaunchOptionsConfig {
+        let inherited_options = LaunchOptionsConfig {
+            env_remove: inherited_env_remove.to_vec(),
+            ..LaunchOptionsConfig::default()
+        };
+        let app_options =
+            inherited_options.merged_with(&launch_options.get(app_id).cloned().unwrap_or_default());
+        return if let Some(profile_options) = launch_options.get(profile_id) {
+            app_options.merged_with(profile_options)
+        } else {
+            app_options
+        };
+    }
+
+    fn merged_with(&self, other: &LaunchOptionsConfig) -> LaunchOptionsConfig {
+        let mut args = self.args.clone();
+        args.extend(other.args.iter().cloned());
+
+        let mut env = self.env.clone();
+        for name in &other.env_remove {
+            env.remove(name);
+        }
+        env.extend(other.env.clone());
+
+        // wrappers of the profile run inside the wrappers of the app
+        let mut wrappers = self.wrappers.clone();
+        wrappers.extend(other.wrappers.iter().cloned());
+
+        let mut env_remove: Vec<String> = self
+            .env_remove
+            .iter()
+            .filter(|name| !other.env.contains_key(*name))
+            .cloned()
+            .collect();
+        env_remove.extend(other.env_remove.iter().cloned());
+
+        return LaunchOptionsConfig {
+            args: args,
+            env: env,
+            env_remove: env_remove,
+            working_dir: other.working_dir.clone().or(self.working_dir.clone()),
+            wrappers: wrappers,
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self == &LaunchOptionsConfig::default();
+    }
+
+    pub fn get_working_dir(&self) -> Option<PathBuf> {
+        return self
+            .working_dir
+            .as_ref()
+            .filter(|working_dir| !working_dir.trim().is_empty())
+            .map(|working_dir| expand_home_dir(working_dir));
+    }
+
+    // arguments are added by the app, as their place depends on the app command
+    pub fn apply_env_and_working_dir(&self, cmd: &mut Command) {
+        for name in &self.env_remove {
+            cmd.env_remove(name);
+        }
+        cmd.envs(&self.env);
+        if let Some(working_dir) = self.get_working_dir() {
+            cmd.current_dir(working_dir);
+        }
+    }
+
+    // runs the command with the wrappers, keeping its environment and working dir
+    pub fn wrap_command(&self, cmd: Command) -> Command {
+        let wrappers: Vec<&Vec<String>> = self
+            .wrappers
+            .iter()
+            .filter(|wrapper| !wrapper.is_empty())
+            .collect();
+        if wrappers.is_empty() {
+            return cmd;
+        }
+
+        let mut argv = get_argv(&cmd);
+        let mut is_env_in_command = false;
+        for wrapper in wrappers.iter().rev() {
+            let has_command_placeholder = wrapper.iter().any(|arg| arg == COMMAND_PLACEHOLDER);
+            argv = if has_command_placeholder {
+                // only the innermost command needs it, the outer ones have it already
+                if !is_env_in_command {
+                    argv = get_env_argv(&cmd).into_iter().chain(argv).collect();
+                    is_env_in_command = true;
+                }
+                let command_str = to_shell_command(&argv);
+                wrapper
+                    .iter()
+                    .map(|arg| {
+                        if arg == COMMAND_PLACEHOLDER {
+                            command_str.clone()
+                        } else {
+                            arg.to_string()
+                        }
+                    })
+                    .collect()
+            } else {
+                wrapper.iter().cloned().chain(argv).collect()
+            };
+        }
+
+        let (program, arguments) = argv.split_at(1);
+        let mut wrapped_cmd = Command::new(program.first().unwrap());
+        wrapped_cmd.args(arguments);
+        for (name, value_maybe) in cmd.get_envs() {
+            match value_maybe {
+                Some(value) => wrapped_cmd.env(name, value),
+                None => wrapped_cmd.env_remove(name),
+            };
+        }
+        if let Some(working_dir) = cmd.get_current_dir() {
+            wrapped_cmd.current_dir(working_dir);
+        }
+        return wrapped_cmd;
+    }
+
+    // Settings edit arguments one per line,
+    // environment variables as "NAME=value" per line, "-NAME" to unset,
+    // and wrappers as a shell-quoted command per line
+    pub fn from_lines(
+        args_lines: &str,
+        env_lines: &str,
+        working_dir: &str,
+        wrappers_lines: &str,
+    ) -> Self {
+        let args: Vec<String> = args_lines
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect();
+
+        let mut env: BTreeMap<String, String> = BTreeMap::new();
+        let mut env_remove: Vec<String> = Vec::new();
+        for line in env_lines.lines().map(|line| line.trim()) {
+            if let Some(name) = line.strip_prefix('-') {
+                if !name.trim().is_empty() {
+                    env_remove.push(name.trim().to_string());
+                }
+            } else if let Some((name, value)) = line.split_once('=') {
+                if !name.trim().is_empty() {
+                    env.insert(name.trim().to_string(), value.to_string());
+                }
+            }
+        }
+
+        let wrappers: Vec<Vec<String>> = wrappers_lines
+            .lines()
+            .filter_map(|line| {
+                let wrapper_maybe = desktop_exec_parser::split_arguments(line);
+                if wrapper_maybe.is_none() {
+                    warn!("Skipping wrapper with an unclosed quote: {}", line);
+                }
+                wrapper_maybe
+            })
+            .filter(|wrapper| !wrapper.is_empty())
+            .collect();
+
+        let working_dir = working_dir.trim();
+        return Self {
+            args: args,
+            env: env,
+            env_remove: env_remove,
+            working_dir: (!working_dir.is_empty()).then(|| working_dir.to_string()),
+            wrappers: wrappers,
+        };
+    }
+
+    pub fn get_args_lines(&self) -> String {
+        return self.args.join("\n");
+    }
+
+    pub fn get_env_lines(&self) -> String {
+        let env_lines = self
+            .env
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value));
+        let env_remove_lines = self.env_remove.iter().map(|name| format!("-{}", name));
+        return env_lines
+            .chain(env_remove_lines)
+            .collect::<Vec<String>>()
+            .join("\n");
+    }
+
+    pub fn get_wrappers_lines(&self) -> String {
+        return self
+            .wrappers
+            .iter()
+            .map(|wrapper| to_shell_command(wrapper))
+            .collect::<Vec<String>>()
+            .join("\n");
+    }
+}
+
+pub fn get_argv(cmd: &Command) -> Vec<String> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let arguments = cmd.get_args().map(|arg| arg.to_string_lossy().to_string());
+    return std::iter::once(program).chain(arguments).collect();
+}
+
+// the command as it would be typed in a shell, e.g for logging or `--dry-run`
+pub fn to_shell_command(argv: &[String]) -> String {
+    return argv
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<String>>()
+        .join(" ");
+}
+
+// `env` with the variables the command sets or unsets (empty if none), e.g
+// ["env", "-u", "LANG", "MOZ_ENABLE_WAYLAND=1"]; unsets go first, as `env` stops reading
+// options after the first variable
+fn get_env_argv(cmd: &Command) -> Vec<String> {
+    let mut unset_args: Vec<String> = Vec::new();
+    let mut set_args: Vec<String> = Vec::new();
+    for (name, value_maybe) in cmd.get_envs() {
+        let name = name.to_string_lossy().to_string();
+        match value_maybe {
+            Some(value) => set_args.push(format!("{}={}", name, value.to_string_lossy())),
+            None => unset_args.extend(["-u".to_string(), name]),
+        }
+    }
+    if unset_args.is_empty() && set_args.is_empty() {
+        return vec![];
+    }
+
+    return std::iter::once("env".to_string())
+        .chain(unset_args)
+        .chain(set_args)
+        .collect();
+}
+
+// the command with its environment and working dir, runnable in a shell, e.g
+// `cd /tmp && env -u LANG MOZ_ENABLE_WAYLAND=1 firejail firefox -P Work https://example.com/`
+pub fn to_shell_command_line(cmd: &Command) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(working_dir) = cmd.get_current_dir() {
+        parts.push(format!("cd {} &&", shell_quote(&working_dir.to_string_lossy())));
+    }
+
+    let env_argv = get_env_argv(cmd);
+    if !env_argv.is_empty() {
+        parts.push(to_shell_command(&env_argv));
+    }
+
+    parts.push(to_shell_command(&get_argv(cmd)));
+    return parts.join(" ");
+}
+
+fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+=/.,:@%".contains(c));
+    if is_safe {
+        return arg.to_string();
+    }
+    return format!("'{}'", arg.replace('\'', "'\\''"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch_options(json: &str) -> BTreeMap<String, LaunchOptionsConfig> {
+        return serde_json::from_str(json).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_merges_app_and_profile_options() {
+        let launch_options = launch_options(
+            r#"{
+                "/usr/bin/google-chrome": {
+                    "args": ["--ozone-platform=wayland"],
+                    "env": {"GTK_THEME": "Adwaita", "LANG": "et_EE.UTF-8"},
+                    "env_remove": ["GTK_IM_MODULE"],
+                    "working_dir": "/tmp"
+                },
+                "/usr/bin/google-chrome#Profile 3": {
+                    "args": ["--enable-features=Vulkan"],
+                    "env": {"GTK_IM_MODULE": "ibus"},
+                    "env_remove": ["LANG"]
+                }
+            }"#,
+        );
+
+        let profile_options = LaunchOptionsConfig::resolve(
+            &launch_options,
+            &[],
+            "/usr/bin/google-chrome",
+            "/usr/bin/google-chrome#Profile 3",
+        );
+        assert_eq!(
+            profile_options.args,
+            vec!["--ozone-platform=wayland", "--enable-features=Vulkan"]
+        );
+        assert_eq!(
+            profile_options.env,
+            BTreeMap::from([
+                ("GTK_IM_MODULE".to_string(), "ibus".to_string()),
+                ("GTK_THEME".to_string(), "Adwaita".to_string()),
+            ])
+        );
+        assert_eq!(profile_options.env_remove, vec!["LANG"]);
+        assert_eq!(profile_options.working_dir, Some("/tmp".to_string()));
+
+        let other_profile_options = LaunchOptionsConfig::resolve(
+            &launch_options,
+            &[],
+            "/usr/bin/google-chrome",
+            "/usr/bin/google-chrome#Default",
+        );
+        assert_eq!(other_profile_options.args, vec!["--ozone-platform=wayland"]);
+
+        let other_app_options = LaunchOptionsConfig::resolve(
+            &launch_options,
+            &[],
+            "/usr/bin/firefox",
+            "/usr/bin/firefox#",
+        );
+        assert!(other_app_options.is_empty());
+    }
+
+    #[test]
+    fn test_from_and_to_lines() {
+        let options = LaunchOptionsConfig::from_lines(
+            "--proxy-server=socks5://localhost:1080\n\n  --no-first-run \n",
+            "MOZ_ENABLE_WAYLAND=1\nEXTRA_FLAGS=--a=b\n-GTK_IM_MODULE\nnot an assignment\n",
+            "  ",
+            "systemd-run --user --scope -p MemoryMax=4G\n\nfirejail --name='client work' \"--x=\\\"a\\\" b\"\n",
+        );
+        assert_eq!(
+            options.args,
+            vec!["--proxy-server=socks5://localhost:1080", "--no-first-run"]
+        );
+        assert_eq!(options.env.get("EXTRA_FLAGS"), Some(&"--a=b".to_string()));
+        assert_eq!(options.env_remove, vec!["GTK_IM_MODULE"]);
+        assert_eq!(options.working_dir, None);
+
+        assert_eq!(
+            options.get_env_lines(),
+            "EXTRA_FLAGS=--a=b\nMOZ_ENABLE_WAYLAND=1\n-GTK_IM_MODULE"
+        );
+        assert_eq!(
+            options.get_args_lines(),
+            "--proxy-server=socks5://localhost:1080\n--no-first-run"
+        );
+
+        assert_eq!(
+            options.wrappers,
+            vec![
+                vec!["systemd-run", "--user", "--scope", "-p", "MemoryMax=4G"],
+                vec!["firejail", "--name=client work", "--x=\"a\" b"],
+            ]
+        );
+        assert_eq!(
+            options.get_wrappers_lines(),
+            "systemd-run --user --scope -p MemoryMax=4G\nfirejail '--name=client work' '--x=\"a\" b'"
+        );
+        let reparsed = LaunchOptionsConfig::from_lines("", "", "", &options.get_wrappers_lines());
+        assert_eq!(reparsed.wrappers, options.wrappers);
+    }
+
+    #[test]
+    fn test_apply_env_and_working_dir() {
+        let options =
+            LaunchOptionsConfig::from_lines("", "MOZ_ENABLE_WAYLAND=1\n-LANG", "/tmp", "");
+        let mut cmd = Command::new("firefox");
+        options.apply_env_and_working_dir(&mut cmd);
+
+        let envs: Vec<(String, Option<String>)> = cmd
+            .get_envs()
+            .map(|(name, value)| {
+                (
+                    name.to_string_lossy().to_string(),
+                    value.map(|value| value.to_string_lossy().to_string()),
+                )
+            })
+            .collect();
+        assert!(envs.contains(&("LANG".to_string(), None)));
+        assert!(envs.contains(&("MOZ_ENABLE_WAYLAND".to_string(), Some("1".to_string()))));
+        assert_eq!(cmd.get_current_dir(), Some(std::path::Path::new("/tmp")));
+    }
+
+    #[test]
+    fn test_wrap_command() {
+        let launch_options = launch_options(
+            r#"{
+                "/usr/bin/firefox": {
+                    "env": {"MOZ_ENABLE_WAYLAND": "1"},
+                    "working_dir": "/tmp",
+                    "wrappers": [["systemd-run", "--user", "--scope", "-p", "MemoryMax=4G"]]
+                },
+                "/usr/bin/firefox#Work": {
+                    "wrappers": [["firejail", "--private"]]
+                },
+                "/usr/bin/firefox#Client": {
+                    "wrappers": [["distrobox", "enter", "client", "--", "sh", "-c", "%c"]]
+                }
+            }"#,
+        );
+
+        let options = LaunchOptionsConfig::resolve(
+            &launch_options,
+            &[],
+            "/usr/bin/firefox",
+            "/usr/bin/firefox#Work",
+        );
+        let mut cmd = Command::new("firefox");
+        cmd.args(["-P", "Work", "https://example.com/?a=1&b=2"]);
+        options.apply_env_and_working_dir(&mut cmd);
+        let cmd = options.wrap_command(cmd);
+        assert_eq!(
+            get_argv(&cmd),
+            vec![
+                "systemd-run",
+                "--user",
+                "--scope",
+                "-p",
+                "MemoryMax=4G",
+                "firejail",
+                "--private",
+                "firefox",
+                "-P",
+                "Work",
+                "https://example.com/?a=1&b=2",
+            ]
+        );
+        assert_eq!(
+            to_shell_command_line(&cmd),
+            "cd /tmp && env MOZ_ENABLE_WAYLAND=1 systemd-run --user --scope -p MemoryMax=4G \
+            firejail --private firefox -P Work 'https://example.com/?a=1&b=2'"
+        );
+
+        let options = LaunchOptionsConfig::resolve(
+            &launch_options,
+            &[],
+            "/usr/bin/firefox",
+            "/usr/bin/firefox#Client",
+        );
+        let mut cmd = Command::new("firefox");
+        cmd.args(["-P", "Client's", "https://example.com/?a=1&b=2"]);
+        cmd.env_remove("LANG");
+        options.apply_env_and_working_dir(&mut cmd);
+        let cmd = options.wrap_command(cmd);
+        let argv = get_argv(&cmd);
+        assert_eq!(
+            argv[5..argv.len() - 1],
+            ["distrobox", "enter", "client", "--", "sh", "-c"]
+        );
+        // so the environment reaches the command inside the container
+        assert_eq!(
+            argv.last().unwrap(),
+            r#"env -u LANG MOZ_ENABLE_WAYLAND=1 firefox -P 'Client'\''s' 'https://example.com/?a=1&b=2'"#
+        );
+    }
+}
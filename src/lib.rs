@@ -1,27 +1,39 @@
 use druid::{ExtEventSink, Target, UrlOpenInfo};
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::path::Path;
 use std::process::{exit, Command};
 use std::str::FromStr;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, instrument, warn};
 use url::form_urlencoded::Parse;
 use url::Url;
 
 use gui::ui;
 
-use crate::browser_repository::{SupportedApp, SupportedAppRepository};
-use crate::gui::ui::{UIBehavioralSettings, UIProfileAndIncognito, UISettingsRule};
+use crate::browser_repository::{GuessedAppConfig, SupportedApp, SupportedAppRepository};
+use crate::custom_apps::CustomAppConfig;
+use crate::google_url_parser::GoogleAuthUserConfig;
+use crate::gui::ui::{
+    UIBehavioralSettings, UILaunchOptions, UIProfileAndIncognito, UISettingsRule,
+};
 use crate::gui::ui::{UIVisualSettings, UI};
+use crate::launch_options::LaunchOptionsConfig;
 use crate::url_rule::UrlGlobMatcher;
 use crate::utils::{
-    BehavioralConfig, Config, ConfigRule, OSAppFinder, ProfileAndOptions, UIConfig,
+    AppInstanceConfig, BehavioralConfig, Config, ConfigRule, LaunchMode, MailAccountConfig,
+    OSAppFinder, ProfileAndOptions, UIConfig,
 };
 
 mod gui;
 
+pub mod launch_options;
 pub mod paths;
 pub mod utils;
 
@@ -32,16 +44,32 @@ mod macos;
 
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::detached_process;
 
 #[cfg(target_os = "windows")]
 mod windows;
 
+mod account_selector;
 mod chromium_profiles_parser;
+mod custom_apps;
+mod desktop_exec_parser;
+mod epiphany_profiles_parser;
+mod falkon_profiles_parser;
+mod firefox_profile_groups_parser;
 mod firefox_profiles_parser;
+mod flatpak_command_parser;
+mod google_url_parser;
+mod mail_url_parser;
+mod qutebrowser_profiles_parser;
 mod slack_profiles_parser;
 mod slack_url_parser;
+mod thunderbird_profiles_parser;
 mod url_rule;
 
+#[cfg(test)]
+mod test_utils;
+
 // a browser (with profiles), or Spotify, Zoom, etc
 pub struct GenericApp {
     app: BrowserCommon,
@@ -49,11 +77,25 @@ pub struct GenericApp {
 }
 
 impl GenericApp {
-    fn new(installed_browser: &InstalledBrowser, app_repository: &SupportedAppRepository) -> Self {
-        let supported_app = app_repository.get_or_generate(
-            installed_browser.bundle.as_str(),
-            &installed_browser.restricted_domains,
-        );
+    fn new(
+        installed_browser: &InstalledBrowser,
+        app_repository: &SupportedAppRepository,
+        google_authuser_config: &GoogleAuthUserConfig,
+    ) -> Self {
+        let mut supported_app = if installed_browser.custom_app.is_some() {
+            app_repository.generate_custom_app(
+                installed_browser.bundle.as_str(),
+                &installed_browser.restricted_domains,
+            )
+        } else {
+            app_repository.get_or_generate(
+                installed_browser.bundle.as_str(),
+                &installed_browser.restricted_domains,
+                installed_browser.guessed_app_config.as_ref(),
+            )
+        };
+        supported_app.add_missing_launch_modes(&installed_browser.desktop_action_launch_modes);
+
         let app = BrowserCommon {
             supported_app: supported_app,
             command: installed_browser.command.clone(),
@@ -61,6 +103,9 @@ impl GenericApp {
             display_name: installed_browser.display_name.to_string(),
             icon_path: installed_browser.icon_path.to_string(),
             profiles_type: installed_browser.profiles.profiles_type.clone(),
+            google_authuser_config: google_authuser_config.clone(),
+            custom_user_dir: installed_browser.custom_user_dir.clone(),
+            custom_app: installed_browser.custom_app.clone(),
         };
 
         let arc = Arc::new(app.clone());
@@ -88,12 +133,31 @@ pub struct BrowserCommon {
     icon_path: String,
     supported_app: SupportedApp,
     profiles_type: InstalledAppProfilesType,
+    google_authuser_config: GoogleAuthUserConfig,
+    // user dir of an app instance (see `AppInstanceConfig`)
+    custom_user_dir: Option<String>,
+    custom_app: Option<CustomAppConfig>,
 }
 
 impl BrowserCommon {
     // used in configuration file to uniquely identify this app
     fn get_unique_app_id(&self) -> String {
-        return self.executable_path.to_string();
+        return if let Some(custom_user_dir) = &self.custom_user_dir {
+            format!("{}#{}", self.executable_path, custom_user_dir)
+        } else {
+            self.executable_path.to_string()
+        };
+    }
+
+    fn get_user_dir_args(&self, profile_cli_arg_value: &str) -> Vec<String> {
+        return self
+            .custom_user_dir
+            .as_ref()
+            .map(|custom_user_dir| {
+                self.supported_app
+                    .get_user_dir_args(custom_user_dir, profile_cli_arg_value)
+            })
+            .unwrap_or_default();
     }
 
     fn has_real_profiles(&self) -> bool {
@@ -104,6 +168,10 @@ impl BrowserCommon {
         return self.supported_app.supports_incognito();
     }
 
+    fn get_launch_modes(&self) -> Vec<LaunchMode> {
+        return self.supported_app.get_launch_modes();
+    }
+
     fn get_browser_icon_path(&self) -> &str {
         return self.icon_path.as_str();
     }
@@ -116,16 +184,26 @@ impl BrowserCommon {
         &self,
         common_browser_profile: &CommonBrowserProfile,
         url: &str,
-        incognito_mode: bool,
+        launch_mode_maybe: Option<LaunchMode>,
     ) -> Command {
+        if let Some(custom_app) = &self.custom_app {
+            return Self::create_custom_app_command(custom_app, common_browser_profile, url);
+        }
+
         let profile_cli_arg_value: &str = &common_browser_profile.profile_cli_arg_value;
-        let profile_args = self.supported_app.get_profile_args(profile_cli_arg_value);
+        let mut profile_args = self.get_user_dir_args(profile_cli_arg_value);
+        profile_args.extend(self.supported_app.get_profile_args(profile_cli_arg_value));
+        profile_args.extend(common_browser_profile.launch_options.args.iter().cloned());
         let app_url = self
             .supported_app
             .get_transformed_url(common_browser_profile, url);
+        // empty if the app doesn't support the launch mode
+        let launch_mode_args = self.supported_app.get_launch_mode_args(launch_mode_maybe);
+        let url_args = self
+            .supported_app
+            .get_url_args(app_url.as_str(), launch_mode_maybe);
 
-        let (main_command, command_arguments) = self.command.split_at(1);
-        let main_command = main_command.first().unwrap(); // guaranteed to not be empty
+        let main_command = self.command.first().unwrap(); // guaranteed to not be empty
 
         // TODO: support BSD - https://doc.rust-lang.org/reference/conditional-compilation.html
         if cfg!(target_os = "macos") {
@@ -145,77 +223,155 @@ impl BrowserCommon {
 
             arguments.arg("--args");
             arguments.args(profile_args);
-
-            if incognito_mode && self.supported_app.supports_incognito() {
-                let incognito_args = self.supported_app.get_incognito_args();
-                arguments.args(incognito_args);
-            }
+            arguments.args(launch_mode_args);
 
             if self.supported_app.is_url_as_first_arg() {
-                arguments.arg(app_url.clone());
+                arguments.args(url_args);
             }
 
             debug!("Launching: {:?}", cmd);
             return cmd;
         } else if cfg!(target_os = "linux") {
-            let has_url_placeholder = command_arguments
-                .iter()
-                .any(|arg| arg.eq_ignore_ascii_case("%u"));
-
-            let arguments = if has_url_placeholder {
-                replace_url_placeholder(command_arguments, app_url.as_str())
-            } else {
-                command_arguments.to_vec()
-            };
-
-            let mut cmd = Command::new(main_command.to_string());
-
-            // this might mess up the command,
-            // if `main_command` is not yet the actual program that takes the incognito argument;
-            // that's because the actual program might be in `arguments` (depends what's in the .desktop file)
-            if incognito_mode && self.supported_app.supports_incognito() {
-                let incognito_args = self.supported_app.get_incognito_args();
-                cmd.args(incognito_args);
-            }
-
+            // app arguments go right after the actual app, which might be run by a launcher
+            // in the desktop entry, e.g `env VAR=..`, `flatpak run <app id>` or `snap run <snap>`
+            let mut app_args = profile_args;
+            app_args.extend(launch_mode_args);
+            let command = desktop_exec_parser::expand_exec(&self.command, &app_args, &url_args);
+            let (program, arguments) = command.split_at(1);
+
+            let mut cmd = Command::new(program.first().unwrap());
             cmd.args(arguments);
-            cmd.args(profile_args);
-
-            // Non-browser apps don't have the placeholder
-            if !has_url_placeholder {
-                cmd.arg(app_url);
-            }
 
+            debug!("Launching: {:?}", cmd);
             return cmd;
         } else if cfg!(target_os = "windows") {
             let mut cmd = Command::new(main_command.to_string());
             cmd.args(profile_args);
-
-            if incognito_mode && self.supported_app.supports_incognito() {
-                let incognito_args = self.supported_app.get_incognito_args();
-                cmd.args(incognito_args);
-            }
-
-            cmd.arg(app_url);
+            cmd.args(launch_mode_args);
+            cmd.args(url_args);
 
             return cmd;
         }
 
         unimplemented!("platform is not supported yet");
     }
+
+    // same on all platforms, launch options arguments are added right after the program
+    fn create_custom_app_command(
+        custom_app: &CustomAppConfig,
+        common_browser_profile: &CommonBrowserProfile,
+        url: &str,
+    ) -> Command {
+        let command = custom_app.get_command(url);
+        let (program, arguments) = command.split_at(1);
+        let program = program.first().unwrap(); // custom apps without a command are skipped
+
+        let mut cmd = Command::new(program);
+        cmd.args(common_browser_profile.launch_options.args.iter());
+        cmd.args(arguments);
+
+        debug!("Launching: {:?}", cmd);
+        return cmd;
+    }
 }
 
-fn replace_url_placeholder(command_arguments: &[String], app_url: &str) -> Vec<String> {
-    return command_arguments
-        .iter()
-        .map(|arg| {
-            if arg.eq_ignore_ascii_case("%u") {
-                app_url.to_string()
-            } else {
-                arg.to_string()
+// e.g a moved binary fails to spawn, while a locked profile makes the app exit with an error soon
+fn spawn_and_check_early_exit(cmd: &mut Command, grace_period: Duration) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    linux::detached_process::detach(cmd);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            warn!(
+                "Failed to launch: {}, error: {}",
+                launch_options::to_shell_command_line(cmd),
+                error
+            );
+            return Err(error.to_string());
+        }
+    };
+
+    let started_at = Instant::now();
+    while started_at.elapsed() < grace_period {
+        match child.try_wait() {
+            // apps often just pass the link to their already running instance and exit
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                warn!(
+                    "Launched app exited early: {}, status: {}",
+                    launch_options::to_shell_command_line(cmd),
+                    status
+                );
+                return Err(format!("exited with {}", status));
             }
-        })
-        .collect();
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(error) => {
+                warn!("Could not check if launched app is running: {}", error);
+                break;
+            }
+        }
+    }
+
+    // still running
+    #[cfg(target_os = "linux")]
+    linux::detached_process::reap_in_background(child);
+
+    return Ok(());
+}
+
+// Opens the link in the profile, or if that fails and fallback is enabled, then in the default
+// opener (or the next visible profile); returns the error to show if the link wasn't opened
+fn open_link_with_fallback(
+    profile: &CommonBrowserProfile,
+    url: &str,
+    launch_mode_maybe: Option<LaunchMode>,
+    authuser_maybe: Option<&str>,
+    opening_rules_and_default_profile: &OpeningRulesAndDefaultProfile,
+    visible_and_hidden_profiles: &VisibleAndHiddenProfiles,
+    behavioral_config: &BehavioralConfig,
+) -> Result<(), String> {
+    let grace_period = Duration::from_millis(behavioral_config.launch_failure_grace_period_ms);
+    let error = match profile.open_link(url, launch_mode_maybe, authuser_maybe, grace_period) {
+        Ok(()) => return Ok(()),
+        Err(error) => error,
+    };
+    if !behavioral_config.fallback_on_launch_failure {
+        return Err(error);
+    }
+
+    let fallback_profile_maybe = visible_and_hidden_profiles
+        .find_fallback_profile(profile, &opening_rules_and_default_profile.default_profile);
+    let fallback_profile = match fallback_profile_maybe {
+        Some(fallback_profile) => fallback_profile,
+        None => return Err(error),
+    };
+
+    info!(
+        "Falling back to {} after: {}",
+        fallback_profile.get_unique_id(),
+        error
+    );
+    // the launch mode (e.g app window) was meant for the failed app, so open normally
+    return fallback_profile
+        .open_link(url, None, None, grace_period)
+        .map_err(|fallback_error| format!("{}; {}", error, fallback_error));
+}
+
+// a failure is shown in the picker, so that another browser can be chosen
+pub fn submit_open_link_result(ui_event_sink: &ExtEventSink, result: Result<(), String>) {
+    match result {
+        Ok(()) => ui_event_sink
+            .submit_command(
+                ui::OPEN_LINK_IN_BROWSER_COMPLETED,
+                "".to_string(),
+                Target::Global,
+            )
+            .ok(),
+        Err(error) => ui_event_sink
+            .submit_command(ui::OPEN_LINK_IN_BROWSER_FAILED, error, Target::Global)
+            .ok(),
+    };
 }
 
 #[derive(Clone)]
@@ -225,6 +381,13 @@ pub struct CommonBrowserProfile {
     profile_name: String,
     profile_icon: Option<String>,
     profile_restricted_url_matchers: Vec<UrlGlobMatcher>,
+    profile_email: Option<String>,
+    profile_hosted_domain: Option<String>,
+    profile_enterprise_domain: Option<String>,
+    profile_last_active: Option<i64>,
+    profile_legacy_cli_arg_value: Option<String>,
+    // from config, set when profiles are generated
+    launch_options: LaunchOptionsConfig,
     app: Arc<BrowserCommon>,
 }
 
@@ -245,6 +408,14 @@ impl CommonBrowserProfile {
                 .as_ref()
                 .map(|path| path.clone()),
             profile_restricted_url_matchers: profile_restricted_url_matchers,
+            profile_email: installed_browser_profile.profile_email.clone(),
+            profile_hosted_domain: installed_browser_profile.profile_hosted_domain.clone(),
+            profile_enterprise_domain: installed_browser_profile.profile_enterprise_domain.clone(),
+            profile_last_active: installed_browser_profile.profile_last_active,
+            profile_legacy_cli_arg_value: installed_browser_profile
+                .profile_legacy_cli_arg_value
+                .clone(),
+            launch_options: LaunchOptionsConfig::default(),
             app: app,
         }
     }
@@ -266,8 +437,20 @@ impl CommonBrowserProfile {
 
     // used in configuration file to uniquely identify this app+profile+container
     fn get_unique_id(&self) -> String {
+        return self.to_unique_id(self.profile_cli_arg_value.as_str());
+    }
+
+    // unique id which was used in configuration file before the profile_cli_arg_value changed
+    fn get_legacy_unique_id(&self) -> Option<String> {
+        return self
+            .profile_legacy_cli_arg_value
+            .as_ref()
+            .map(|legacy_cli_arg_value| self.to_unique_id(legacy_cli_arg_value.as_str()));
+    }
+
+    fn to_unique_id(&self, profile_cli_arg_value: &str) -> String {
         let app_id = self.get_unique_app_id();
-        let app_and_profile = app_id + "#" + self.profile_cli_arg_value.as_str();
+        let app_and_profile = app_id + "#" + profile_cli_arg_value;
 
         if let Some(ref profile_cli_container_name) = self.profile_cli_container_name {
             return app_and_profile + "#" + profile_cli_container_name.as_str();
@@ -316,12 +499,96 @@ impl CommonBrowserProfile {
         return self.profile_name.as_str();
     }
 
-    fn open_link(&self, url: &str, incognito_mode: bool) {
-        let _ = &self.create_command(url, incognito_mode).spawn();
+    fn get_profile_email(&self) -> Option<&String> {
+        return self.profile_email.as_ref();
+    }
+
+    fn get_profile_hosted_domain(&self) -> Option<&String> {
+        return self.profile_hosted_domain.as_ref();
+    }
+
+    fn get_profile_last_active(&self) -> Option<i64> {
+        return self.profile_last_active;
+    }
+
+    // e.g "Firefox (Work)", or just "Spotify" if it has no profiles
+    fn get_full_name(&self) -> String {
+        return if self.profile_name.is_empty() {
+            self.get_browser_name().to_string()
+        } else {
+            format!("{} ({})", self.get_browser_name(), self.profile_name)
+        };
+    }
+
+    // authuser_maybe is the Google account given by a rule, which takes precedence over the
+    // signed-in account of the profile;
+    // returns an error if the app couldn't be started or exited with an error within grace_period
+    fn open_link(
+        &self,
+        url: &str,
+        launch_mode_maybe: Option<LaunchMode>,
+        authuser_maybe: Option<&str>,
+        grace_period: Duration,
+    ) -> Result<(), String> {
+        let mut cmd = self.create_link_command(url, launch_mode_maybe, authuser_maybe);
+        return spawn_and_check_early_exit(&mut cmd, grace_period)
+            .map_err(|error| format!("Could not open in {}: {}", self.get_full_name(), error));
+    }
+
+    fn create_link_command(
+        &self,
+        url: &str,
+        launch_mode_maybe: Option<LaunchMode>,
+        authuser_maybe: Option<&str>,
+    ) -> Command {
+        let url = self.add_google_authuser(url, authuser_maybe);
+        return self.create_command(url.as_str(), launch_mode_maybe);
+    }
+
+    fn add_google_authuser(&self, url: &str, authuser_maybe: Option<&str>) -> String {
+        let google_authuser_config = &self.get_browser_common().google_authuser_config;
+
+        let account_maybe = authuser_maybe.or_else(|| {
+            google_authuser_config
+                .use_profile_account
+                .then(|| self.get_profile_email().map(|a| a.as_str()))
+                .flatten()
+        });
+        if account_maybe.is_none() {
+            return url.to_string();
+        }
+        let account = account_maybe.unwrap();
+
+        let url_with_account_maybe =
+            google_url_parser::add_authuser(google_authuser_config, url, account);
+        if let Some(ref url_with_account) = url_with_account_maybe {
+            debug!("Added Google account to url: {}", url_with_account);
+        }
+
+        return url_with_account_maybe.unwrap_or(url.to_string());
+    }
+
+    fn create_command(&self, url: &str, launch_mode_maybe: Option<LaunchMode>) -> Command {
+        let mut cmd = self.app.create_command(self, url, launch_mode_maybe);
+        self.launch_options.apply_env_and_working_dir(&mut cmd);
+        if cfg!(target_os = "linux") {
+            // e.g firejail or systemd-run, see `LaunchOptionsConfig`
+            cmd = self.launch_options.wrap_command(cmd);
+        }
+        return cmd;
     }
 
-    fn create_command(&self, url: &str, incognito_mode: bool) -> Command {
-        return self.app.create_command(self, url, incognito_mode);
+    fn set_launch_options(
+        &mut self,
+        launch_options: &BTreeMap<String, LaunchOptionsConfig>,
+        inherited_env_remove: &[String],
+    ) {
+        self.launch_options = LaunchOptionsConfig::resolve(
+            launch_options,
+            inherited_env_remove,
+            self.get_unique_app_id().as_str(),
+            self.get_unique_id().as_str(),
+        );
     }
 }
 
@@ -359,6 +626,28 @@ pub struct InstalledBrowser {
 
     #[serde(default)]
     restricted_domains: Vec<String>,
+
+    // user dir of an app instance (see `AppInstanceConfig`)
+    #[serde(default)]
+    custom_user_dir: Option<String>,
+
+    // set for custom apps (see `CustomAppConfig`)
+    #[serde(default)]
+    custom_app: Option<CustomAppConfig>,
+
+    // Linux only, launch modes from the Desktop Actions of the app (e.g "new-private-window"),
+    // as the arguments to add to the command
+    #[serde(default)]
+    desktop_action_launch_modes: Vec<(LaunchMode, Vec<String>)>,
+
+    // Linux only, `NoDisplay` of the desktop entry, e.g helper entries which only handle links
+    #[serde(default)]
+    no_display: bool,
+
+    // config of a Chromium or Firefox fork which is not in the app repository,
+    // guessed once when finding installed apps
+    #[serde(default)]
+    guessed_app_config: Option<GuessedAppConfig>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -391,6 +680,11 @@ impl InstalledAppProfiles {
             profile_name: "".to_string(),
             profile_icon: None,
             profile_restricted_url_patterns: vec![],
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: None,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: None,
         });
 
         return browser_profiles;
@@ -410,6 +704,24 @@ pub struct InstalledBrowserProfile {
     profile_name: String,
     profile_icon: Option<String>,
     profile_restricted_url_patterns: Vec<String>,
+    // email of the signed-in account
+    #[serde(default)]
+    profile_email: Option<String>,
+    // e.g company domain of a Google Workspace account
+    #[serde(default)]
+    profile_hosted_domain: Option<String>,
+    // Enterprise Grid organization of a Slack workspace, e.g "acme-corp" of
+    // "acme-corp.enterprise.slack.com"
+    #[serde(default)]
+    profile_enterprise_domain: Option<String>,
+    // seconds since unix epoch
+    #[serde(default)]
+    profile_last_active: Option<i64>,
+    // previous profile_cli_arg_value, when the way to launch the profile has changed
+    // (e.g Firefox profiles by name before they were launched by path),
+    // used to migrate the profile ids in config
+    #[serde(default)]
+    profile_legacy_cli_arg_value: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -520,6 +832,78 @@ impl VisibleAndHiddenProfiles {
 
         return None;
     }
+
+    // Profile of a rule opener, either by its signed-in account or by its unique id
+    pub(crate) fn get_browser_profile_for_opener(
+        &self,
+        opener: &ProfileAndOptions,
+    ) -> Option<&CommonBrowserProfile> {
+        return if let Some(ref account) = opener.account {
+            self.get_browser_profile_by_account(account.as_str())
+        } else {
+            self.get_browser_profile_by_id(opener.profile.as_str())
+        };
+    }
+
+    // Resolved when opening the link, so it keeps working even if the profile is recreated.
+    // If several profiles match equally well, the one with the smallest unique id wins,
+    // as the picker order can change between runs (e.g when sorted by last activity).
+    fn get_browser_profile_by_account(&self, account: &str) -> Option<&CommonBrowserProfile> {
+        let mut all_profiles: Vec<&CommonBrowserProfile> = self
+            .visible_browser_profiles
+            .iter()
+            .chain(self.hidden_browser_profiles.iter())
+            .collect();
+        all_profiles.sort_by_key(|p| p.get_unique_id());
+
+        let candidates: Vec<(Option<&str>, Option<&str>)> = all_profiles
+            .iter()
+            .map(|p| {
+                (
+                    p.get_profile_email().map(|a| a.as_str()),
+                    p.get_profile_hosted_domain().map(|a| a.as_str()),
+                )
+            })
+            .collect();
+
+        let profile_index_maybe = account_selector::find_best_match(account, &candidates);
+        if profile_index_maybe.is_none() {
+            info!("No profile is signed in as {}", account);
+        }
+
+        return profile_index_maybe.map(|i| all_profiles[i]);
+    }
+
+    // The default opener, or if that's the failed profile too, then the next visible profile
+    fn find_fallback_profile(
+        &self,
+        failed_profile: &CommonBrowserProfile,
+        default_profile: &Option<ProfileAndOptions>,
+    ) -> Option<&CommonBrowserProfile> {
+        let failed_profile_id = failed_profile.get_unique_id();
+
+        let default_opener_maybe = default_profile
+            .as_ref()
+            .and_then(|opener| self.get_browser_profile_for_opener(opener))
+            .filter(|p| p.get_unique_id() != failed_profile_id);
+        if default_opener_maybe.is_some() {
+            return default_opener_maybe;
+        }
+
+        let visible_profiles = &self.visible_browser_profiles;
+        let next_index = visible_profiles
+            .iter()
+            .position(|p| p.get_unique_id() == failed_profile_id)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        // wraps around to the first profile, if the failed one was the last
+        return visible_profiles
+            .iter()
+            .cycle()
+            .skip(next_index)
+            .take(visible_profiles.len())
+            .find(|p| p.get_unique_id() != failed_profile_id);
+    }
 }
 
 pub fn get_opening_rules(config: &Config) -> OpeningRulesAndDefaultProfile {
@@ -545,32 +929,86 @@ fn to_opening_rules(config_rules: &Vec<ConfigRule>) -> Vec<OpeningRule> {
 }
 
 #[instrument(skip_all)]
+// Also migrates legacy profile ids in config (and saves the config), so config must be used
+// only after calling this.
+// A dry run (`--dry-run`) doesn't save the config or url schemes, nor sync the scheme handlers
 pub fn generate_all_browser_profiles(
-    config: &Config,
+    config: &mut Config,
     app_finder: &OSAppFinder,
     force_reload: bool,
+    dry_run: bool,
 ) -> VisibleAndHiddenProfiles {
-    let installed_browsers = app_finder.get_installed_browsers_cached(force_reload);
+    let schemes = config.get_schemes();
+    let schemes_changed = !app_finder.has_cached_schemes(&schemes);
+    if schemes_changed && !dry_run {
+        app_finder.save_cached_schemes(&schemes);
+        utils::sync_url_scheme_handlers(&schemes);
+    }
+    let mut installed_browsers =
+        app_finder.get_installed_browsers_cached(force_reload || schemes_changed, &schemes);
+    add_mail_account_profiles(
+        &mut installed_browsers,
+        config.get_mail_accounts(),
+        app_finder.get_app_repository(),
+    );
+    let app_instances = app_finder.get_app_instances_cached(
+        force_reload || schemes_changed,
+        &installed_browsers,
+        config.get_app_instances(),
+    );
+    let custom_apps = create_custom_apps(config.get_custom_apps());
+    let hide_no_display_apps = config.get_hide_no_display_apps();
+
+    //let support_dir = macos_get_application_support_dir();
+    debug!("Apps");
+    let apps: Vec<GenericApp> = installed_browsers
+        .iter()
+        .filter(|installed_browser| {
+            let is_hidden = hide_no_display_apps && installed_browser.no_display;
+            if is_hidden {
+                debug!(
+                    "Skipping App: {:?} because of NoDisplay",
+                    installed_browser.bundle
+                );
+            }
+            !is_hidden
+        })
+        .chain(app_instances.iter())
+        .chain(custom_apps.iter())
+        .map(|installed_browser| {
+            debug!("App: {:?}", installed_browser.bundle);
+            debug!("  Path: {:?}", installed_browser.executable_path);
+            GenericApp::new(
+                installed_browser,
+                app_finder.get_app_repository(),
+                config.get_google_authuser(),
+            )
+        })
+        .collect();
+
+    if migrate_legacy_profile_ids(config, &apps) && !dry_run {
+        app_finder.save_config(config);
+    }
+
     let hidden_apps = config.get_hidden_apps();
     let hidden_profiles = config.get_hidden_profiles();
 
+    let inherited_env_remove = config.get_inherited_env_remove();
+
     let mut visible_browser_profiles: Vec<CommonBrowserProfile> = Vec::new();
     let mut hidden_browser_profiles: Vec<CommonBrowserProfile> = Vec::new();
-    //let support_dir = macos_get_application_support_dir();
-    debug!("Apps");
-    for installed_browser in installed_browsers {
-        debug!("App: {:?}", installed_browser.bundle);
-        debug!("  Path: {:?}", installed_browser.executable_path);
-        let app = GenericApp::new(&installed_browser, app_finder.get_app_repository());
-
+    for app in &apps {
         for p in app.get_profiles() {
+            let mut p = p.clone();
+            p.set_launch_options(config.get_launch_options(), &inherited_env_remove);
+
             let app_id = p.get_unique_app_id();
             if hidden_apps.contains(&app_id) {
                 debug!(
                     "Skipping Profile: {:?} because whole app is hidden",
                     p.get_profile_name()
                 );
-                hidden_browser_profiles.push(p.clone());
+                hidden_browser_profiles.push(p);
                 continue;
             }
 
@@ -581,16 +1019,17 @@ pub fn generate_all_browser_profiles(
                     "Skipping Profile: {:?} because the specific profile is hidden",
                     p.get_profile_name()
                 );
-                hidden_browser_profiles.push(p.clone());
+                hidden_browser_profiles.push(p);
                 continue;
             }
             debug!("Profile: {:?}", profile_unique_id.as_str());
-            visible_browser_profiles.push(p.clone());
+            visible_browser_profiles.push(p);
         }
     }
 
     let profile_order = config.get_profile_order();
-    sort_browser_profiles(&mut visible_browser_profiles, profile_order);
+    let sort_by_last_active = config.get_ui_config().sort_by_last_active;
+    sort_browser_profiles(&mut visible_browser_profiles, profile_order, sort_by_last_active);
 
     return VisibleAndHiddenProfiles {
         visible_browser_profiles: visible_browser_profiles,
@@ -598,9 +1037,179 @@ pub fn generate_all_browser_profiles(
     };
 }
 
+// app instances are cached apart from the installed browsers (see `get_app_instances_cached`),
+// so they are created again when the config changes
+pub(crate) fn create_app_instances(
+    installed_browsers: &[InstalledBrowser],
+    app_instance_configs: &[AppInstanceConfig],
+    app_repository: &SupportedAppRepository,
+) -> Vec<InstalledBrowser> {
+    let mut app_instances: Vec<InstalledBrowser> = Vec::new();
+    for app_instance_config in app_instance_configs {
+        let base_browser_maybe = installed_browsers.iter().find(|installed_browser| {
+            installed_browser.bundle == app_instance_config.app
+                || installed_browser.executable_path == app_instance_config.app
+        });
+        let base_browser = match base_browser_maybe {
+            Some(base_browser) => base_browser,
+            None => {
+                warn!(
+                    "Skipping app instance {:?}, app {:?} is not installed",
+                    app_instance_config.name, app_instance_config.app
+                );
+                continue;
+            }
+        };
+
+        let supported_app = app_repository.get_or_generate(
+            base_browser.bundle.as_str(),
+            &base_browser.restricted_domains,
+            base_browser.guessed_app_config.as_ref(),
+        );
+        if !supported_app.supports_custom_user_dir() {
+            warn!(
+                "Skipping app instance {:?}, {} doesn't support a custom user dir",
+                app_instance_config.name, base_browser.display_name
+            );
+            continue;
+        }
+
+        let user_dir = app_instance_config.get_user_dir();
+        let profiles = supported_app.find_app_instance_profiles(
+            Path::new(base_browser.executable_path.as_str()),
+            user_dir.as_path(),
+            app_instance_config.name.as_str(),
+        );
+        let user_dir = user_dir.to_str().unwrap().to_string();
+        info!(
+            "App instance {:?} of {} with user dir {}",
+            app_instance_config.name, base_browser.display_name, user_dir
+        );
+
+        app_instances.push(InstalledBrowser {
+            command: base_browser.command.clone(),
+            executable_path: base_browser.executable_path.clone(),
+            display_name: format!("{} ({})", base_browser.display_name, app_instance_config.name),
+            bundle: base_browser.bundle.clone(),
+            user_dir: user_dir.clone(),
+            icon_path: base_browser.icon_path.clone(),
+            profiles: profiles,
+            restricted_domains: base_browser.restricted_domains.clone(),
+            custom_user_dir: Some(user_dir),
+            custom_app: None,
+            desktop_action_launch_modes: base_browser.desktop_action_launch_modes.clone(),
+            no_display: false,
+            guessed_app_config: base_browser.guessed_app_config.clone(),
+        });
+    }
+    return app_instances;
+}
+
+// mail accounts are from the config, so they are added to the cached installed browsers
+// each time, instead of being found with the other profiles
+fn add_mail_account_profiles(
+    installed_browsers: &mut [InstalledBrowser],
+    mail_account_configs: &[MailAccountConfig],
+    app_repository: &SupportedAppRepository,
+) {
+    for installed_browser in installed_browsers.iter_mut() {
+        let accounts: Vec<String> = mail_account_configs
+            .iter()
+            .filter(|mail_account_config| {
+                installed_browser.bundle == mail_account_config.app
+                    || installed_browser.executable_path == mail_account_config.app
+            })
+            .map(|mail_account_config| mail_account_config.account.clone())
+            .collect();
+        if accounts.is_empty() {
+            continue;
+        }
+
+        let supported_app = app_repository.get_or_generate(
+            installed_browser.bundle.as_str(),
+            &installed_browser.restricted_domains,
+            installed_browser.guessed_app_config.as_ref(),
+        );
+        let profiles = match supported_app.find_mail_account_profiles(&accounts) {
+            Some(profiles) => profiles,
+            None => {
+                warn!(
+                    "Skipping mail accounts of {}, it doesn't scope its links to an account",
+                    installed_browser.display_name
+                );
+                continue;
+            }
+        };
+        info!(
+            "Mail accounts of {}: {:?}",
+            installed_browser.display_name, accounts
+        );
+        installed_browser.profiles = profiles;
+    }
+}
+
+// custom apps are commands from the config, so they have a single placeholder profile
+fn create_custom_apps(custom_app_configs: &[CustomAppConfig]) -> Vec<InstalledBrowser> {
+    let mut custom_apps: Vec<InstalledBrowser> = Vec::new();
+    for custom_app_config in custom_app_configs {
+        if !custom_app_config.is_valid() {
+            warn!(
+                "Skipping custom app {:?}, it needs a name and a command",
+                custom_app_config.name
+            );
+            continue;
+        }
+
+        let unique_app_id = custom_app_config.get_unique_app_id();
+        let app_id_seen = custom_apps
+            .iter()
+            .any(|custom_app| custom_app.executable_path == unique_app_id);
+        if app_id_seen {
+            warn!(
+                "Skipping custom app {:?}, id {} is already used",
+                custom_app_config.name, unique_app_id
+            );
+            continue;
+        }
+
+        custom_apps.push(InstalledBrowser {
+            command: custom_app_config.command.clone(),
+            executable_path: unique_app_id.clone(),
+            display_name: custom_app_config.name.clone(),
+            bundle: unique_app_id,
+            user_dir: "".to_string(),
+            icon_path: custom_app_config.get_icon_path(),
+            profiles: InstalledAppProfiles::new_placeholder(),
+            restricted_domains: custom_app_config.restricted_url_patterns.clone(),
+            custom_user_dir: None,
+            custom_app: Some(custom_app_config.clone()),
+            desktop_action_launch_modes: vec![],
+            no_display: false,
+            guessed_app_config: None,
+        });
+    }
+    return custom_apps;
+}
+
+// returns true if config was changed
+fn migrate_legacy_profile_ids(config: &mut Config, apps: &Vec<GenericApp>) -> bool {
+    let mut migrated = false;
+    for p in apps.iter().flat_map(|app| app.get_profiles()) {
+        if let Some(legacy_unique_id) = p.get_legacy_unique_id() {
+            let unique_id = p.get_unique_id();
+            if config.migrate_profile_id(legacy_unique_id.as_str(), unique_id.as_str()) {
+                info!("Migrated profile id {} to {}", legacy_unique_id, unique_id);
+                migrated = true;
+            }
+        }
+    }
+    return migrated;
+}
+
 fn sort_browser_profiles(
     visible_browser_profiles: &mut Vec<CommonBrowserProfile>,
     profile_order: &Vec<String>,
+    sort_by_last_active: bool,
 ) {
     let unordered_index = profile_order.len();
 
@@ -611,6 +1220,12 @@ fn sort_browser_profiles(
         return order_maybe.unwrap_or(unordered_index);
     });
 
+    if sort_by_last_active {
+        // most recently used first; profiles without last active time (e.g non-Chromium apps)
+        // keep the explicit order after them
+        visible_browser_profiles.sort_by_key(|p| Reverse(p.get_profile_last_active()));
+    }
+
     // always show special apps first
     visible_browser_profiles.sort_by_key(|b| !b.has_priority_ordering());
 }
@@ -662,15 +1277,18 @@ pub fn handle_messages_to_main(
     visible_and_hidden_profiles: &mut VisibleAndHiddenProfiles,
     app_finder: &OSAppFinder,
 ) {
+    // updated when saved in Settings
+    let mut behavioral_config = app_finder.load_config().get_behavior().clone();
+
     for message in main_receiver.iter() {
         match message {
             MessageToMain::Refresh => {
                 info!("refresh called");
 
-                let config = app_finder.load_config();
+                let mut config = app_finder.load_config();
 
                 let visible_and_hidden_profiles =
-                    generate_all_browser_profiles(&config, &app_finder, true);
+                    generate_all_browser_profiles(&mut config, &app_finder, true, false);
 
                 let ui_browsers =
                     UI::real_to_ui_browsers(&visible_and_hidden_profiles.visible_browser_profiles);
@@ -678,19 +1296,21 @@ pub fn handle_messages_to_main(
                     .submit_command(ui::NEW_BROWSERS_RECEIVED, ui_browsers, Target::Global)
                     .ok();
             }
-            MessageToMain::OpenLink(profile_index, incognito_mode, url) => {
+            MessageToMain::OpenLink(profile_index, launch_mode_maybe, url) => {
                 let option = &visible_and_hidden_profiles
                     .visible_browser_profiles
                     .get(profile_index);
                 let profile = option.unwrap();
-                profile.open_link(url.as_str(), incognito_mode);
-                ui_event_sink
-                    .submit_command(
-                        ui::OPEN_LINK_IN_BROWSER_COMPLETED,
-                        "meh2".to_string(),
-                        Target::Global,
-                    )
-                    .ok();
+                let result = open_link_with_fallback(
+                    profile,
+                    url.as_str(),
+                    launch_mode_maybe,
+                    None,
+                    opening_rules_and_default_profile,
+                    visible_and_hidden_profiles,
+                    &behavioral_config,
+                );
+                submit_open_link_result(&ui_event_sink, result);
             }
             MessageToMain::UrlOpenRequest(from_bundle_id, url) => {
                 let url_open_info = UrlOpenInfo {
@@ -740,26 +1360,34 @@ pub fn handle_messages_to_main(
 
                 if let Some(opening_profile_id) = opening_profile_id_maybe {
                     let profile_and_options = opening_profile_id.clone();
-                    let profile_id = profile_and_options.profile;
-                    let incognito = profile_and_options.incognito;
+                    let launch_mode_maybe = profile_and_options.get_launch_mode();
 
-                    let profile_maybe =
-                        visible_and_hidden_profiles.get_browser_profile_by_id(profile_id.as_str());
+                    let profile_maybe = visible_and_hidden_profiles
+                        .get_browser_profile_for_opener(&profile_and_options);
 
                     if let Some(profile) = profile_maybe {
-                        profile.open_link(new_modified_url.as_str(), incognito);
-                        ui_event_sink
-                            .submit_command(
-                                ui::OPEN_LINK_IN_BROWSER_COMPLETED,
-                                "meh2".to_string(),
-                                Target::Global,
-                            )
-                            .ok();
+                        let result = open_link_with_fallback(
+                            profile,
+                            new_modified_url.as_str(),
+                            launch_mode_maybe,
+                            profile_and_options.authuser.as_deref(),
+                            opening_rules_and_default_profile,
+                            visible_and_hidden_profiles,
+                            &behavioral_config,
+                        );
+                        submit_open_link_result(&ui_event_sink, result);
                     }
                 }
             }
             MessageToMain::SetBrowsersAsDefaultBrowser => {
                 utils::set_as_default_web_browser();
+                utils::sync_url_scheme_handlers(&app_finder.load_config().get_schemes());
+            }
+            MessageToMain::RestorePreviousDefaultBrowser => {
+                let restored = utils::restore_previous_default_web_browser();
+                ui_event_sink
+                    .submit_command(ui::PREVIOUS_DEFAULT_BROWSER_RESTORED, restored, Target::Global)
+                    .ok();
             }
             MessageToMain::HideAllProfiles(app_id) => {
                 info!("Hiding all profiles of app {}", app_id);
@@ -866,6 +1494,7 @@ pub fn handle_messages_to_main(
                     sort_browser_profiles(
                         &mut visible_and_hidden_profiles.visible_browser_profiles,
                         profile_order,
+                        config.get_ui_config().sort_by_last_active,
                     );
 
                     let ui_browsers = UI::real_to_ui_browsers(
@@ -919,7 +1548,10 @@ pub fn handle_messages_to_main(
                 info!("Saving default opener");
                 let new_default_profile = default_opener.map(|p| ProfileAndOptions {
                     profile: p.profile,
-                    incognito: p.incognito,
+                    account: p.account,
+                    authuser: p.authuser,
+                    incognito: p.launch_mode == Some(LaunchMode::Incognito),
+                    launch_mode: p.launch_mode,
                 });
 
                 let mut config = app_finder.load_config();
@@ -937,6 +1569,7 @@ pub fn handle_messages_to_main(
                     show_hotkeys: settings.show_hotkeys,
                     quit_on_lost_focus: settings.quit_on_lost_focus,
                     theme: settings.theme,
+                    sort_by_last_active: settings.sort_by_last_active,
                 };
 
                 let mut config = app_finder.load_config();
@@ -945,13 +1578,46 @@ pub fn handle_messages_to_main(
             }
             MessageToMain::SaveConfigUIBehavioralSettings(settings) => {
                 info!("Saving Behavioral settings");
-                let behavioral_config = BehavioralConfig {
-                    unwrap_urls: settings.unwrap_urls,
-                };
+                behavioral_config = settings.to_behavioral_config();
+
+                let mut config = app_finder.load_config();
+                config.set_behavior(behavioral_config.clone());
+                app_finder.save_config(&config);
+            }
+            MessageToMain::SaveConfigLaunchOptions(ui_launch_options) => {
+                info!("Saving launch options");
+                let launch_options: BTreeMap<String, LaunchOptionsConfig> = ui_launch_options
+                    .iter()
+                    .filter(|ui_options| !ui_options.target_id.is_empty())
+                    .map(|ui_options| {
+                        let options = LaunchOptionsConfig::from_lines(
+                            ui_options.args.as_str(),
+                            ui_options.env.as_str(),
+                            ui_options.working_dir.as_str(),
+                            ui_options.wrappers.as_str(),
+                        );
+                        (ui_options.target_id.to_string(), options)
+                    })
+                    .collect();
 
                 let mut config = app_finder.load_config();
-                config.set_behavior(behavioral_config);
+                config.set_launch_options(launch_options);
                 app_finder.save_config(&config);
+
+                // apply immediately, so that links opened from the same Browsers instance
+                // are already launched with the new options
+                let profiles = visible_and_hidden_profiles
+                    .visible_browser_profiles
+                    .iter_mut()
+                    .chain(
+                        visible_and_hidden_profiles
+                            .hidden_browser_profiles
+                            .iter_mut(),
+                    );
+                let inherited_env_remove = config.get_inherited_env_remove();
+                for profile in profiles {
+                    profile.set_launch_options(config.get_launch_options(), &inherited_env_remove);
+                }
             }
         }
     }
@@ -986,28 +1652,71 @@ pub fn prepare_ui(
     );
 }
 
+// returns false if there is no matching rule, or an error if the link couldn't be opened
+// (in both cases the picker is shown)
 pub fn open_link_if_matching_rule(
     url_open_context: &UrlOpenContext,
     opening_rules_and_default_profile: &OpeningRulesAndDefaultProfile,
     visible_and_hidden_profiles: &VisibleAndHiddenProfiles,
-) -> bool {
+    behavioral_config: &BehavioralConfig,
+) -> Result<bool, String> {
     let opening_profile_id_maybe =
         opening_rules_and_default_profile.get_rule_for_source_app_and_url(url_open_context);
 
     if let Some(opening_profile_id) = opening_profile_id_maybe {
         let profile_and_options = opening_profile_id.clone();
-        let profile_id = profile_and_options.profile;
-        let incognito = profile_and_options.incognito;
+        let launch_mode_maybe = profile_and_options.get_launch_mode();
 
         let profile_maybe =
-            visible_and_hidden_profiles.get_browser_profile_by_id(profile_id.as_str());
+            visible_and_hidden_profiles.get_browser_profile_for_opener(&profile_and_options);
         if let Some(profile) = profile_maybe {
-            profile.open_link(url_open_context.cleaned_url.as_str(), incognito);
-            return true;
+            let result = open_link_with_fallback(
+                profile,
+                url_open_context.cleaned_url.as_str(),
+                launch_mode_maybe,
+                profile_and_options.authuser.as_deref(),
+                opening_rules_and_default_profile,
+                visible_and_hidden_profiles,
+                behavioral_config,
+            );
+            return result.map(|()| true);
         }
     }
 
-    return false;
+    return Ok(false);
+}
+
+// `--dry-run` prints the commands instead of launching them; the one of the matching rule,
+// or otherwise the ones of all visible profiles
+pub fn print_launch_commands(
+    url_open_context: &UrlOpenContext,
+    opening_rules_and_default_profile: &OpeningRulesAndDefaultProfile,
+    visible_and_hidden_profiles: &VisibleAndHiddenProfiles,
+) {
+    let url = url_open_context.cleaned_url.as_str();
+
+    let opening_profile_id_maybe =
+        opening_rules_and_default_profile.get_rule_for_source_app_and_url(url_open_context);
+    if let Some(profile_and_options) = opening_profile_id_maybe {
+        let profile_maybe =
+            visible_and_hidden_profiles.get_browser_profile_for_opener(&profile_and_options);
+        if let Some(profile) = profile_maybe {
+            let cmd = profile.create_link_command(
+                url,
+                profile_and_options.get_launch_mode(),
+                profile_and_options.authuser.as_deref(),
+            );
+            println!("{} (matching rule)", profile.get_unique_id());
+            println!("  {}", launch_options::to_shell_command_line(&cmd));
+            return;
+        }
+    }
+
+    for profile in &visible_and_hidden_profiles.visible_browser_profiles {
+        let cmd = profile.create_link_command(url, None, None);
+        println!("{}", profile.get_unique_id());
+        println!("  {}", launch_options::to_shell_command_line(&cmd));
+    }
 }
 
 pub struct UrlOpenContext {
@@ -1018,7 +1727,10 @@ pub struct UrlOpenContext {
 fn map_as_profile_and_options(opener: &Option<UIProfileAndIncognito>) -> Option<ProfileAndOptions> {
     return opener.as_ref().map(|p| ProfileAndOptions {
         profile: p.profile.clone(),
-        incognito: p.incognito,
+        account: p.account.clone(),
+        authuser: p.authuser.clone(),
+        incognito: p.launch_mode == Some(LaunchMode::Incognito),
+        launch_mode: p.launch_mode,
     });
 }
 
@@ -1119,12 +1831,13 @@ pub enum MoveTo {
 #[derive(Debug)]
 pub enum MessageToMain {
     Refresh,
-    OpenLink(usize, bool, String),
+    OpenLink(usize, Option<LaunchMode>, String),
     // UrlOpenRequest is almost like LinkOpenedFromBundle, but triggers gui, not from gui
     UrlOpenRequest(String, String),
     UrlPassedToMain(String, String, BehavioralConfig),
     LinkOpenedFromBundle(String, String),
     SetBrowsersAsDefaultBrowser,
+    RestorePreviousDefaultBrowser,
     HideAppProfile(String),
     HideAllProfiles(String),
     RestoreAppProfile(String),
@@ -1133,4 +1846,5 @@ pub enum MessageToMain {
     SaveConfigDefaultOpener(Option<UIProfileAndIncognito>),
     SaveConfigUISettings(UIVisualSettings),
     SaveConfigUIBehavioralSettings(UIBehavioralSettings),
+    SaveConfigLaunchOptions(Vec<UILaunchOptions>),
 }
@@ -0,0 +1,260 @@
+use freedesktop_desktop_entry::DesktopEntry;
+use tracing::debug;
+
+use crate::desktop_exec_parser;
+use crate::utils::LaunchMode;
+
+// `[Desktop Action <id>]` group of a desktop entry, listed in its `Actions` key, e.g
+// [Desktop Action new-private-window]
+// Name=New Private Window
+// Exec=firefox --private-window %u
+#[derive(Clone, Debug)]
+pub struct DesktopAction {
+    pub id: String,
+    // untranslated, as it's only used to recognize the action
+    pub name: String,
+    pub exec: String,
+}
+
+pub fn read_desktop_actions(desktop_entry: &DesktopEntry) -> Vec<DesktopAction> {
+    let action_ids = desktop_entry.actions().unwrap_or_default();
+    return action_ids
+        .iter()
+        .filter(|action_id| !action_id.trim().is_empty())
+        .filter_map(|action_id| {
+            let exec = desktop_entry.action_exec(action_id)?;
+            let name = desktop_entry.action_entry(action_id, "Name").unwrap_or("");
+            Some(DesktopAction {
+                id: action_id.to_string(),
+                name: name.to_string(),
+                exec: exec.to_string(),
+            })
+        })
+        .collect();
+}
+
+// Launch modes of the app from its private/incognito, new window and new tab actions,
+// as the arguments the action adds to the app command (see `desktop_exec_parser::find_added_app_args`)
+pub fn find_launch_modes(
+    desktop_actions: &[DesktopAction],
+    command: &[String],
+) -> Vec<(LaunchMode, Vec<String>)> {
+    let mut launch_modes: Vec<(LaunchMode, Vec<String>)> = Vec::new();
+    for desktop_action in desktop_actions {
+        let launch_mode = match recognize_launch_mode(desktop_action) {
+            Some(launch_mode) => launch_mode,
+            None => continue,
+        };
+        if launch_modes
+            .iter()
+            .any(|(existing, _)| *existing == launch_mode)
+        {
+            continue;
+        }
+
+        let action_command = match desktop_exec_parser::parse_exec(desktop_action.exec.as_str()) {
+            Some(action_command) => action_command,
+            None => continue,
+        };
+        let args = match desktop_exec_parser::find_added_app_args(command, &action_command) {
+            // e.g "New Window" of Chromium-based apps is just the app command
+            Some(args) if !args.is_empty() => args,
+            _ => {
+                debug!(
+                    "Desktop Action {} can't be used as a launch mode: {}",
+                    desktop_action.id, desktop_action.exec
+                );
+                continue;
+            }
+        };
+        launch_modes.push((launch_mode, args));
+    }
+    return launch_modes;
+}
+
+// by id (e.g "new-private-window", "PrivateBrowsing", "incognito") or else by name
+// (e.g "Open a New Private Window"), as there are no standard action ids
+fn recognize_launch_mode(desktop_action: &DesktopAction) -> Option<LaunchMode> {
+    return launch_mode_from_words(desktop_action.id.as_str())
+        .or_else(|| launch_mode_from_words(desktop_action.name.as_str()));
+}
+
+fn launch_mode_from_words(value: &str) -> Option<LaunchMode> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+
+    return if normalized.contains("private") || normalized.contains("incognito") {
+        Some(LaunchMode::Incognito)
+    } else if normalized.contains("newwindow") {
+        Some(LaunchMode::NewWindow)
+    } else if normalized.contains("newtab") {
+        Some(LaunchMode::NewTab)
+    } else {
+        None
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn find_launch_modes_in(desktop_file_content: &str) -> Vec<(LaunchMode, Vec<String>)> {
+        let desktop_file_path = Path::new("/usr/share/applications/test.desktop");
+        let locales: &Vec<String> = &vec![];
+        let desktop_entry =
+            DesktopEntry::from_str(&desktop_file_path, desktop_file_content, Some(locales))
+                .unwrap();
+        let command = desktop_exec_parser::parse_exec(desktop_entry.exec().unwrap()).unwrap();
+        return find_launch_modes(&read_desktop_actions(&desktop_entry), &command);
+    }
+
+    fn to_strings(arguments: &[&str]) -> Vec<String> {
+        return arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn test_epiphany() {
+        let launch_modes = find_launch_modes_in(
+            r#"[Desktop Entry]
+Name=Web
+Exec=epiphany %U
+Icon=org.gnome.Epiphany
+Type=Application
+MimeType=text/html;x-scheme-handler/http;x-scheme-handler/https;
+Actions=new-window;incognito;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=epiphany --new-window
+
+[Desktop Action incognito]
+Name=New Incognito Window
+Exec=epiphany --incognito-mode
+"#,
+        );
+        assert_eq!(
+            launch_modes,
+            vec![
+                (LaunchMode::NewWindow, to_strings(&["--new-window"])),
+                (LaunchMode::Incognito, to_strings(&["--incognito-mode"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_falkon() {
+        let launch_modes = find_launch_modes_in(
+            r#"[Desktop Entry]
+Name=Falkon
+Exec=falkon %u
+Icon=falkon
+Type=Application
+Actions=NewTab;NewWindow;PrivateBrowsing;
+
+[Desktop Action NewTab]
+Name=Open new tab
+Exec=falkon -t
+
+[Desktop Action NewWindow]
+Name=Open new window
+Exec=falkon -w
+
+[Desktop Action PrivateBrowsing]
+Name=Start private browsing
+Exec=falkon -pb
+"#,
+        );
+        assert_eq!(
+            launch_modes,
+            vec![
+                (LaunchMode::NewTab, to_strings(&["-t"])),
+                (LaunchMode::NewWindow, to_strings(&["-w"])),
+                (LaunchMode::Incognito, to_strings(&["-pb"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_brave() {
+        let launch_modes = find_launch_modes_in(
+            r#"[Desktop Entry]
+Version=1.0
+Name=Brave Web Browser
+Exec=/usr/bin/brave-browser-stable %U
+Type=Application
+Icon=brave-browser
+Actions=new-window;new-private-window;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=/usr/bin/brave-browser-stable
+
+[Desktop Action new-private-window]
+Name=New Incognito Window
+Exec=/usr/bin/brave-browser-stable --incognito
+"#,
+        );
+        // new window action is the app command itself
+        assert_eq!(
+            launch_modes,
+            vec![(LaunchMode::Incognito, to_strings(&["--incognito"]))]
+        );
+    }
+
+    #[test]
+    fn test_librewolf_flatpak() {
+        let launch_modes = find_launch_modes_in(
+            r#"[Desktop Entry]
+Name=LibreWolf
+Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=librewolf --file-forwarding io.gitlab.librewolf-community @@u %u @@
+Icon=io.gitlab.librewolf-community
+Type=Application
+Actions=new-window;new-private-window;profile-manager-window;
+
+[Desktop Action new-window]
+Name=Open a New Window
+Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=librewolf io.gitlab.librewolf-community --new-window %u
+
+[Desktop Action new-private-window]
+Name=Open a New Private Window
+Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=librewolf io.gitlab.librewolf-community --private-window %u
+
+[Desktop Action profile-manager-window]
+Name=Open the Profile Manager
+Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=librewolf io.gitlab.librewolf-community --ProfileManager
+"#,
+        );
+        assert_eq!(
+            launch_modes,
+            vec![
+                (LaunchMode::NewWindow, to_strings(&["--new-window"])),
+                (LaunchMode::Incognito, to_strings(&["--private-window"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_action_of_another_app() {
+        let launch_modes = find_launch_modes_in(
+            r#"[Desktop Entry]
+Name=Browser
+Exec=browser %u
+Type=Application
+Actions=private;
+
+[Desktop Action private]
+Name=Private Window
+Exec=private-browser-launcher --url=%u
+"#,
+        );
+        assert!(launch_modes.is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use freedesktop_desktop_entry::DesktopEntry;
+use tracing::debug;
+
+// Desktop files of the application dirs, the first one for each desktop file id, see
+// https://specifications.freedesktop.org/desktop-entry-spec/latest/file-naming.html
+// Dirs are in precedence order (XDG_DATA_HOME first, then XDG_DATA_DIRS), so e.g
+// "~/.local/share/applications/firefox.desktop" overrides "/usr/share/applications/firefox.desktop",
+// even if it's a `Hidden` one which removes the app
+pub fn find_desktop_files(application_dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut seen_application_dirs: HashSet<&PathBuf> = HashSet::new();
+    let mut seen_desktop_ids: HashSet<String> = HashSet::new();
+    let mut desktop_files: Vec<(String, PathBuf)> = Vec::new();
+
+    for application_dir in application_dirs {
+        // e.g XDG_DATA_DIRS sometimes has every path twice
+        if !seen_application_dirs.insert(application_dir) {
+            continue;
+        }
+
+        let mut visited_dirs: HashSet<PathBuf> = HashSet::new();
+        for desktop_file_path in find_desktop_files_in_dir(application_dir, &mut visited_dirs) {
+            let desktop_id = to_desktop_id(application_dir, desktop_file_path.as_path());
+            if seen_desktop_ids.insert(desktop_id.clone()) {
+                desktop_files.push((desktop_id, desktop_file_path));
+            }
+        }
+    }
+
+    return desktop_files;
+}
+
+// Desktop file of a desktop id in the application dirs (the first one, as in
+// `find_desktop_files`), without reading all desktop files of the dirs
+pub fn find_desktop_file(application_dirs: &[PathBuf], desktop_id: &str) -> Option<PathBuf> {
+    if desktop_id.contains('/') {
+        return None;
+    }
+    return application_dirs
+        .iter()
+        .find_map(|application_dir| find_desktop_file_in_dir(application_dir, desktop_id));
+}
+
+// any "-" of the desktop id can be a subdir, e.g "kde4-konqueror.desktop" is
+// "kde4/konqueror.desktop" or "kde4-konqueror.desktop", see `to_desktop_id`
+fn find_desktop_file_in_dir(dir: &Path, desktop_id: &str) -> Option<PathBuf> {
+    let desktop_file_path = dir.join(desktop_id);
+    if desktop_file_path.is_file() {
+        return Some(desktop_file_path);
+    }
+
+    for (index, _) in desktop_id.match_indices('-') {
+        let subdir_name = &desktop_id[..index];
+        if subdir_name.is_empty() || subdir_name == "." || subdir_name == ".." {
+            continue;
+        }
+        let subdir = dir.join(subdir_name);
+        if !subdir.is_dir() {
+            continue;
+        }
+        let desktop_file_path_maybe = find_desktop_file_in_dir(&subdir, &desktop_id[index + 1..]);
+        if desktop_file_path_maybe.is_some() {
+            return desktop_file_path_maybe;
+        }
+    }
+    return None;
+}
+
+// recursively, sorted to have the same order on every run;
+// a symlinked dir can point back to a parent dir, so each dir is only visited once
+fn find_desktop_files_in_dir(dir: &Path, visited_dirs: &mut HashSet<PathBuf>) -> Vec<PathBuf> {
+    let canonical_dir = match fs::canonicalize(dir) {
+        Ok(canonical_dir) => canonical_dir,
+        Err(_) => return vec![],
+    };
+    if !visited_dirs.insert(canonical_dir) {
+        return vec![];
+    }
+
+    let mut entry_paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => return vec![],
+    };
+    entry_paths.sort();
+
+    let mut desktop_file_paths: Vec<PathBuf> = Vec::new();
+    for entry_path in entry_paths {
+        if entry_path.is_dir() {
+            desktop_file_paths
+                .extend(find_desktop_files_in_dir(entry_path.as_path(), visited_dirs));
+        } else if entry_path
+            .extension()
+            .map(|ext| ext == "desktop")
+            .unwrap_or(false)
+        {
+            desktop_file_paths.push(entry_path);
+        }
+    }
+    return desktop_file_paths;
+}
+
+// path relative to the application dir with "/" replaced by "-",
+// e.g "kde4/konqueror.desktop" is "kde4-konqueror.desktop"
+fn to_desktop_id(application_dir: &Path, desktop_file_path: &Path) -> String {
+    let relative_path = desktop_file_path
+        .strip_prefix(application_dir)
+        .unwrap_or(desktop_file_path);
+    let components: Vec<String> = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    return components.join("-");
+}
+
+// Whether a desktop entry is an installed app meant for the current desktop;
+// `NoDisplay` is not checked, as such apps can still be used to open links
+pub struct DesktopEntryFilter {
+    // from XDG_CURRENT_DESKTOP, e.g ["ubuntu", "GNOME"]
+    current_desktops: Vec<String>,
+    // from PATH, to find `TryExec` programs
+    path_dirs: Vec<PathBuf>,
+}
+
+impl DesktopEntryFilter {
+    pub fn new(current_desktops: Vec<String>, path_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            current_desktops: current_desktops,
+            path_dirs: path_dirs,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let current_desktops: Vec<String> = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .split(':')
+            .filter(|desktop| !desktop.is_empty())
+            .map(|desktop| desktop.to_string())
+            .collect();
+        let path_dirs: Vec<PathBuf> = env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default();
+        return Self::new(current_desktops, path_dirs);
+    }
+
+    pub fn is_shown(&self, desktop_entry: &DesktopEntry) -> bool {
+        let app_id = desktop_entry.id();
+        if is_true(desktop_entry.desktop_entry("Hidden")) {
+            debug!("Skipping {}, it is Hidden (deleted)", app_id);
+            return false;
+        }
+
+        if let Some(try_exec) = desktop_entry.desktop_entry("TryExec") {
+            if !self.is_executable_found(try_exec.trim()) {
+                debug!("Skipping {}, its TryExec {} is not installed", app_id, try_exec);
+                return false;
+            }
+        }
+
+        if let Some(only_show_in) = desktop_entry.desktop_entry("OnlyShowIn") {
+            if !self.is_current_desktop_in(only_show_in) {
+                debug!("Skipping {}, it is only shown in {}", app_id, only_show_in);
+                return false;
+            }
+        }
+
+        if let Some(not_show_in) = desktop_entry.desktop_entry("NotShowIn") {
+            if self.is_current_desktop_in(not_show_in) {
+                debug!("Skipping {}, it is not shown in {}", app_id, not_show_in);
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    // `desktops` is a list value, e.g "GNOME;Unity;"
+    fn is_current_desktop_in(&self, desktops: &str) -> bool {
+        return desktops.split(';').any(|desktop| {
+            self.current_desktops
+                .iter()
+                .any(|current| current == desktop)
+        });
+    }
+
+    // an absolute path, or a program name to look for in PATH
+    fn is_executable_found(&self, program: &str) -> bool {
+        if program.is_empty() {
+            return true;
+        }
+
+        let program_path = Path::new(program);
+        if program_path.is_absolute() {
+            return is_executable_file(program_path);
+        }
+        return self
+            .path_dirs
+            .iter()
+            .any(|path_dir| is_executable_file(path_dir.join(program).as_path()));
+    }
+}
+
+pub fn is_no_display(desktop_entry: &DesktopEntry) -> bool {
+    return is_true(desktop_entry.desktop_entry("NoDisplay"));
+}
+
+fn is_true(value_maybe: Option<&str>) -> bool {
+    return value_maybe
+        .map(|value| value.trim() == "true")
+        .unwrap_or(false);
+}
+
+fn is_executable_file(path: &Path) -> bool {
+    return fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    fn write_desktop_file(application_dir: &Path, relative_path: &str, extra_lines: &str) {
+        let desktop_file_path = application_dir.join(relative_path);
+        fs::create_dir_all(desktop_file_path.parent().unwrap()).unwrap();
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={} %u\nMimeType=x-scheme-handler/https;\n{}",
+            relative_path, relative_path, extra_lines
+        );
+        fs::write(desktop_file_path, content).unwrap();
+    }
+
+    fn read_desktop_entry(desktop_file_path: &Path) -> DesktopEntry {
+        let locales: &Vec<String> = &vec![];
+        let content = fs::read_to_string(desktop_file_path).unwrap();
+        return DesktopEntry::from_str(&desktop_file_path, &content, Some(locales)).unwrap();
+    }
+
+    #[test]
+    fn test_desktop_id_precedence() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let test_dir = temp_dir.path().to_path_buf();
+        // XDG_DATA_HOME, then XDG_DATA_DIRS
+        let data_home_applications = test_dir.join("home/.local/share/applications");
+        let usr_local_applications = test_dir.join("usr/local/share/applications");
+        let usr_applications = test_dir.join("usr/share/applications");
+
+        write_desktop_file(&data_home_applications, "firefox.desktop", "Comment=Nightly\n");
+        write_desktop_file(&usr_applications, "firefox.desktop", "");
+        write_desktop_file(&usr_local_applications, "chromium.desktop", "");
+        write_desktop_file(&usr_applications, "chromium.desktop", "");
+        // same file name, but another desktop id
+        write_desktop_file(&usr_applications, "kde4/firefox.desktop", "");
+
+        let application_dirs = vec![
+            data_home_applications.clone(),
+            usr_local_applications.clone(),
+            usr_applications.clone(),
+            usr_applications.clone(),
+        ];
+        let desktop_files = find_desktop_files(&application_dirs);
+        assert_eq!(
+            desktop_files,
+            vec![
+                (
+                    "firefox.desktop".to_string(),
+                    data_home_applications.join("firefox.desktop")
+                ),
+                (
+                    "chromium.desktop".to_string(),
+                    usr_local_applications.join("chromium.desktop")
+                ),
+                (
+                    "kde4-firefox.desktop".to_string(),
+                    usr_applications.join("kde4/firefox.desktop")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_desktop_file() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let test_dir = temp_dir.path().to_path_buf();
+        let data_home_applications = test_dir.join("home/.local/share/applications");
+        let usr_applications = test_dir.join("usr/share/applications");
+        write_desktop_file(&data_home_applications, "firefox.desktop", "");
+        write_desktop_file(&usr_applications, "firefox.desktop", "");
+        write_desktop_file(&usr_applications, "kde4/konqueror.desktop", "");
+        write_desktop_file(&usr_applications, "org.gnome-Epiphany.desktop", "");
+
+        let application_dirs = vec![data_home_applications.clone(), usr_applications.clone()];
+        assert_eq!(
+            find_desktop_file(&application_dirs, "firefox.desktop"),
+            Some(data_home_applications.join("firefox.desktop"))
+        );
+        assert_eq!(
+            find_desktop_file(&application_dirs, "kde4-konqueror.desktop"),
+            Some(usr_applications.join("kde4/konqueror.desktop"))
+        );
+        assert_eq!(
+            find_desktop_file(&application_dirs, "org.gnome-Epiphany.desktop"),
+            Some(usr_applications.join("org.gnome-Epiphany.desktop"))
+        );
+        assert_eq!(find_desktop_file(&application_dirs, "konqueror.desktop"), None);
+        assert_eq!(
+            find_desktop_file(&application_dirs, "kde4/konqueror.desktop"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_symlinked_dir_loop() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let usr_applications = temp_dir.path().join("usr/share/applications");
+        write_desktop_file(&usr_applications, "kde4/firefox.desktop", "");
+        // points back to the application dir
+        std::os::unix::fs::symlink(&usr_applications, usr_applications.join("kde4/loop")).unwrap();
+
+        let desktop_files = find_desktop_files(&vec![usr_applications.clone()]);
+        assert_eq!(
+            desktop_files,
+            vec![(
+                "kde4-firefox.desktop".to_string(),
+                usr_applications.join("kde4/firefox.desktop")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_hidden_entry_overrides_installed_one() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let test_dir = temp_dir.path().to_path_buf();
+        let data_home_applications = test_dir.join("home/.local/share/applications");
+        let usr_applications = test_dir.join("usr/share/applications");
+        write_desktop_file(&data_home_applications, "brave-browser.desktop", "Hidden=true\n");
+        write_desktop_file(&usr_applications, "brave-browser.desktop", "");
+
+        let desktop_files = find_desktop_files(&vec![data_home_applications, usr_applications]);
+        assert_eq!(desktop_files.len(), 1);
+
+        let filter = DesktopEntryFilter::new(vec![], vec![]);
+        let desktop_entry = read_desktop_entry(desktop_files[0].1.as_path());
+        assert!(!filter.is_shown(&desktop_entry));
+    }
+
+    #[test]
+    fn test_try_exec() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let test_dir = temp_dir.path().to_path_buf();
+        let bin_dir = test_dir.join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let installed_program = bin_dir.join("installed-browser");
+        fs::write(&installed_program, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&installed_program, fs::Permissions::from_mode(0o755)).unwrap();
+        let not_executable_program = bin_dir.join("not-executable-browser");
+        fs::write(&not_executable_program, "").unwrap();
+        fs::set_permissions(&not_executable_program, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let applications = test_dir.join("share/applications");
+        write_desktop_file(&applications, "installed.desktop", "TryExec=installed-browser\n");
+        let absolute_try_exec = format!("TryExec={}\n", installed_program.display());
+        write_desktop_file(&applications, "installed-absolute.desktop", &absolute_try_exec);
+        write_desktop_file(&applications, "removed.desktop", "TryExec=removed-browser\n");
+        write_desktop_file(
+            &applications,
+            "not-executable.desktop",
+            "TryExec=not-executable-browser\n",
+        );
+
+        let filter = DesktopEntryFilter::new(vec![], vec![test_dir.join("empty"), bin_dir]);
+        let shown_ids: Vec<String> = find_desktop_files(&vec![applications])
+            .into_iter()
+            .filter(|(_, path)| filter.is_shown(&read_desktop_entry(path.as_path())))
+            .map(|(desktop_id, _)| desktop_id)
+            .collect();
+        assert_eq!(
+            shown_ids,
+            vec!["installed-absolute.desktop", "installed.desktop"]
+        );
+    }
+
+    #[test]
+    fn test_only_show_in_and_not_show_in() {
+        let temp_dir = test_utils::create_temp_dir("desktop-entry-finder");
+        let test_dir = temp_dir.path().to_path_buf();
+        let applications = test_dir.join("share/applications");
+        write_desktop_file(&applications, "everywhere.desktop", "");
+        write_desktop_file(&applications, "gnome-only.desktop", "OnlyShowIn=GNOME;\n");
+        write_desktop_file(&applications, "kde-only.desktop", "OnlyShowIn=KDE;LXQt;\n");
+        write_desktop_file(&applications, "not-in-gnome.desktop", "NotShowIn=GNOME;\n");
+        write_desktop_file(&applications, "not-in-kde.desktop", "NotShowIn=KDE;\n");
+        write_desktop_file(&applications, "no-display.desktop", "NoDisplay=true\n");
+        let desktop_files = find_desktop_files(&vec![applications]);
+
+        let find_shown_ids = |filter: &DesktopEntryFilter| -> Vec<String> {
+            return desktop_files
+                .iter()
+                .filter(|(_, path)| filter.is_shown(&read_desktop_entry(path.as_path())))
+                .map(|(desktop_id, _)| desktop_id.clone())
+                .collect();
+        };
+
+        // XDG_CURRENT_DESKTOP=ubuntu:GNOME
+        let gnome_filter =
+            DesktopEntryFilter::new(vec!["ubuntu".to_string(), "GNOME".to_string()], vec![]);
+        assert_eq!(
+            find_shown_ids(&gnome_filter),
+            vec![
+                "everywhere.desktop",
+                "gnome-only.desktop",
+                "no-display.desktop",
+                "not-in-kde.desktop",
+            ]
+        );
+
+        // desktop-specific entries are not shown if the desktop is unknown
+        let unknown_desktop_filter = DesktopEntryFilter::new(vec![], vec![]);
+        assert_eq!(
+            find_shown_ids(&unknown_desktop_filter),
+            vec![
+                "everywhere.desktop",
+                "no-display.desktop",
+                "not-in-gnome.desktop",
+                "not-in-kde.desktop",
+            ]
+        );
+
+        let no_display_entry = read_desktop_entry(
+            desktop_files
+                .iter()
+                .find(|(desktop_id, _)| desktop_id == "no-display.desktop")
+                .map(|(_, path)| path.as_path())
+                .unwrap(),
+        );
+        assert!(is_no_display(&no_display_entry));
+    }
+}
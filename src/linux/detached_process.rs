@@ -0,0 +1,31 @@
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+// Launched apps shouldn't depend on Browsers, which usually exits right after launching them,
+// so they run in their own session (not killed with the terminal or process group of Browsers)
+// and don't write to the stdio of Browsers
+pub fn detach(cmd: &mut Command) {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // setsid() is async-signal-safe, so it can be called between fork and exec
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            return Ok(());
+        });
+    }
+}
+
+// Browsers might keep running (e.g Settings are open, or the link was opened from the picker
+// which stays open on failure), so the launched app is waited for to not leave a zombie
+pub fn reap_in_background(mut child: Child) {
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+}
@@ -1,17 +1,24 @@
-use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
 use std::slice::Split;
 
 use druid::image;
 use druid::image::ImageFormat;
 use tracing::{info, warn};
 
-use freedesktop_desktop_entry::{default_paths, get_languages_from_env, DesktopEntry, Iter};
+use freedesktop_desktop_entry::{default_paths, get_languages_from_env, DesktopEntry};
 use freedesktop_icons;
 
-use crate::{InstalledBrowser, SupportedAppRepository};
+use crate::desktop_exec_parser::DesktopEntryFields;
+use crate::linux::desktop_actions;
+use crate::linux::desktop_actions::DesktopAction;
+use crate::linux::desktop_entry_finder;
+use crate::linux::desktop_entry_finder::DesktopEntryFilter;
+use crate::linux::mimeapps_list::{MimeAppsDirs, MimeAppsList};
+use crate::linux::snap_detector;
+use crate::{
+    desktop_exec_parser, flatpak_command_parser, paths, InstalledBrowser, SupportedAppRepository,
+};
 
 const XDG_NAME: &'static str = "software.Browsers";
 
@@ -20,9 +27,15 @@ struct DesktopEntryHolder {
     app_id: String,
     display_name: String,
     icon: Option<String>,
+    desktop_file_path: PathBuf,
+    // `X-SnapInstanceName`, which snapd adds to the desktop entries of snaps
+    snap_instance_name: Option<String>,
 
-    // uses %u or %U, see https://specifications.freedesktop.org/desktop-entry-spec/latest/ar01s07.html
+    // uses %u or %U, see `desktop_exec_parser`
     exec: String,
+    // e.g "new-private-window", see `desktop_actions`
+    actions: Vec<DesktopAction>,
+    no_display: bool,
 }
 
 pub struct OsHelper {
@@ -84,36 +97,23 @@ impl OsHelper {
     }
 
     fn freedesktop_find_all_desktop_entries(content_type: &str) -> Vec<DesktopEntryHolder> {
-        let unique_search_paths = default_paths()
-            .into_iter()
-            // remove duplicate entries (e.g XDG_DATA_DIRS sometimes has every path twice)
-            .collect::<HashSet<PathBuf>>()
-            .into_iter();
-
-        // collect all .desktop file paths and map them by file name to remove duplicate files,
-        // even if they exist in different directories
-        let mut desktop_file_paths_by_filename: BTreeMap<_, PathBuf> =
-            Iter::new(unique_search_paths)
-                .filter_map(|file_path| {
-                    file_path
-                        .file_name()
-                        .map(|a| a.to_owned())
-                        .map(|file_name| (file_name, file_path))
-                })
-                .collect();
-
-        let cleaned_desktop_file_paths: Vec<PathBuf> =
-            desktop_file_paths_by_filename.into_values().collect();
+        // application dirs of XDG_DATA_HOME and XDG_DATA_DIRS, in precedence order
+        let application_dirs: Vec<PathBuf> = default_paths().collect();
+
+        // only the desktop file with the highest precedence is used for each desktop file id
+        let desktop_files = desktop_entry_finder::find_desktop_files(&application_dirs);
 
         let locales = get_languages_from_env();
+        let desktop_entry_filter = DesktopEntryFilter::from_env();
 
-        let vec = cleaned_desktop_file_paths
+        let vec = desktop_files
             .iter()
-            .filter_map(|desktop_file_path| {
+            .filter_map(|(_, desktop_file_path)| {
                 Self::read_desktop_entry_matching(
                     &desktop_file_path.as_path(),
                     content_type,
                     &locales,
+                    &desktop_entry_filter,
                 )
             })
             .collect();
@@ -125,6 +125,7 @@ impl OsHelper {
         desktop_file_path: &Path,
         content_type: &str,
         locales: &Vec<String>,
+        desktop_entry_filter: &DesktopEntryFilter,
     ) -> Option<DesktopEntryHolder> {
         return fs::read_to_string(&desktop_file_path)
             .ok()
@@ -137,8 +138,12 @@ impl OsHelper {
                             .filter(|mime_types| mime_types.contains(&content_type))
                             .is_some();
 
-                        if contains_mime_type {
-                            Self::freedesktop_desktop_entry_to_desktop_entry_holder(&entry, locales)
+                        if contains_mime_type && desktop_entry_filter.is_shown(&entry) {
+                            Self::freedesktop_desktop_entry_to_desktop_entry_holder(
+                                &entry,
+                                desktop_file_path,
+                                locales,
+                            )
                         } else {
                             None
                         }
@@ -148,6 +153,7 @@ impl OsHelper {
 
     fn freedesktop_desktop_entry_to_desktop_entry_holder(
         desktop_entry: &DesktopEntry,
+        desktop_file_path: &Path,
         locales: &Vec<String>,
     ) -> Option<DesktopEntryHolder> {
         let app_id = desktop_entry.id();
@@ -167,12 +173,21 @@ impl OsHelper {
         };
 
         let icon_maybe = desktop_entry.icon().map(|icon| icon.to_string());
+        let snap_instance_name_maybe = desktop_entry
+            .desktop_entry("X-SnapInstanceName")
+            .map(|snap_instance_name| snap_instance_name.to_string());
+        let actions = desktop_actions::read_desktop_actions(desktop_entry);
+        let no_display = desktop_entry_finder::is_no_display(desktop_entry);
 
         return Some(DesktopEntryHolder {
             app_id: app_id.to_string(),
             display_name: display_name,
             icon: icon_maybe,
+            desktop_file_path: desktop_file_path.to_path_buf(),
+            snap_instance_name: snap_instance_name_maybe,
             exec: exec.to_string(),
+            actions: actions,
+            no_display: no_display,
         });
     }
 
@@ -189,25 +204,28 @@ impl OsHelper {
         }
 
         let command_str = desktop_entry_holder.exec.as_str();
-        let command_parts: Vec<String> = shell_words::split(command_str)
-            .expect("failed to parse Exec value in the .desktop file");
+        let command_parts: Vec<String> = match desktop_exec_parser::parse_exec(command_str) {
+            Some(command_parts) => command_parts,
+            None => {
+                warn!("Exec line of {} has an unclosed quote: {}", id, command_str);
+                return None;
+            }
+        };
 
         if command_parts.is_empty() {
             warn!("Exec line is empty! This browser won't work");
             return None;
         }
 
-        // check if it's snap package in a bit naive way
-        // "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop /snap/bin/firefox %u"
-        let is_snap = command_parts
-            .iter()
-            .any(|part| part.starts_with("/snap/bin"));
+        // "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=/app/bin/chrome --file-forwarding com.google.Chrome @@u %U @@"
+        let flatpak_run_maybe = flatpak_command_parser::parse_flatpak_run(&command_parts);
 
-        //let snap_root_path = self.snap_base.clone();
-        //let snap_linux_config_dir_relative_path = PathBuf::from(snap_name)
-        //    .join("common")
-        //    .join(linux_config_dir_relative);
-        //let config_dir_absolute = snap_root_path.join(snap_linux_config_dir_relative_path);
+        // "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop /snap/bin/firefox %u"
+        let snap_instance_name_maybe = snap_detector::find_snap_instance_name(
+            &command_parts,
+            desktop_entry_holder.desktop_file_path.as_path(),
+            desktop_entry_holder.snap_instance_name.as_deref(),
+        );
 
         // we need executable path for two reasons:
         //  - to uniquely identify apps
@@ -232,7 +250,16 @@ impl OsHelper {
         /*let icon_maybe = app_info.icon();
         let icon: Icon = icon_maybe.unwrap();*/
 
-        let supported_app = self.app_repository.get_or_generate(id, &restricted_domains);
+        let guessed_app_config_maybe = self.app_repository.guess_app_config(
+            id,
+            executable_path_best_guess,
+            desktop_entry_holder.display_name.as_str(),
+        );
+        let supported_app = self.app_repository.get_or_generate(
+            id,
+            &restricted_domains,
+            guessed_app_config_maybe.as_ref(),
+        );
 
         let icon_filename = id.to_string() + ".png";
         let full_stored_icon_path = icons_root_dir.join(icon_filename);
@@ -246,19 +273,44 @@ impl OsHelper {
         //let _string = app_info.to_string();
         //println!("app_info: {}", id);
 
-        // new firefox actually doesn't refer to the snap binary in the desktop file,
-        // so there is no clean way to check that
-        // I think we should just try both
-        if !is_snap {
-            // look deeper!
-        }
+        let app_config_dir_abs = if let Some(flatpak_run) = flatpak_run_maybe.as_ref() {
+            info!("{} is flatpak app {}", id, flatpak_run.app_id);
+            supported_app
+                .get_app_config_dir_abs(true, false)
+                .to_path_buf()
+        } else {
+            if let Some(snap_instance_name) = snap_instance_name_maybe.as_ref() {
+                info!("{} is snap {}", id, snap_instance_name);
+            }
 
-        let app_config_dir_abs = supported_app.get_app_config_dir_abs(is_snap, false);
+            // new firefox doesn't always refer to the snap binary in the desktop file,
+            // so the native config dir is tried even for snaps and the snap dir for all other apps
+            supported_app.find_snap_or_native_app_config_dir_abs(
+                paths::get_snap_root().as_path(),
+                snap_instance_name_maybe.as_deref(),
+            )
+        };
+        info!("{} config dir: {}", id, app_config_dir_abs.display());
+
+        let profiles =
+            supported_app.find_profiles(executable_path_best_guess, app_config_dir_abs.as_path());
+
+        // %i, %c and %k don't change, the url is added when launching
+        let command = desktop_exec_parser::expand_desktop_entry_fields(
+            &command_parts,
+            &DesktopEntryFields {
+                icon: desktop_entry_holder.icon.as_deref(),
+                name: display_name,
+                desktop_file_path: desktop_entry_holder.desktop_file_path.as_path(),
+            },
+        );
 
-        let profiles = supported_app.find_profiles(executable_path_best_guess, app_config_dir_abs);
+        // for generic apps especially, as apps in the repository usually have the launch modes
+        let desktop_action_launch_modes =
+            desktop_actions::find_launch_modes(&desktop_entry_holder.actions, &command_parts);
 
         let browser = InstalledBrowser {
-            command: command_parts.clone(),
+            command: command,
             executable_path: executable_path_best_guess.to_str().unwrap().to_string(),
             display_name: display_name.to_string(),
             bundle: supported_app.get_app_id().to_string(),
@@ -266,6 +318,11 @@ impl OsHelper {
             icon_path: icon_path_str.clone(),
             profiles: profiles,
             restricted_domains: restricted_domains,
+            custom_user_dir: None,
+            custom_app: None,
+            desktop_action_launch_modes: desktop_action_launch_modes,
+            no_display: desktop_entry_holder.no_display,
+            guessed_app_config: guessed_app_config_maybe,
         };
         return Some(browser);
     }
@@ -427,11 +484,49 @@ pub fn get_snap_root_dir() -> PathBuf {
     //return buf;
 }
 
+// $HOME/.var/app, which has a data dir for each flatpak app (e.g $HOME/.var/app/com.google.Chrome),
+// with "config" dir being its $XDG_CONFIG_HOME and the rest being its home dir
+pub fn get_flatpak_root_dir() -> PathBuf {
+    // TODO: escape sandbox if Browsers is running in snap/flatpak
+    let home_dir = dirs::home_dir().unwrap();
+    return home_dir.join(".var").join("app");
+}
+
 pub fn linux_get_unsandboxed_home_dir() -> PathBuf {
     // TODO: escape sandbox if in snap/flatpak
     return dirs::home_dir().unwrap();
 }
 
+// mime types Browsers handles as the default web browser
+const WEB_BROWSER_MIME_TYPES: [&'static str; 4] = [
+    "x-scheme-handler/http",
+    "x-scheme-handler/https",
+    "text/html",
+    "application/xhtml+xml",
+];
+
+// the ones of `WEB_BROWSER_MIME_TYPES` which make an app the default web browser
+const WEB_BROWSER_SCHEME_MIME_TYPES: [&'static str; 2] =
+    ["x-scheme-handler/http", "x-scheme-handler/https"];
+
+// defaults before Browsers became the default web browser, see `MimeAppsDirs::backup_defaults`
+fn get_default_browser_backup_path() -> PathBuf {
+    return get_this_app_config_root_dir().join("default-browser-backup.list");
+}
+
+// whether the desktop id still has a desktop file, to skip defaults which are not installed
+// anymore; Browsers itself is installed even if its desktop file isn't in the application
+// dirs (e.g AppImage)
+fn is_installed_desktop_id(mimeapps_dirs: &MimeAppsDirs, desktop_id: &str) -> bool {
+    let desktop_file_name = format!("{}.desktop", XDG_NAME);
+    return desktop_id == desktop_file_name
+        || desktop_entry_finder::find_desktop_file(
+            &mimeapps_dirs.get_application_dirs(),
+            desktop_id,
+        )
+        .is_some();
+}
+
 // returns true if it was already default web browser (then nothing was done)
 pub fn set_default_web_browser() -> bool {
     if is_default_web_browser() {
@@ -439,47 +534,97 @@ pub fn set_default_web_browser() -> bool {
     }
 
     let desktop_file_name = format!("{}.desktop", XDG_NAME);
-
-    let result = Command::new("xdg-mime")
-        .arg("default")
-        .arg(desktop_file_name.as_str())
-        .arg("x-scheme-handler/https")
-        .arg("x-scheme-handler/http")
-        .status();
-
-    if result.is_err() {
+    let mimeapps_dirs = MimeAppsDirs::from_env();
+
+    mimeapps_dirs.backup_defaults(
+        &WEB_BROWSER_MIME_TYPES,
+        desktop_file_name.as_str(),
+        get_default_browser_backup_path().as_path(),
+        |desktop_id| is_installed_desktop_id(&mimeapps_dirs, desktop_id),
+    );
+    if !mimeapps_dirs.set_default(&WEB_BROWSER_MIME_TYPES, desktop_file_name.as_str()) {
         warn!("Could not set Browsers as default app");
     }
 
     return false;
 }
 
+// only the url schemes decide it, as apps often leave out the file types (e.g text/html)
 pub fn is_default_web_browser() -> bool {
     let desktop_file_name = format!("{}.desktop", XDG_NAME);
+    let mimeapps_dirs = MimeAppsDirs::from_env();
 
-    let https_default_app = query_default_app("x-scheme-handler/https").unwrap_or("".to_string());
-    let http_default_app = query_default_app("x-scheme-handler/http").unwrap_or("".to_string());
+    return WEB_BROWSER_SCHEME_MIME_TYPES.iter().all(|mime_type| {
+        let default_app = mimeapps_dirs
+            .query_default(mime_type, |desktop_id| {
+                is_installed_desktop_id(&mimeapps_dirs, desktop_id)
+            })
+            .unwrap_or("".to_string());
+        info!("Default for {mime_type} is '{default_app}'");
+        default_app == desktop_file_name
+    });
+}
 
-    return https_default_app == desktop_file_name && http_default_app == desktop_file_name;
+// returns false if Browsers was not the default web browser
+pub fn restore_previous_default_web_browser() -> bool {
+    let desktop_file_name = format!("{}.desktop", XDG_NAME);
+    let mimeapps_dirs = MimeAppsDirs::from_env();
+
+    let restored = mimeapps_dirs.restore_defaults(
+        &WEB_BROWSER_MIME_TYPES,
+        desktop_file_name.as_str(),
+        get_default_browser_backup_path().as_path(),
+    );
+    if !restored {
+        warn!("Browsers is not the default browser, nothing to restore");
+    }
+    return restored;
 }
 
-fn query_default_app(scheme: &str) -> Option<String> {
-    let result = Command::new("xdg-mime")
-        .arg("query")
-        .arg("default")
-        .arg(scheme)
-        .stdout(Stdio::piped())
-        .output();
-
-    if result.is_err() {
-        warn!("Could not query default app for scheme {}", scheme);
-        return None;
+// Makes Browsers the default handler for configured url schemes (e.g "tel", "magnet")
+// and gives schemes which are not configured anymore back to their previous handler.
+// http, https and mailto are left alone, because they are set up separately.
+pub fn sync_url_scheme_handlers(schemes: &Vec<String>) {
+    let desktop_file_name = format!("{}.desktop", XDG_NAME);
+    let built_in_mime_types = vec![
+        "x-scheme-handler/http".to_string(),
+        "x-scheme-handler/https".to_string(),
+        "x-scheme-handler/mailto".to_string(),
+    ];
+
+    let wanted_mime_types: Vec<String> = schemes
+        .iter()
+        .map(|scheme| format!("x-scheme-handler/{scheme}"))
+        .collect();
+
+    let mimeapps_list_path = MimeAppsDirs::from_env().get_user_mimeapps_list_path();
+    let mut mimeapps_list = MimeAppsList::load(mimeapps_list_path.as_path());
+    let registered_mime_types =
+        mimeapps_list.get_mime_types_defaulting_to(desktop_file_name.as_str());
+
+    let mut changed = false;
+
+    for registered_mime_type in &registered_mime_types {
+        let is_scheme = registered_mime_type.starts_with("x-scheme-handler/");
+        if is_scheme
+            && !built_in_mime_types.contains(registered_mime_type)
+            && !wanted_mime_types.contains(registered_mime_type)
+        {
+            info!("Unregistering Browsers for {}", registered_mime_type);
+            mimeapps_list.remove_default(registered_mime_type, desktop_file_name.as_str());
+            changed = true;
+        }
     }
 
-    let output = result.unwrap();
+    for wanted_mime_type in &wanted_mime_types {
+        if !registered_mime_types.contains(wanted_mime_type) {
+            info!("Registering Browsers for {}", wanted_mime_type);
+            mimeapps_list.add_default(wanted_mime_type, desktop_file_name.as_str());
+            changed = true;
+        }
+    }
 
-    // extract the raw bytes that we captured and interpret them as a string
-    let default_app = String::from_utf8(output.stdout).unwrap().trim().to_string();
-    info!("Default for {scheme} is '{default_app}'");
-    return Some(default_app);
+    if changed {
+        mimeapps_list.save(mimeapps_list_path.as_path());
+    }
 }
@@ -0,0 +1,748 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tracing::{debug, info, warn};
+
+const DEFAULT_APPLICATIONS_SECTION: &'static str = "Default Applications";
+
+// Dirs which have mimeapps.list files, see
+// https://specifications.freedesktop.org/mime-apps-spec/latest/file.html
+pub struct MimeAppsDirs {
+    config_home: PathBuf,
+    config_dirs: Vec<PathBuf>,
+    data_home: PathBuf,
+    data_dirs: Vec<PathBuf>,
+    // from XDG_CURRENT_DESKTOP, lowercased for desktop-specific files, e.g ["ubuntu", "gnome"]
+    desktops: Vec<String>,
+}
+
+impl MimeAppsDirs {
+    pub fn new(
+        config_home: PathBuf,
+        config_dirs: Vec<PathBuf>,
+        data_home: PathBuf,
+        data_dirs: Vec<PathBuf>,
+        current_desktops: Vec<String>,
+    ) -> Self {
+        Self {
+            config_home: config_home,
+            config_dirs: config_dirs,
+            data_home: data_home,
+            data_dirs: data_dirs,
+            desktops: current_desktops
+                .iter()
+                .map(|desktop| desktop.to_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let current_desktops: Vec<String> = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .split(':')
+            .filter(|desktop| !desktop.is_empty())
+            .map(|desktop| desktop.to_string())
+            .collect();
+
+        return Self::new(
+            dirs::config_dir().unwrap(),
+            env_dirs_or("XDG_CONFIG_DIRS", "/etc/xdg"),
+            dirs::data_dir().unwrap(),
+            env_dirs_or("XDG_DATA_DIRS", "/usr/local/share:/usr/share"),
+            current_desktops,
+        );
+    }
+
+    // changes are written here
+    pub fn get_user_mimeapps_list_path(&self) -> PathBuf {
+        return self.config_home.join("mimeapps.list");
+    }
+
+    // e.g $XDG_CONFIG_HOME/gnome-mimeapps.list, which take precedence over the user mimeapps.list
+    fn get_user_desktop_mimeapps_list_paths(&self) -> Vec<PathBuf> {
+        return self
+            .desktops
+            .iter()
+            .map(|desktop| self.config_home.join(format!("{}-mimeapps.list", desktop)))
+            .collect();
+    }
+
+    // in precedence order, desktop-specific files first in each dir:
+    // $XDG_CONFIG_HOME, $XDG_CONFIG_DIRS, $XDG_DATA_HOME/applications (deprecated)
+    // and $XDG_DATA_DIRS/applications
+    pub fn get_mimeapps_list_paths(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = vec![self.config_home.clone()];
+        dirs.extend(self.config_dirs.iter().cloned());
+        dirs.push(self.data_home.join("applications"));
+        dirs.extend(
+            self.data_dirs
+                .iter()
+                .map(|data_dir| data_dir.join("applications")),
+        );
+
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for dir in dirs {
+            for desktop in &self.desktops {
+                paths.push(dir.join(format!("{}-mimeapps.list", desktop)));
+            }
+            paths.push(dir.join("mimeapps.list"));
+        }
+        return paths;
+    }
+
+    // where desktop files are looked up, to check if a default app is installed
+    pub fn get_application_dirs(&self) -> Vec<PathBuf> {
+        let mut application_dirs: Vec<PathBuf> = vec![self.data_home.join("applications")];
+        application_dirs.extend(
+            self.data_dirs
+                .iter()
+                .map(|data_dir| data_dir.join("applications")),
+        );
+        return application_dirs;
+    }
+
+    // first installed app of "Default Applications" in the mimeapps.list files with the
+    // highest precedence which has one, e.g "firefox.desktop" for "x-scheme-handler/https"
+    pub fn query_default<F>(&self, mime_type: &str, is_installed: F) -> Option<String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        for path in self.get_mimeapps_list_paths() {
+            if !path.exists() {
+                continue;
+            }
+
+            let mimeapps_list = MimeAppsList::load(path.as_path());
+            let installed_default_maybe = mimeapps_list
+                .get_defaults(mime_type)
+                .into_iter()
+                .find(|desktop_id| is_installed(desktop_id));
+            if let Some(installed_default) = installed_default_maybe {
+                debug!(
+                    "Default for {} is {} in {}",
+                    mime_type,
+                    installed_default,
+                    path.display()
+                );
+                return Some(installed_default);
+            }
+        }
+        return None;
+    }
+
+    // Makes the desktop id the default for the mime types in the user mimeapps.list,
+    // and in desktop-specific user files which would otherwise override it
+    pub fn set_default(&self, mime_types: &[&str], desktop_id: &str) -> bool {
+        let mut saved = true;
+        for path in self.get_user_desktop_mimeapps_list_paths() {
+            if !path.exists() {
+                continue;
+            }
+
+            let mut mimeapps_list = MimeAppsList::load(path.as_path());
+            let overridden_mime_types: Vec<&str> = mime_types
+                .iter()
+                .filter(|mime_type| !mimeapps_list.get_defaults(mime_type).is_empty())
+                .cloned()
+                .collect();
+            if overridden_mime_types.is_empty() {
+                continue;
+            }
+            for mime_type in overridden_mime_types {
+                mimeapps_list.add_default(mime_type, desktop_id);
+            }
+            saved = mimeapps_list.save(path.as_path()) && saved;
+        }
+
+        let user_mimeapps_list_path = self.get_user_mimeapps_list_path();
+        let mut mimeapps_list = MimeAppsList::load(user_mimeapps_list_path.as_path());
+        for mime_type in mime_types {
+            mimeapps_list.add_default(mime_type, desktop_id);
+        }
+        return mimeapps_list.save(user_mimeapps_list_path.as_path()) && saved;
+    }
+
+    // Saves the current defaults of the mime types (other than the desktop id) in mimeapps.list
+    // format, unless there already is a backup, so it always has the defaults before Browsers;
+    // nothing is saved if there are no defaults, so a later call can still back them up
+    pub fn backup_defaults<F>(
+        &self,
+        mime_types: &[&str],
+        desktop_id: &str,
+        backup_path: &Path,
+        is_installed: F,
+    ) where
+        F: Fn(&str) -> bool,
+    {
+        if backup_path.exists() {
+            return;
+        }
+
+        let mut backup = MimeAppsList::parse("");
+        let mut has_defaults = false;
+        for mime_type in mime_types {
+            let default_maybe = self
+                .query_default(mime_type, &is_installed)
+                .filter(|default| default != desktop_id);
+            if let Some(default) = default_maybe {
+                backup.add_default(mime_type, default.as_str());
+                has_defaults = true;
+            }
+        }
+        if has_defaults {
+            backup.save(backup_path);
+        }
+    }
+
+    // Gives the mime types back to the defaults in the backup (see `backup_defaults`),
+    // or to the next default in the mimeapps.list files if the backup doesn't have one;
+    // returns false if the desktop id was not a default
+    pub fn restore_defaults(
+        &self,
+        mime_types: &[&str],
+        desktop_id: &str,
+        backup_path: &Path,
+    ) -> bool {
+        // empty if there is no backup
+        let backup = MimeAppsList::load(backup_path);
+
+        let mut restored = false;
+        let mut paths = self.get_user_desktop_mimeapps_list_paths();
+        paths.push(self.get_user_mimeapps_list_path());
+        for path in paths {
+            if !path.exists() {
+                continue;
+            }
+
+            let mut mimeapps_list = MimeAppsList::load(path.as_path());
+            let mut changed = false;
+            for mime_type in mime_types {
+                let defaults = mimeapps_list.get_defaults(mime_type);
+                if !defaults.iter().any(|default| default == desktop_id) {
+                    continue;
+                }
+
+                mimeapps_list.remove_default(mime_type, desktop_id);
+                for previous_default in backup.get_defaults(mime_type).iter().rev() {
+                    mimeapps_list.add_default(mime_type, previous_default.as_str());
+                }
+                changed = true;
+            }
+            if changed {
+                restored = mimeapps_list.save(path.as_path()) || restored;
+            }
+        }
+
+        if restored {
+            fs::remove_file(backup_path).ok();
+        }
+        return restored;
+    }
+}
+
+// absolute dirs of a colon-separated env variable, e.g XDG_DATA_DIRS
+fn env_dirs_or(name: &str, default_value: &str) -> Vec<PathBuf> {
+    let value = env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or(default_value.to_string());
+    return env::split_paths(&value)
+        .filter(|dir| dir.is_absolute())
+        .collect();
+}
+
+// Keeps all lines (including comments and other sections) as they were,
+// so that saving only changes the entries we touched
+pub struct MimeAppsList {
+    sections: Vec<MimeAppsSection>,
+}
+
+struct MimeAppsSection {
+    name: String,
+    lines: Vec<String>,
+}
+
+impl MimeAppsList {
+    pub fn load(path: &Path) -> Self {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        return Self::parse(content.as_str());
+    }
+
+    fn parse(content: &str) -> Self {
+        // lines before the first section are kept in a nameless section
+        let mut sections: Vec<MimeAppsSection> = vec![MimeAppsSection {
+            name: "".to_string(),
+            lines: vec![],
+        }];
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                sections.push(MimeAppsSection {
+                    name: trimmed[1..trimmed.len() - 1].to_string(),
+                    lines: vec![],
+                });
+            } else {
+                sections.last_mut().unwrap().lines.push(line.to_string());
+            }
+        }
+
+        return Self { sections: sections };
+    }
+
+    pub fn save(&self, path: &Path) -> bool {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+
+        let result = fs::write(path, self.to_file_content());
+        if result.is_err() {
+            warn!("Could not write {}", path.display());
+            return false;
+        }
+
+        info!("Updated {}", path.display());
+        return true;
+    }
+
+    fn to_file_content(&self) -> String {
+        let mut content = String::new();
+        for section in &self.sections {
+            if !section.name.is_empty() {
+                content.push_str(format!("[{}]\n", section.name).as_str());
+            }
+            for line in &section.lines {
+                content.push_str(line.as_str());
+                content.push('\n');
+            }
+        }
+        return content;
+    }
+
+    // desktop ids in order of preference, e.g ["software.Browsers.desktop", "firefox.desktop"]
+    pub fn get_defaults(&self, mime_type: &str) -> Vec<String> {
+        return self
+            .find_section(DEFAULT_APPLICATIONS_SECTION)
+            .and_then(|section| {
+                section
+                    .lines
+                    .iter()
+                    .find_map(|line| parse_entry(line).filter(|(key, _)| key == mime_type))
+            })
+            .map(|(_, desktop_ids)| desktop_ids)
+            .unwrap_or_default();
+    }
+
+    // mime types which have the desktop id as the preferred default
+    pub fn get_mime_types_defaulting_to(&self, desktop_id: &str) -> Vec<String> {
+        return self
+            .find_section(DEFAULT_APPLICATIONS_SECTION)
+            .map(|section| {
+                section
+                    .lines
+                    .iter()
+                    .filter_map(|line| parse_entry(line))
+                    .filter(|(_, desktop_ids)| {
+                        desktop_ids.first().map(|id| id.as_str()) == Some(desktop_id)
+                    })
+                    .map(|(mime_type, _)| mime_type)
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    // Makes desktop id the preferred default, but keeps the previous defaults after it,
+    // so they become the default again once it's removed
+    pub fn add_default(&mut self, mime_type: &str, desktop_id: &str) {
+        let mut desktop_ids: Vec<String> = self
+            .get_defaults(mime_type)
+            .into_iter()
+            .filter(|id| id != desktop_id)
+            .collect();
+        desktop_ids.insert(0, desktop_id.to_string());
+
+        self.set_defaults(mime_type, desktop_ids);
+    }
+
+    pub fn remove_default(&mut self, mime_type: &str, desktop_id: &str) {
+        let desktop_ids: Vec<String> = self
+            .get_defaults(mime_type)
+            .into_iter()
+            .filter(|id| id != desktop_id)
+            .collect();
+
+        self.set_defaults(mime_type, desktop_ids);
+    }
+
+    fn set_defaults(&mut self, mime_type: &str, desktop_ids: Vec<String>) {
+        if self.find_section(DEFAULT_APPLICATIONS_SECTION).is_none() {
+            self.sections.push(MimeAppsSection {
+                name: DEFAULT_APPLICATIONS_SECTION.to_string(),
+                lines: vec![],
+            });
+        }
+
+        let section = self
+            .sections
+            .iter_mut()
+            .find(|section| section.name == DEFAULT_APPLICATIONS_SECTION)
+            .unwrap();
+
+        let line_index_maybe = section.lines.iter().position(|line| {
+            parse_entry(line)
+                .filter(|(key, _)| key == mime_type)
+                .is_some()
+        });
+
+        let new_line_maybe = if desktop_ids.is_empty() {
+            None
+        } else {
+            Some(format!("{}={};", mime_type, desktop_ids.join(";")))
+        };
+
+        match (line_index_maybe, new_line_maybe) {
+            (Some(line_index), Some(new_line)) => section.lines[line_index] = new_line,
+            (Some(line_index), None) => {
+                section.lines.remove(line_index);
+            }
+            (None, Some(new_line)) => {
+                // keep the new entry before trailing empty lines
+                let insert_index = section
+                    .lines
+                    .iter()
+                    .rposition(|line| !line.trim().is_empty())
+                    .map(|index| index + 1)
+                    .unwrap_or(0);
+                section.lines.insert(insert_index, new_line);
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn find_section(&self, name: &str) -> Option<&MimeAppsSection> {
+        return self.sections.iter().find(|section| section.name == name);
+    }
+}
+
+// "x-scheme-handler/https=firefox.desktop;chromium.desktop;"
+// to ("x-scheme-handler/https", ["firefox.desktop", "chromium.desktop"])
+fn parse_entry(line: &str) -> Option<(String, Vec<String>)> {
+    let trimmed = line.trim();
+    if trimmed.starts_with('#') {
+        return None;
+    }
+
+    let (key, value) = trimmed.split_once('=')?;
+    let desktop_ids: Vec<String> = value
+        .split(';')
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect();
+
+    return Some((key.trim().to_string(), desktop_ids));
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_utils;
+
+    const MIMEAPPS_LIST: &str = "[Added Associations]
+x-scheme-handler/magnet=transmission-gtk.desktop;
+
+[Default Applications]
+# managed by desktop environment
+x-scheme-handler/https=software.Browsers.desktop
+x-scheme-handler/magnet=org.qbittorrent.qBittorrent.desktop;
+
+";
+
+    #[test]
+    fn test_get_defaults() {
+        let mimeapps_list = MimeAppsList::parse(MIMEAPPS_LIST);
+        assert_eq!(
+            mimeapps_list.get_defaults("x-scheme-handler/magnet"),
+            vec!["org.qbittorrent.qBittorrent.desktop".to_string()]
+        );
+        assert!(
+            mimeapps_list
+                .get_defaults("x-scheme-handler/tel")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_add_default_keeps_previous_default() {
+        let mut mimeapps_list = MimeAppsList::parse(MIMEAPPS_LIST);
+        mimeapps_list.add_default("x-scheme-handler/magnet", "software.Browsers.desktop");
+        mimeapps_list.add_default("x-scheme-handler/tel", "software.Browsers.desktop");
+
+        assert_eq!(
+            mimeapps_list.to_file_content(),
+            "[Added Associations]
+x-scheme-handler/magnet=transmission-gtk.desktop;
+
+[Default Applications]
+# managed by desktop environment
+x-scheme-handler/https=software.Browsers.desktop
+x-scheme-handler/magnet=software.Browsers.desktop;org.qbittorrent.qBittorrent.desktop;
+x-scheme-handler/tel=software.Browsers.desktop;
+
+"
+        );
+    }
+
+    #[test]
+    fn test_remove_default_restores_previous_default() {
+        let mut mimeapps_list = MimeAppsList::parse(MIMEAPPS_LIST);
+        mimeapps_list.add_default("x-scheme-handler/magnet", "software.Browsers.desktop");
+        mimeapps_list.remove_default("x-scheme-handler/magnet", "software.Browsers.desktop");
+        mimeapps_list.remove_default("x-scheme-handler/https", "software.Browsers.desktop");
+
+        assert_eq!(
+            mimeapps_list.to_file_content(),
+            "[Added Associations]
+x-scheme-handler/magnet=transmission-gtk.desktop;
+
+[Default Applications]
+# managed by desktop environment
+x-scheme-handler/magnet=org.qbittorrent.qBittorrent.desktop;
+
+"
+        );
+    }
+
+    #[test]
+    fn test_add_default_creates_section() {
+        let mut mimeapps_list = MimeAppsList::parse("");
+        mimeapps_list.add_default("x-scheme-handler/sip", "software.Browsers.desktop");
+
+        assert_eq!(
+            mimeapps_list.to_file_content(),
+            "[Default Applications]\nx-scheme-handler/sip=software.Browsers.desktop;\n"
+        );
+        assert_eq!(
+            mimeapps_list.get_mime_types_defaulting_to("software.Browsers.desktop"),
+            vec!["x-scheme-handler/sip".to_string()]
+        );
+    }
+
+    const WEB_BROWSER_MIME_TYPES: [&str; 4] = [
+        "x-scheme-handler/http",
+        "x-scheme-handler/https",
+        "text/html",
+        "application/xhtml+xml",
+    ];
+
+    const INSTALLED_APPS: [&str; 3] = [
+        "firefox.desktop",
+        "org.gnome.Epiphany.desktop",
+        "software.Browsers.desktop",
+    ];
+
+    fn is_installed(desktop_id: &str) -> bool {
+        return INSTALLED_APPS.contains(&desktop_id);
+    }
+
+    // temporary XDG dirs, with GNOME as the current desktop
+    fn create_mimeapps_dirs() -> (TempDir, MimeAppsDirs) {
+        let temp_dir = test_utils::create_temp_dir("mimeapps-list");
+        let test_dir = temp_dir.path();
+
+        let dirs = MimeAppsDirs::new(
+            test_dir.join("home/.config"),
+            vec![test_dir.join("etc/xdg")],
+            test_dir.join("home/.local/share"),
+            vec![test_dir.join("usr/local/share"), test_dir.join("usr/share")],
+            vec!["ubuntu".to_string(), "GNOME".to_string()],
+        );
+        return (temp_dir, dirs);
+    }
+
+    fn write_file(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_mimeapps_list_paths() {
+        let (_temp_dir, dirs) = create_mimeapps_dirs();
+        let paths = dirs.get_mimeapps_list_paths();
+        let relative_paths: Vec<String> = paths
+            .iter()
+            .map(|path| {
+                path.strip_prefix(dirs.config_home.parent().unwrap().parent().unwrap())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            relative_paths,
+            vec![
+                "home/.config/ubuntu-mimeapps.list",
+                "home/.config/gnome-mimeapps.list",
+                "home/.config/mimeapps.list",
+                "etc/xdg/ubuntu-mimeapps.list",
+                "etc/xdg/gnome-mimeapps.list",
+                "etc/xdg/mimeapps.list",
+                "home/.local/share/applications/ubuntu-mimeapps.list",
+                "home/.local/share/applications/gnome-mimeapps.list",
+                "home/.local/share/applications/mimeapps.list",
+                "usr/local/share/applications/ubuntu-mimeapps.list",
+                "usr/local/share/applications/gnome-mimeapps.list",
+                "usr/local/share/applications/mimeapps.list",
+                "usr/share/applications/ubuntu-mimeapps.list",
+                "usr/share/applications/gnome-mimeapps.list",
+                "usr/share/applications/mimeapps.list",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_default_precedence() {
+        let (_temp_dir, dirs) = create_mimeapps_dirs();
+        let usr_share = dirs.data_dirs[1].clone();
+        write_file(
+            usr_share.join("applications/gnome-mimeapps.list").as_path(),
+            "[Default Applications]\ntext/html=org.gnome.Epiphany.desktop\nx-scheme-handler/https=org.gnome.Epiphany.desktop\n",
+        );
+        // chromium is not installed, so the next one is used
+        write_file(
+            dirs.config_dirs[0].join("mimeapps.list").as_path(),
+            "[Default Applications]\nx-scheme-handler/https=chromium.desktop;firefox.desktop;\n",
+        );
+
+        assert_eq!(
+            dirs.query_default("x-scheme-handler/https", is_installed),
+            Some("firefox.desktop".to_string())
+        );
+        assert_eq!(
+            dirs.query_default("text/html", is_installed),
+            Some("org.gnome.Epiphany.desktop".to_string())
+        );
+        assert_eq!(dirs.query_default("x-scheme-handler/http", is_installed), None);
+
+        // user's desktop-specific file overrides the user's mimeapps.list
+        write_file(
+            dirs.get_user_mimeapps_list_path().as_path(),
+            "[Default Applications]\nx-scheme-handler/https=org.gnome.Epiphany.desktop\n",
+        );
+        write_file(
+            dirs.config_home.join("gnome-mimeapps.list").as_path(),
+            "[Default Applications]\nx-scheme-handler/https=software.Browsers.desktop\n",
+        );
+        assert_eq!(
+            dirs.query_default("x-scheme-handler/https", is_installed),
+            Some("software.Browsers.desktop".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_default_backup_and_restore() {
+        let (_temp_dir, dirs) = create_mimeapps_dirs();
+        let backup_path = dirs
+            .config_home
+            .join("software.Browsers/default-browser-backup.list");
+        write_file(
+            dirs.config_dirs[0].join("mimeapps.list").as_path(),
+            "[Default Applications]\nx-scheme-handler/http=firefox.desktop\nx-scheme-handler/https=firefox.desktop\ntext/html=firefox.desktop\n",
+        );
+        // e.g set by the desktop's own settings
+        write_file(
+            dirs.config_home.join("gnome-mimeapps.list").as_path(),
+            "[Default Applications]\nx-scheme-handler/https=org.gnome.Epiphany.desktop\n",
+        );
+
+        dirs.backup_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+            is_installed,
+        );
+        assert!(dirs.set_default(&WEB_BROWSER_MIME_TYPES, "software.Browsers.desktop"));
+        for mime_type in WEB_BROWSER_MIME_TYPES {
+            assert_eq!(
+                dirs.query_default(mime_type, is_installed),
+                Some("software.Browsers.desktop".to_string()),
+                "{}",
+                mime_type
+            );
+        }
+
+        // backup is kept from the first time
+        dirs.backup_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+            is_installed,
+        );
+        assert_eq!(
+            fs::read_to_string(&backup_path).unwrap(),
+            "[Default Applications]
+x-scheme-handler/http=firefox.desktop;
+x-scheme-handler/https=org.gnome.Epiphany.desktop;
+text/html=firefox.desktop;
+"
+        );
+
+        assert!(dirs.restore_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+        ));
+        assert_eq!(
+            dirs.query_default("x-scheme-handler/https", is_installed),
+            Some("org.gnome.Epiphany.desktop".to_string())
+        );
+        assert_eq!(
+            dirs.query_default("x-scheme-handler/http", is_installed),
+            Some("firefox.desktop".to_string())
+        );
+        assert_eq!(dirs.query_default("application/xhtml+xml", is_installed), None);
+        assert!(!backup_path.exists());
+
+        // nothing to restore anymore
+        assert!(!dirs.restore_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+        ));
+    }
+
+    #[test]
+    fn test_no_backup_without_defaults() {
+        let (_temp_dir, dirs) = create_mimeapps_dirs();
+        let backup_path = dirs
+            .config_home
+            .join("software.Browsers/default-browser-backup.list");
+
+        dirs.backup_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+            is_installed,
+        );
+        assert!(!backup_path.exists());
+
+        // so the defaults are still backed up once there are some
+        write_file(
+            dirs.config_home.join("mimeapps.list").as_path(),
+            "[Default Applications]\nx-scheme-handler/https=firefox.desktop\n",
+        );
+        dirs.backup_defaults(
+            &WEB_BROWSER_MIME_TYPES,
+            "software.Browsers.desktop",
+            backup_path.as_path(),
+            is_installed,
+        );
+        assert_eq!(
+            fs::read_to_string(&backup_path).unwrap(),
+            "[Default Applications]\nx-scheme-handler/https=firefox.desktop;\n"
+        );
+    }
+}
@@ -1 +1,6 @@
+pub mod desktop_actions;
+pub mod desktop_entry_finder;
+pub mod detached_process;
 pub mod linux_utils;
+pub mod mimeapps_list;
+pub mod snap_detector;
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+const SNAPD_DESKTOP_APPLICATIONS_DIR: &'static str = "/var/lib/snapd/desktop/applications";
+
+// Finds the snap instance name (e.g "firefox", or "firefox_work" for a parallel install)
+// of a desktop entry, which is also the name of its user data dir "~/snap/<instance name>".
+// Snap desktop entries don't always refer to the snap binary, e.g newer Firefox has
+// "Exec=env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox_firefox.desktop firefox %u",
+// so this checks (in order):
+//  - `X-SnapInstanceName` key, which snapd adds to the desktop entries it installs
+//  - `BAMF_DESKTOP_FILE_HINT` in the command
+//  - the desktop file being in snapd desktop applications dir
+//  - "/snap/bin/<instance name>" in the command
+pub fn find_snap_instance_name(
+    command_parts: &[String],
+    desktop_file_path: &Path,
+    snap_instance_name_maybe: Option<&str>,
+) -> Option<String> {
+    if let Some(snap_instance_name) = snap_instance_name_maybe {
+        let snap_instance_name = snap_instance_name.trim();
+        if !snap_instance_name.is_empty() {
+            return Some(snap_instance_name.to_string());
+        }
+    }
+
+    let bamf_desktop_file_hint_maybe = command_parts
+        .iter()
+        .find_map(|part| part.strip_prefix("BAMF_DESKTOP_FILE_HINT="))
+        .map(|hint| Path::new(hint));
+    if let Some(bamf_desktop_file_hint) = bamf_desktop_file_hint_maybe {
+        if let Some(instance_name) = snapd_desktop_file_instance_name(bamf_desktop_file_hint) {
+            return Some(instance_name);
+        }
+    }
+
+    if desktop_file_path.starts_with(SNAPD_DESKTOP_APPLICATIONS_DIR) {
+        if let Some(instance_name) = snapd_desktop_file_instance_name(desktop_file_path) {
+            return Some(instance_name);
+        }
+    }
+
+    return command_parts
+        .iter()
+        .find_map(|part| part.strip_prefix("/snap/bin/"))
+        .and_then(|snap_command| snap_command_instance_name(snap_command));
+}
+
+// snapd names desktop files "<instance name>_<desktop file name>.desktop",
+// with instance key separated by "+" instead of "_", e.g
+// "/var/lib/snapd/desktop/applications/firefox_firefox.desktop" to "firefox"
+// "/var/lib/snapd/desktop/applications/firefox+work_firefox.desktop" to "firefox_work"
+fn snapd_desktop_file_instance_name(desktop_file_path: &Path) -> Option<String> {
+    let file_stem = desktop_file_path.file_stem()?.to_str()?;
+    let (instance_name, _) = file_stem.split_once('_')?;
+    if instance_name.is_empty() {
+        return None;
+    }
+
+    return Some(instance_name.replace('+', "_"));
+}
+
+// "/snap/bin/<instance name>" runs the app of the same name,
+// "/snap/bin/<instance name>.<app>" runs any other app of the snap, e.g
+// "chromium" to "chromium"
+// "chromium.chromedriver" to "chromium"
+fn snap_command_instance_name(snap_command: &str) -> Option<String> {
+    let instance_name = snap_command.split('.').next().unwrap_or(snap_command);
+    if instance_name.is_empty() || instance_name.contains('/') {
+        return None;
+    }
+
+    return Some(instance_name.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_command_parts(exec: &str) -> Vec<String> {
+        return exec
+            .split_whitespace()
+            .map(|part| part.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn test_snap_instance_name_key() {
+        let command_parts = to_command_parts("firefox %u");
+        assert_eq!(
+            find_snap_instance_name(
+                &command_parts,
+                Path::new("/usr/share/applications/firefox.desktop"),
+                Some("firefox_work"),
+            ),
+            Some("firefox_work".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bamf_desktop_file_hint() {
+        let command_parts = to_command_parts(
+            "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/firefox+work_firefox.desktop firefox %u",
+        );
+        assert_eq!(
+            find_snap_instance_name(
+                &command_parts,
+                Path::new("/home/madis/.local/share/applications/firefox.desktop"),
+                None,
+            ),
+            Some("firefox_work".to_string())
+        );
+    }
+
+    #[test]
+    fn test_snapd_desktop_file() {
+        let command_parts = to_command_parts("firefox %u");
+        assert_eq!(
+            find_snap_instance_name(
+                &command_parts,
+                Path::new("/var/lib/snapd/desktop/applications/firefox_firefox.desktop"),
+                None,
+            ),
+            Some("firefox".to_string())
+        );
+    }
+
+    #[test]
+    fn test_snap_bin_command() {
+        let command_parts = to_command_parts("/snap/bin/chromium.chromedriver %U");
+        assert_eq!(
+            find_snap_instance_name(
+                &command_parts,
+                Path::new("/usr/share/applications/chromium.desktop"),
+                None,
+            ),
+            Some("chromium".to_string())
+        );
+    }
+
+    #[test]
+    fn test_not_snap() {
+        let command_parts = to_command_parts("/usr/bin/firefox %u");
+        assert_eq!(
+            find_snap_instance_name(
+                &command_parts,
+                Path::new("/usr/share/applications/firefox.desktop"),
+                Some(""),
+            ),
+            None
+        );
+    }
+}
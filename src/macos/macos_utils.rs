@@ -132,9 +132,6 @@ impl OsHelper {
             return None;
         }
 
-        let supported_app = self
-            .app_repository
-            .get_or_generate(bundle_id, &restricted_domain_patterns);
         let icon_filename = bundle_id.to_string() + ".png";
         let full_stored_icon_path = icons_root_dir.join(icon_filename);
 
@@ -149,6 +146,17 @@ impl OsHelper {
         let executable_path = macos_native::get_app_executable_path(&bundle_url);
         let executable_path = PathBuf::from(executable_path);
 
+        let guessed_app_config_maybe = self.app_repository.guess_app_config(
+            bundle_id,
+            executable_path.as_path(),
+            display_name.as_str(),
+        );
+        let supported_app = self.app_repository.get_or_generate(
+            bundle_id,
+            &restricted_domain_patterns,
+            guessed_app_config_maybe.as_ref(),
+        );
+
         let icon_path_str = full_stored_icon_path.display().to_string();
         macos_native::create_icon_for_app(&bundle_url, icon_path_str.as_str());
 
@@ -168,6 +176,11 @@ impl OsHelper {
             icon_path: icon_path_str.clone(),
             profiles: supported_app.find_profiles(executable_path.as_path(), app_config_dir_abs),
             restricted_domains: restricted_domain_patterns,
+            custom_user_dir: None,
+            custom_app: None,
+            desktop_action_launch_modes: vec![],
+            no_display: false,
+            guessed_app_config: guessed_app_config_maybe,
         };
 
         return Some(browser);
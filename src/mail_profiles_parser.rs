@@ -0,0 +1,105 @@
+use tracing::warn;
+
+use crate::InstalledBrowserProfile;
+use crate::mail_url_parser::AccountLinkFormat;
+
+// Each configured account (see `MailAccountConfig`) is a profile, launched by putting the
+// account in the deep link. Links which contain an account are only offered to that account.
+pub fn find_mail_account_profiles(
+    link_format: &AccountLinkFormat,
+    accounts: &[String],
+) -> Vec<InstalledBrowserProfile> {
+    let mut profiles: Vec<InstalledBrowserProfile> = Vec::new();
+
+    for account in accounts {
+        let account = account.trim();
+        if !is_account_address(account) {
+            warn!("Skipping mail account {:?}, it's not an email address", account);
+            continue;
+        }
+        let profile_seen = profiles
+            .iter()
+            .any(|profile| profile.profile_cli_arg_value == account);
+        if profile_seen {
+            continue;
+        }
+
+        profiles.push(InstalledBrowserProfile {
+            profile_cli_arg_value: account.to_string(),
+            profile_cli_container_name: None,
+            profile_name: account.to_string(),
+            profile_icon: None,
+            profile_restricted_url_patterns: link_format.account_url_patterns(account),
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: None,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: None,
+        });
+    }
+
+    return profiles;
+}
+
+// the account is part of the link path, so it can't contain a path separator
+fn is_account_address(account: &str) -> bool {
+    let parts: Vec<&str> = account.split('@').collect();
+    if parts.len() != 2 {
+        return false;
+    }
+    let local_part = parts[0];
+    let domain = parts[1];
+
+    return !local_part.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !account.contains(|c: char| c.is_whitespace() || c == '/');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mail_url_parser;
+
+    use super::*;
+
+    #[test]
+    fn test_is_account_address() {
+        assert!(is_account_address("madis@qminderapp.com"));
+        assert!(!is_account_address("Accounts"));
+        assert!(!is_account_address("@qminderapp.com"));
+        assert!(!is_account_address("madis@localhost"));
+        assert!(!is_account_address("a@b@example.com"));
+        assert!(!is_account_address("a/b@example.com"));
+    }
+
+    #[test]
+    fn test_find_mail_account_profiles() {
+        let accounts = vec![
+            "work@example.com".to_string(),
+            " personal@example.org ".to_string(),
+            "work@example.com".to_string(),
+            "Work".to_string(),
+        ];
+
+        let profiles =
+            find_mail_account_profiles(&mail_url_parser::MIMESTREAM, accounts.as_slice());
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+
+        assert_eq!(names, vec!["work@example.com", "personal@example.org"]);
+        assert_eq!(profiles[0].profile_cli_arg_value, "work@example.com");
+        assert_eq!(
+            profiles[0].profile_restricted_url_patterns,
+            vec![
+                "links.mimestream.com/g/work@example.com/**".to_string(),
+                "links.mimestream.com/*".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_mail_account_profiles_without_accounts() {
+        let profiles = find_mail_account_profiles(&mail_url_parser::MIMESTREAM, &[]);
+        assert!(profiles.is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use url::Url;
+
+// Mail clients which scope their web links to an account, e.g
+// "https://links.mimestream.com/g/madis@qminderapp.com/t/18f06bace4319301"
+// can open the same link in the native app via a deep link, e.g
+// "mimestream:///open/g/madis@qminderapp.com/t/18f06bace4319301"
+pub struct AccountLinkFormat {
+    // host of the web links, e.g "links.mimestream.com"
+    pub web_host: &'static str,
+    // scheme of the deep link, e.g "mimestream"
+    pub app_scheme: &'static str,
+    // path segment which the deep link expects before the web link path, e.g "open"
+    pub app_path_prefix: &'static str,
+    // path segment which precedes the account in the web link, e.g "g"
+    pub account_segment_marker: &'static str,
+}
+
+pub const MIMESTREAM: AccountLinkFormat = AccountLinkFormat {
+    web_host: "links.mimestream.com",
+    app_scheme: "mimestream",
+    app_path_prefix: "open",
+    account_segment_marker: "g",
+};
+
+impl AccountLinkFormat {
+    // url patterns which should open in the given account
+    pub fn account_url_patterns(&self, account: &str) -> Vec<String> {
+        return vec![
+            // links which include the account, e.g "/g/<account>/t/<thread>"
+            format!(
+                "{}/{}/{}/**",
+                self.web_host, self.account_segment_marker, account
+            ),
+            // links without an account (e.g "/LINK") are opened in the chosen account
+            format!("{}/*", self.web_host),
+        ];
+    }
+
+    fn has_account(&self, path: &str) -> bool {
+        let account_prefix = format!("{}/", self.account_segment_marker);
+        return path.starts_with(account_prefix.as_str());
+    }
+}
+
+// "https://links.mimestream.com/g/madis@qminderapp.com/t/18f06bace4319301"
+// to "mimestream:///open/g/madis@qminderapp.com/t/18f06bace4319301"
+// "https://links.mimestream.com/LINK" to "mimestream:///open/g/<account>/LINK",
+// or to "mimestream:///open/LINK" if the account is empty.
+// The account of the link itself is kept, as the thread belongs to it.
+pub fn convert_account_uri(format: &AccountLinkFormat, account: &str, url: &Url) -> Option<String> {
+    if url.host_str() != Some(format.web_host) {
+        return None;
+    }
+
+    let path = url.path().trim_start_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+
+    let account_path = if account.is_empty() || format.has_account(path) {
+        path.to_string()
+    } else {
+        format!("{}/{}/{}", format.account_segment_marker, account, path)
+    };
+
+    let mut uri = format!(
+        "{}:///{}/{}",
+        format.app_scheme, format.app_path_prefix, account_path
+    );
+    if let Some(query) = url.query() {
+        uri = uri + "?" + query;
+    }
+    if let Some(fragment) = url.fragment() {
+        uri = uri + "#" + fragment;
+    }
+
+    return Some(uri);
+}
+
+// Recipients of a mailto: link, from both the path and the `to` header, e.g
+// "mailto:a@example.com,b@example.com?to=c@example.com"
+pub fn mailto_recipients(url: &Url) -> Vec<String> {
+    let mut recipients = split_addresses(percent_decode(url.path()).as_str());
+
+    for (key, value) in mailto_headers(url) {
+        if key == "to" {
+            recipients.extend(split_addresses(value.as_str()));
+        }
+    }
+
+    return recipients;
+}
+
+// Splits the first recipient into its local part and domain,
+// e.g "madis@qminderapp.com" to ("madis", "qminderapp.com")
+pub fn mailto_first_recipient_parts(url: &Url) -> Option<(String, String)> {
+    let recipients = mailto_recipients(url);
+    let first_recipient = recipients.first()?;
+
+    return first_recipient
+        .rsplit_once('@')
+        .map(|(local_part, domain)| (local_part.to_string(), domain.to_lowercase()));
+}
+
+// Value for Thunderbird `-compose` argument, e.g
+// "mailto:a@example.com?subject=Hello%20there&cc=b@example.com"
+// to "to='a@example.com',cc='b@example.com',subject='Hello there'"
+// "'", "," and "%" in values are url-encoded, e.g "subject='It%27s'"
+// see https://kb.mozillazine.org/Command_line_arguments_-_Thunderbird
+pub fn to_compose_fields(url: &Url) -> Option<String> {
+    if url.scheme() != "mailto" {
+        return None;
+    }
+
+    let headers = mailto_headers(url);
+    let header_values = |name: &str| -> Vec<String> {
+        headers
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+            .collect()
+    };
+
+    let mut fields: Vec<String> = Vec::new();
+
+    let recipients = mailto_recipients(url);
+    if !recipients.is_empty() {
+        fields.push(compose_field("to", &recipients));
+    }
+    for name in ["cc", "bcc"] {
+        let addresses: Vec<String> = header_values(name)
+            .iter()
+            .flat_map(|value| split_addresses(value.as_str()))
+            .collect();
+        if !addresses.is_empty() {
+            fields.push(compose_field(name, &addresses));
+        }
+    }
+    for name in ["subject", "body"] {
+        if let Some(value) = header_values(name).first() {
+            fields.push(compose_field(name, &[value.to_string()]));
+        }
+    }
+
+    return Some(fields.join(","));
+}
+
+// Characters which would end the quoted value or the field, and "%" itself,
+// as Thunderbird url-decodes the values
+const COMPOSE_VALUE_ENCODE_SET: &AsciiSet = &CONTROLS.add(b'%').add(b'\'').add(b',').add(b'"');
+
+// values are quoted, so that commas between multiple addresses are kept
+fn compose_field(name: &str, values: &[String]) -> String {
+    let encoded_values: Vec<String> = values
+        .iter()
+        .map(|value| utf8_percent_encode(value.as_str(), COMPOSE_VALUE_ENCODE_SET).to_string())
+        .collect();
+    return format!("{}='{}'", name, encoded_values.join(","));
+}
+
+// header names are case-insensitive
+fn mailto_headers(url: &Url) -> Vec<(String, String)> {
+    return url
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key).to_lowercase(), percent_decode(value)))
+        .collect();
+}
+
+fn split_addresses(addresses: &str) -> Vec<String> {
+    return addresses
+        .split(',')
+        .map(|address| address.trim())
+        .filter(|address| !address.is_empty())
+        .map(|address| address.to_string())
+        .collect();
+}
+
+// Unlike query strings in http urls, "+" is not a space in mailto: links (RFC 6068),
+// so the form_urlencoded decoding can't be used here
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex_maybe = input
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        if let Some(byte) = hex_maybe {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    return String::from_utf8_lossy(decoded.as_slice()).to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use crate::url_rule;
+
+    use super::*;
+
+    fn convert(url_str: &str) -> Option<String> {
+        let url = Url::parse(url_str).unwrap();
+        return convert_account_uri(&MIMESTREAM, "", &url);
+    }
+
+    fn convert_in_account(account: &str, url_str: &str) -> Option<String> {
+        let url = Url::parse(url_str).unwrap();
+        return convert_account_uri(&MIMESTREAM, account, &url);
+    }
+
+    fn account_matches(account: &str, url_str: &str) -> bool {
+        return MIMESTREAM
+            .account_url_patterns(account)
+            .iter()
+            .any(|pattern| {
+                url_rule::to_url_matcher(pattern)
+                    .to_glob_matcher()
+                    .url_str_matches(url_str)
+            });
+    }
+
+    #[test]
+    fn test_convert_account_thread_link() {
+        assert_eq!(
+            convert("https://links.mimestream.com/g/madis@qminderapp.com/t/18f06bace4319301"),
+            Some("mimestream:///open/g/madis@qminderapp.com/t/18f06bace4319301".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_link_without_account() {
+        assert_eq!(
+            convert("https://links.mimestream.com/LINK"),
+            Some("mimestream:///open/LINK".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_keeps_query() {
+        assert_eq!(
+            convert("https://links.mimestream.com/g/a@example.com/t/1?view=full"),
+            Some("mimestream:///open/g/a@example.com/t/1?view=full".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_rejects_other_hosts() {
+        assert_eq!(convert("https://mimestream.com/g/a@example.com/t/1"), None);
+        assert_eq!(convert("https://links.mimestream.com/"), None);
+    }
+
+    #[test]
+    fn test_convert_link_without_account_in_profile_account() {
+        assert_eq!(
+            convert_in_account("madis@qminderapp.com", "https://links.mimestream.com/LINK"),
+            Some("mimestream:///open/g/madis@qminderapp.com/LINK".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_keeps_account_of_link() {
+        assert_eq!(
+            convert_in_account(
+                "other@example.com",
+                "https://links.mimestream.com/g/a@example.com/t/1"
+            ),
+            Some("mimestream:///open/g/a@example.com/t/1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_account_url_patterns_match_only_own_account() {
+        let url = "https://links.mimestream.com/g/madis@qminderapp.com/t/18f06bace4319301";
+        assert!(account_matches("madis@qminderapp.com", url));
+        assert!(!account_matches("other@qminderapp.com", url));
+
+        // links without an account are fine to open in any account
+        assert!(account_matches(
+            "other@qminderapp.com",
+            "https://links.mimestream.com/LINK"
+        ));
+    }
+
+    fn compose(url_str: &str) -> Option<String> {
+        let url = Url::parse(url_str).unwrap();
+        return to_compose_fields(&url);
+    }
+
+    #[test]
+    fn test_compose_fields_for_single_recipient() {
+        assert_eq!(
+            compose("mailto:madis@qminderapp.com"),
+            Some("to='madis@qminderapp.com'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compose_fields_for_all_headers() {
+        assert_eq!(
+            compose(
+                "mailto:a@example.com,b@example.com?cc=c@example.com&BCC=d@example.com&subject=Hello%20there&body=1+1%3D2"
+            ),
+            Some(
+                "to='a@example.com,b@example.com',cc='c@example.com',bcc='d@example.com',subject='Hello there',body='1+1=2'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_compose_fields_encodes_quotes_and_commas() {
+        assert_eq!(
+            compose("mailto:a@example.com?subject=It's%20a%2C%20b&body=',to='evil@example.com%25"),
+            Some(
+                "to='a@example.com',subject='It%27s a%2C b',body='%27%2Cto=%27evil@example.com%25'"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_compose_fields_merges_to_header() {
+        assert_eq!(
+            compose("mailto:?to=a@example.com&to=b%40example.com"),
+            Some("to='a@example.com,b@example.com'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compose_fields_without_recipient() {
+        assert_eq!(compose("mailto:?subject=Hi"), Some("subject='Hi'".to_string()));
+        assert_eq!(compose("https://example.com"), None);
+    }
+
+    #[test]
+    fn test_mailto_first_recipient_parts() {
+        let url = Url::parse("mailto:Madis@QminderApp.com,b@example.com").unwrap();
+        assert_eq!(
+            mailto_first_recipient_parts(&url),
+            Some(("Madis".to_string(), "qminderapp.com".to_string()))
+        );
+
+        let url = Url::parse("mailto:?subject=Hi").unwrap();
+        assert_eq!(mailto_first_recipient_parts(&url), None);
+    }
+}
@@ -5,7 +5,7 @@ use rolling_file::{BasicRollingFileAppender, RollingConditionBasic};
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::{env, fs, thread};
-use tracing::{Level, info};
+use tracing::{Level, info, warn};
 use tracing_subscriber;
 use tracing_subscriber::fmt::time::OffsetTime;
 use tracing_subscriber::fmt::writer::MakeWriterExt;
@@ -13,7 +13,8 @@ use tracing_subscriber::fmt::writer::MakeWriterExt;
 use browsers::utils::OSAppFinder;
 use browsers::{
     MessageToMain, UrlOpenContext, generate_all_browser_profiles, get_opening_rules,
-    open_link_if_matching_rule, prepare_ui, unwrap_url, utils,
+    open_link_if_matching_rule, prepare_ui, print_launch_commands, submit_open_link_result,
+    unwrap_url, utils,
 };
 use browsers::{handle_messages_to_main, paths};
 
@@ -57,23 +58,38 @@ fn main() {
     let args: Vec<String> = env::args().collect();
     //info!("{:?}", args);
 
-    let mut url = "".to_string();
-    let url_input_maybe = args.iter().find(|i| i.starts_with("http"));
-    if let Some(url_input) = url_input_maybe {
-        url = url_input.to_string();
-    }
-
     let show_gui = !args.contains(&"--no-gui".to_string());
     let force_reload = args.contains(&"--reload".to_string());
+    // prints the commands which would be launched, without launching anything
+    let dry_run = args.contains(&"--dry-run".to_string());
 
     let (main_sender, main_receiver) = mpsc::channel::<MessageToMain>();
 
     let app_finder = OSAppFinder::new();
-    let config = app_finder.load_config();
-    let mut opening_rules_and_default_profile = get_opening_rules(&config);
+    let mut config = app_finder.load_config();
+
+    // e.g "https:", "mailto:", or any of the configured schemes (e.g "magnet:")
+    let handled_scheme_prefixes: Vec<String> = config
+        .get_handled_schemes()
+        .iter()
+        .map(|scheme| scheme.to_string() + ":")
+        .collect();
+
+    let mut url = "".to_string();
+    let url_input_maybe = args.iter().skip(1).find(|i| {
+        i.starts_with("http")
+            || handled_scheme_prefixes
+                .iter()
+                .any(|prefix| i.to_lowercase().starts_with(prefix.as_str()))
+    });
+    if let Some(url_input) = url_input_maybe {
+        url = url_input.to_string();
+    }
 
     let mut visible_and_hidden_profiles =
-        generate_all_browser_profiles(&config, &app_finder, force_reload);
+        generate_all_browser_profiles(&mut config, &app_finder, force_reload, dry_run);
+
+    let mut opening_rules_and_default_profile = get_opening_rules(&config);
 
     let behavioral_settings = config.get_behavior();
     // TODO: url should not be considered here in case of macos
@@ -85,14 +101,26 @@ fn main() {
         source_app_maybe: None,
     };
 
-    if open_link_if_matching_rule(
+    if dry_run {
+        print_launch_commands(
+            &url_open_context,
+            &opening_rules_and_default_profile,
+            &visible_and_hidden_profiles,
+        );
+        return;
+    }
+
+    let launch_error_maybe = match open_link_if_matching_rule(
         &url_open_context,
         &opening_rules_and_default_profile,
         &visible_and_hidden_profiles,
+        behavioral_settings,
     ) {
         // opened in a browser because of an opening rule, so we are done here
-        return;
-    }
+        Ok(true) => return,
+        Ok(false) => None,
+        Err(error) => Some(error),
+    };
 
     let is_default = utils::is_default_web_browser();
     let show_set_as_default = !is_default;
@@ -106,6 +134,9 @@ fn main() {
     );
 
     if !show_gui {
+        if let Some(launch_error) = launch_error_maybe {
+            warn!("{}", launch_error);
+        }
         ui.print_visible_options();
         return;
     }
@@ -113,6 +144,10 @@ fn main() {
     let launcher = ui.create_app_launcher();
     let ui_event_sink = launcher.get_external_handle();
 
+    if let Some(launch_error) = launch_error_maybe {
+        submit_open_link_result(&ui_event_sink, Err(launch_error));
+    }
+
     thread::spawn(move || {
         handle_messages_to_main(
             main_receiver,
@@ -119,6 +119,21 @@ pub fn get_snap_root() -> PathBuf {
     return PathBuf::new();
 }
 
+#[cfg(target_os = "macos")]
+pub fn get_flatpak_root() -> PathBuf {
+    return PathBuf::new();
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_flatpak_root() -> PathBuf {
+    return linux_utils::get_flatpak_root_dir();
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_flatpak_root() -> PathBuf {
+    return PathBuf::new();
+}
+
 pub fn get_app_icon_path() -> PathBuf {
     return get_resources_basedir().join("icons/512x512/software.Browsers.png");
 }
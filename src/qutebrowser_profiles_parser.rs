@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::Path;
+
+use crate::InstalledBrowserProfile;
+
+// qutebrowser has no profiles of its own, separate instances are started with `--basedir <dir>`;
+// profile managers like qbpm keep each basedir in the same root, e.g
+// ~/.local/share/qutebrowser-profiles/<profile name>/{config,data,...}
+// The default instance (without `--basedir`) is always listed as well.
+pub fn find_qutebrowser_profiles(
+    qutebrowser_basedirs_root: &Path,
+    _binary_path: &Path,
+    _app_id: &str,
+) -> Vec<InstalledBrowserProfile> {
+    let mut browser_profiles: Vec<InstalledBrowserProfile> = Vec::new();
+    browser_profiles.push(qutebrowser_profile("", "Default"));
+
+    let entries = match fs::read_dir(qutebrowser_basedirs_root) {
+        Ok(entries) => entries,
+        Err(_) => return browser_profiles,
+    };
+
+    for entry in entries.flatten() {
+        let basedir = entry.path();
+        if !is_qutebrowser_basedir(basedir.as_path()) {
+            continue;
+        }
+
+        // the basedir is given as an argument, so it must be valid UTF-8
+        let basedir_str = match basedir.to_str() {
+            Some(basedir_str) => basedir_str,
+            None => continue,
+        };
+        let profile_name = entry.file_name().to_string_lossy().to_string();
+        browser_profiles.push(qutebrowser_profile(basedir_str, profile_name.as_str()));
+    }
+
+    return browser_profiles;
+}
+
+// basedir has the same layout as the default instance, with at least the config dir
+fn is_qutebrowser_basedir(dir: &Path) -> bool {
+    return dir.join("config").is_dir();
+}
+
+fn qutebrowser_profile(basedir: &str, profile_name: &str) -> InstalledBrowserProfile {
+    return InstalledBrowserProfile {
+        profile_cli_arg_value: basedir.to_string(),
+        profile_cli_container_name: None,
+        profile_name: profile_name.to_string(),
+        profile_icon: None,
+        profile_restricted_url_patterns: vec![],
+        profile_email: None,
+        profile_hosted_domain: None,
+        profile_enterprise_domain: None,
+        profile_last_active: None,
+        profile_legacy_cli_arg_value: None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils;
+
+    #[test]
+    fn test_find_qutebrowser_profiles() {
+        let temp_dir = test_utils::create_temp_dir("qutebrowser-profiles");
+        let dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(dir.join("work").join("config")).unwrap();
+        fs::create_dir_all(dir.join("work").join("data")).unwrap();
+        fs::create_dir_all(dir.join("personal").join("config")).unwrap();
+        // not a basedir
+        fs::create_dir_all(dir.join("backups")).unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let binary_path = Path::new("/usr/bin/qutebrowser");
+        let mut profiles =
+            find_qutebrowser_profiles(dir.as_path(), binary_path, "org.qutebrowser.qutebrowser");
+        profiles.sort_by_key(|p| p.profile_name.clone());
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Default", "personal", "work"]);
+        assert_eq!(profiles[0].profile_cli_arg_value, "");
+        assert_eq!(
+            profiles[2].profile_cli_arg_value,
+            dir.join("work").to_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_find_qutebrowser_profiles_without_basedirs_root() {
+        let temp_dir = test_utils::create_temp_dir("qutebrowser-profiles");
+        let dir = temp_dir.path().join("qutebrowser-profiles");
+
+        let binary_path = Path::new("/usr/bin/qutebrowser");
+        let profiles =
+            find_qutebrowser_profiles(dir.as_path(), binary_path, "org.qutebrowser.qutebrowser");
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Default"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_qutebrowser_profiles_skips_non_utf8_basedir() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = test_utils::create_temp_dir("qutebrowser-profiles");
+        let dir = temp_dir.path().to_path_buf();
+        fs::create_dir_all(dir.join("work").join("config")).unwrap();
+        let non_utf8_basedir = dir.join(OsStr::from_bytes(b"caf\xe9"));
+        if fs::create_dir_all(non_utf8_basedir.join("config")).is_err() {
+            // e.g filesystems which only allow UTF-8 names
+            return;
+        }
+
+        let binary_path = Path::new("/usr/bin/qutebrowser");
+        let mut profiles =
+            find_qutebrowser_profiles(dir.as_path(), binary_path, "org.qutebrowser.qutebrowser");
+        profiles.sort_by_key(|p| p.profile_name.clone());
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Default", "work"]);
+    }
+}
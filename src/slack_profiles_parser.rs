@@ -6,7 +6,7 @@ use std::path::Path;
 use serde_json::{Map, Value};
 use tracing::{debug, info};
 
-use crate::{paths, InstalledBrowserProfile};
+use crate::{InstalledBrowserProfile, paths};
 
 pub fn find_slack_profiles(
     slack_user_dir: &Path,
@@ -55,30 +55,44 @@ pub fn find_slack_profiles(
         };*/
         let profile_icon_path = None;
 
-        let workspace_url_pattern = format!("{}.slack.com", workspace.domain.as_str());
-        let enterprise_workspace_url_pattern =
-            format!("{}.enterprise.slack.com", workspace.domain.as_str());
-        let slack_gov_workspace_url_pattern =
-            format!("{}.slack-gov.com", workspace.domain.as_str());
-
-        let app_url_pattern = format!("app.slack.com/**/{}/**", workspace.id.as_str());
+        let profile_restricted_url_patterns = workspace_url_patterns(&workspace);
 
         browser_profiles.push(InstalledBrowserProfile {
             profile_cli_arg_value: workspace.id.to_string(),
             profile_cli_container_name: Some(workspace.domain.to_string()),
             profile_name: workspace.name,
             profile_icon: profile_icon_path,
-            profile_restricted_url_patterns: vec![
-                workspace_url_pattern,
-                //enterprise_workspace_url_pattern,
-                //slack_gov_workspace_url_pattern,
-                //app_url_pattern,
-            ],
+            profile_restricted_url_patterns: profile_restricted_url_patterns,
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: workspace.enterprise_domain,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: None,
         })
     }
     return browser_profiles;
 }
 
+// url patterns which should open in this workspace
+fn workspace_url_patterns(workspace: &SlackWorkspace) -> Vec<String> {
+    let mut patterns = vec![
+        format!("{}.slack.com", workspace.domain.as_str()),
+        format!("{}.enterprise.slack.com", workspace.domain.as_str()),
+        format!("{}.slack-gov.com", workspace.domain.as_str()),
+        format!("app.slack.com/**/{}/**", workspace.id.as_str()),
+    ];
+
+    // Enterprise Grid workspaces are also reachable via the organization domain and id
+    if let Some(ref enterprise_domain) = workspace.enterprise_domain {
+        patterns.push(format!("{}.enterprise.slack.com", enterprise_domain));
+    }
+    if let Some(ref enterprise_id) = workspace.enterprise_id {
+        patterns.push(format!("app.slack.com/**/{}/**", enterprise_id));
+    }
+
+    return patterns;
+}
+
 pub struct SlackWorkspacesMap {
     workspaces_map: Map<String, Value>,
 }
@@ -90,11 +104,21 @@ impl SlackWorkspacesMap {
         }
     }
 
+    fn new_from_root_state_value(root_state: &Value) -> Self {
+        Self {
+            workspaces_map: Self::workspaces_map_from_value(root_state),
+        }
+    }
+
     fn workspaces_map(local_state_file_path: &Path) -> Map<String, Value> {
         // Open the file in read-only mode with buffer.
         let file = File::open(local_state_file_path).unwrap();
         let reader = BufReader::new(file);
         let v: Value = serde_json::from_reader(reader).unwrap();
+        return Self::workspaces_map_from_value(&v);
+    }
+
+    fn workspaces_map_from_value(v: &Value) -> Map<String, Value> {
         let workspaces = &v["workspaces"];
         let workspaces_map = workspaces.as_object().unwrap();
         return workspaces_map.to_owned();
@@ -118,12 +142,18 @@ impl SlackWorkspacesMap {
             let image_68 = icon["image_68"].as_str().unwrap_or("").to_string();
             let image_88 = icon["image_88"].as_str().unwrap_or("").to_string();
 
+            // only set for Enterprise Grid workspaces
+            let enterprise_id = non_empty_str(&workspace["enterprise_id"]);
+            let enterprise_domain = non_empty_str(&workspace["enterprise_domain"]);
+
             profiles_vec.push(SlackWorkspace {
                 domain: domain,
                 id: id,
                 name: name,
                 icon_68: image_68,
                 icon_88: image_88,
+                enterprise_id: enterprise_id,
+                enterprise_domain: enterprise_domain,
             });
         }
         // constant ordering (well based on name)
@@ -132,12 +162,21 @@ impl SlackWorkspacesMap {
     }
 }
 
+fn non_empty_str(value: &Value) -> Option<String> {
+    return value
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string());
+}
+
 pub struct SlackWorkspace {
     pub domain: String,
     pub id: String,
     pub name: String,
     pub icon_68: String,
     pub icon_88: String,
+    pub enterprise_id: Option<String>,
+    pub enterprise_domain: Option<String>,
 }
 
 /*
@@ -161,6 +200,125 @@ pub struct SlackWorkspace {
      },
      "name": "Browsers",
      "order": 1
+   },
+   "T02GRIDWS1": {
+     "domain": "acme-eng",
+     "id": "T02GRIDWS1",
+     "icon": { ... },
+     "name": "Acme Engineering",
+     "enterprise_id": "E01ACMEORG",
+     "enterprise_domain": "acme",
+     "enterprise_name": "Acme",
+     "order": 2
    }
  },
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::url_rule;
+
+    const ROOT_STATE_JSON: &str = r#"{
+      "workspaces": {
+        "T05ALPT0XU6": {
+          "domain": "browsersgroup",
+          "id": "T05ALPT0XU6",
+          "icon": { "image_68": "", "image_88": "" },
+          "name": "Browsers",
+          "order": 0
+        },
+        "T02GRIDWS1": {
+          "domain": "acme-eng",
+          "id": "T02GRIDWS1",
+          "icon": { "image_68": "", "image_88": "" },
+          "name": "Acme Engineering",
+          "enterprise_id": "E01ACMEORG",
+          "enterprise_domain": "acme",
+          "order": 1
+        }
+      }
+    }"#;
+
+    fn parse_workspaces() -> Vec<SlackWorkspace> {
+        let root_state: Value = serde_json::from_str(ROOT_STATE_JSON).unwrap();
+        return SlackWorkspacesMap::new_from_root_state_value(&root_state).parse_profiles();
+    }
+
+    fn matches_any(patterns: &Vec<String>, url: &str) -> bool {
+        return patterns.iter().any(|pattern| {
+            url_rule::to_url_matcher(pattern.as_str())
+                .to_glob_matcher()
+                .url_str_matches(url)
+        });
+    }
+
+    #[test]
+    fn test_parse_profiles_reads_enterprise_ids() {
+        let workspaces = parse_workspaces();
+        assert_eq!(workspaces.len(), 2);
+
+        let grid = &workspaces[0];
+        assert_eq!(grid.name, "Acme Engineering");
+        assert_eq!(grid.enterprise_id, Some("E01ACMEORG".to_string()));
+        assert_eq!(grid.enterprise_domain, Some("acme".to_string()));
+
+        let regular = &workspaces[1];
+        assert_eq!(regular.name, "Browsers");
+        assert_eq!(regular.enterprise_id, None);
+        assert_eq!(regular.enterprise_domain, None);
+    }
+
+    #[test]
+    fn test_workspace_url_patterns_match_workspace_hosts() {
+        let workspaces = parse_workspaces();
+        let patterns = workspace_url_patterns(&workspaces[1]);
+
+        assert!(matches_any(
+            &patterns,
+            "https://browsersgroup.slack.com/archives/C1"
+        ));
+        assert!(matches_any(
+            &patterns,
+            "https://browsersgroup.enterprise.slack.com/archives/C1"
+        ));
+        assert!(matches_any(
+            &patterns,
+            "https://browsersgroup.slack-gov.com/archives/C1"
+        ));
+        assert!(matches_any(
+            &patterns,
+            "https://app.slack.com/client/T05ALPT0XU6/C1"
+        ));
+
+        assert!(!matches_any(&patterns, "https://other.slack.com/archives/C1"));
+        assert!(!matches_any(
+            &patterns,
+            "https://app.slack.com/client/T02GRIDWS1/C1"
+        ));
+    }
+
+    #[test]
+    fn test_workspace_url_patterns_match_enterprise_organization() {
+        let workspaces = parse_workspaces();
+        let patterns = workspace_url_patterns(&workspaces[0]);
+
+        assert!(matches_any(
+            &patterns,
+            "https://acme.enterprise.slack.com/archives/C1"
+        ));
+        assert!(matches_any(
+            &patterns,
+            "https://app.slack.com/client/E01ACMEORG/C1"
+        ));
+        assert!(matches_any(
+            &patterns,
+            "https://app.slack.com/client/T02GRIDWS1/C1"
+        ));
+
+        assert!(!matches_any(
+            &patterns,
+            "https://other.enterprise.slack.com/archives/C1"
+        ));
+    }
+}
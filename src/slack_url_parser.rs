@@ -1,7 +1,12 @@
 use tracing::info;
 use url::Url;
 
-pub fn convert_slack_uri(profile_team_id: &str, profile_team_domain: &str, url: &Url) -> String {
+pub fn convert_slack_uri(
+    profile_team_id: &str,
+    profile_team_domain: &str,
+    profile_enterprise_domain_maybe: Option<&str>,
+    url: &Url,
+) -> String {
     let unknown = format!("slack://channel?team={}", profile_team_id);
 
     let url_host_str = (&url.host_str().unwrap()).to_string();
@@ -14,110 +19,392 @@ pub fn convert_slack_uri(profile_team_id: &str, profile_team_domain: &str, url:
 
     // https://slack.com/help/articles/221769328-Locate-your-Slack-URL
     // https://api.slack.com/reference/deep-linking#supported_URIs
-    return if url_host_str == format!("{}.slack.com", profile_team_domain) {
+    return if url_host_str == "app.slack.com" {
+        // https://app.slack.com/client/...
+        info!("Domain is Slack web client");
+
+        url_path_segments_maybe
+            .and_then(|segments| convert_app_client_path(profile_team_id, &segments))
+            .unwrap_or(unknown)
+    } else if is_workspace_host(
+        url_host_str.as_str(),
+        profile_team_domain,
+        profile_enterprise_domain_maybe,
+    ) {
+        // https://mycompany.slack.com/...
+        // https://mycompany.enterprise.slack.com/...
+        // https://mycompany.slack-gov.com/...
+        // https://mycompany-org.enterprise.slack.com/...
         info!("Domain matches Slack profile");
 
-        // Slack commands:
-        //   channel
-        //   file
-        //   team
-
-        // Slack query params:
-        //      id: ([CDG][A-Z0-9]{8,})
-        //    team: (T[A-Z0-9]{8,})
-        // message: ([0-9]+\.[0-9]+)
-
-        // Team host:
-        //    Canvas:          https://<team-domain-name>.slack.com/docs/<ignored_id>/<doc_id>
-        //    File:            https://<team-domain-name>.slack.com/messages/<ignored_id>/files/<file_id>
-        //    Channel:         https://<team-domain-name>.slack.com/archives/<channel_id>
-        //    Channel message: https://<team-domain-name>.slack.com/archives/<channel_id>/p<timestamp_without_decimal>
-        //    User:            https://<team-domain-name>.slack.com/team/<user_id>
-        //    User?:           https://<team-domain-name>.slack.com/messages/<ignored_id>/team/<user_id>
-
-        if url_path_segments_maybe.is_some() {
-            let segments = url_path_segments_maybe.unwrap();
-            let resource_type_maybe = segments.get(0);
-            let resource_id_maybe = segments.get(1);
-
-            let uri_maybe =
-                resource_type_maybe
-                    .zip(resource_id_maybe)
-                    .map(|(resource_type, resource_id)| {
-                        let subresource_id_maybe = segments.get(2);
-
-                        match (resource_type.as_str(), subresource_id_maybe) {
-                            ("docs", Some(doc_id)) => {
-                                // Canvas; resource_id: team id; subresource_id: doc id
-                                // From: https://<team-domain>.slack.com/docs/<team-id>/<resource_id>
-                                //   To: slack://doc?team=<team-id>&id=<resource_id>
-                                //        &user_id=???
-                                format!("slack://doc?team={}&id={}", profile_team_id, doc_id)
-                            }
-                            ("team", _) => {
-                                // User; resource_id: user id
-                                // From: https://<team-domain>.slack.com/team/<resource_id>
-                                //   To: slack://team?team=<team-id>&id=<resource_id>
-                                format!("slack://team?team={}&id={}", profile_team_id, resource_id)
-                            }
-                            ("files", Some(file_id)) => {
-                                // File; resource_id: user id; subresource_id: file id
-                                // From https://<team-domain>.slack.com/files/<resource_id>/<file-id>/<filename>
-                                //   To slack://file?team=<team-id>&id=<file-id>
-                                format!("slack://file?team={}&id={}", resource_id, file_id)
-                            }
-                            ("archives", Some(message_id)) => {
-                                let query_pairs = url.query_pairs();
-                                let thread_ts_maybe: Option<String> = query_pairs
-                                    .into_iter()
-                                    .find(|(key, _)| key == "thread_ts")
-                                    .map(|(_, value)| value.to_string());
-
-                                match thread_ts_maybe {
-                                    // Channel thread message; resource_id: channel id; subresource_id: message id
-                                    // From https://<team-domain>.slack.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399&cid=C05BH52KSC8
-                                    //   To slack://channel?team=<team-id>&id=<resource_id>&message=1686336166.083089&thread_ts=1686232159.321829
-                                    Some(thread_ts) => format!(
-                                        "slack://channel?team={}&id={}&message={}&thread_ts={}",
-                                        profile_team_id, resource_id, message_id, thread_ts
-                                    ),
-                                    // Channel Message; resource_id: channel id; subresource_id: message id
-                                    // From https://<team-domain>.slack.com/archives/<resource_id>/p1647522989096739
-                                    //   To slack://channel?team=<team-id>&id=<resource_id>&message=1647522989.096739
-                                    None => format!(
-                                        "slack://channel?team={}&id={}&message={}",
-                                        profile_team_id, resource_id, message_id
-                                    ),
-                                }
-                            }
-                            ("archives", None) => {
-                                // Channel; resource_id: channel id
-                                // From https://<team-domain>.slack.com/archives/<channel_id>
-                                //   To slack://channel?team=<team-id>&id=<channel_id>
-                                format!(
-                                    "slack://channel?team={}&id={}",
-                                    profile_team_id, resource_id
-                                )
-                            }
-                            _ => unknown.clone(),
+        url_path_segments_maybe
+            .and_then(|segments| convert_workspace_path(profile_team_id, &segments, url))
+            .unwrap_or(unknown)
+    } else {
+        unknown
+    };
+}
+
+// Workspace hosts all share the same path structure, and so does the Enterprise Grid
+// organization host of the workspace (channel ids are unique in the whole organization)
+fn is_workspace_host(
+    url_host_str: &str,
+    profile_team_domain: &str,
+    profile_enterprise_domain_maybe: Option<&str>,
+) -> bool {
+    let is_organization_host = profile_enterprise_domain_maybe
+        .map(|enterprise_domain| {
+            url_host_str == format!("{}.enterprise.slack.com", enterprise_domain)
+        })
+        .unwrap_or(false);
+
+    return is_organization_host
+        || url_host_str == format!("{}.slack.com", profile_team_domain)
+        || url_host_str == format!("{}.enterprise.slack.com", profile_team_domain)
+        || url_host_str == format!("{}.slack-gov.com", profile_team_domain);
+}
+
+// "p1686336166083089" to "1686336166.083089"
+fn to_message_ts(message_id: &str) -> Option<String> {
+    let digits = message_id.strip_prefix('p')?;
+    if digits.len() <= 6 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (seconds, micros) = digits.split_at(digits.len() - 6);
+    return Some(format!("{}.{}", seconds, micros));
+}
+
+fn convert_workspace_path(
+    profile_team_id: &str,
+    segments: &Vec<String>,
+    url: &Url,
+) -> Option<String> {
+    // Slack commands:
+    //   channel
+    //   file
+    //   team
+
+    // Slack query params:
+    //      id: ([CDG][A-Z0-9]{8,})
+    //    team: (T[A-Z0-9]{8,})
+    // message: ([0-9]+\.[0-9]+)
+
+    // Team host:
+    //    Canvas:          https://<team-domain-name>.slack.com/docs/<ignored_id>/<doc_id>
+    //    File:            https://<team-domain-name>.slack.com/messages/<ignored_id>/files/<file_id>
+    //    Channel:         https://<team-domain-name>.slack.com/archives/<channel_id>
+    //    Channel message: https://<team-domain-name>.slack.com/archives/<channel_id>/p<timestamp_without_decimal>
+    //    User:            https://<team-domain-name>.slack.com/team/<user_id>
+    //    User?:           https://<team-domain-name>.slack.com/messages/<ignored_id>/team/<user_id>
+
+    let resource_type_maybe = segments.get(0);
+    let resource_id_maybe = segments.get(1);
+
+    let uri_maybe =
+        resource_type_maybe
+            .zip(resource_id_maybe)
+            .and_then(|(resource_type, resource_id)| {
+                let subresource_id_maybe = segments.get(2);
+
+                match (resource_type.as_str(), subresource_id_maybe) {
+                    ("docs", Some(doc_id)) => {
+                        // Canvas; resource_id: team id; subresource_id: doc id
+                        // From: https://<team-domain>.slack.com/docs/<team-id>/<resource_id>
+                        //   To: slack://doc?team=<team-id>&id=<resource_id>
+                        //        &user_id=???
+                        Some(format!("slack://doc?team={}&id={}", profile_team_id, doc_id))
+                    }
+                    ("team", _) => {
+                        // User; resource_id: user id
+                        // From: https://<team-domain>.slack.com/team/<resource_id>
+                        //   To: slack://team?team=<team-id>&id=<resource_id>
+                        Some(format!(
+                            "slack://team?team={}&id={}",
+                            profile_team_id, resource_id
+                        ))
+                    }
+                    ("files", Some(file_id)) => {
+                        // File; resource_id: user id; subresource_id: file id
+                        // From https://<team-domain>.slack.com/files/<resource_id>/<file-id>/<filename>
+                        //   To slack://file?team=<team-id>&id=<file-id>
+                        Some(format!("slack://file?team={}&id={}", resource_id, file_id))
+                    }
+                    ("archives", Some(message_id)) => {
+                        let message_ts = to_message_ts(message_id)?;
+                        let query_pairs = url.query_pairs();
+                        let thread_ts_maybe: Option<String> = query_pairs
+                            .into_iter()
+                            .find(|(key, _)| key == "thread_ts")
+                            .map(|(_, value)| value.to_string());
+
+                        match thread_ts_maybe {
+                            // Channel thread message; resource_id: channel id; subresource_id: message id
+                            // From https://<team-domain>.slack.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399&cid=C05BH52KSC8
+                            //   To slack://channel?team=<team-id>&id=<resource_id>&message=1686336166.083089&thread_ts=1686232159.321829
+                            Some(thread_ts) => Some(format!(
+                                "slack://channel?team={}&id={}&message={}&thread_ts={}",
+                                profile_team_id, resource_id, message_ts, thread_ts
+                            )),
+                            // Channel Message; resource_id: channel id; subresource_id: message id
+                            // From https://<team-domain>.slack.com/archives/<resource_id>/p1647522989096739
+                            //   To slack://channel?team=<team-id>&id=<resource_id>&message=1647522989.096739
+                            None => Some(format!(
+                                "slack://channel?team={}&id={}&message={}",
+                                profile_team_id, resource_id, message_ts
+                            )),
                         }
-                    });
+                    }
+                    ("archives", None) => {
+                        // Channel; resource_id: channel id
+                        // From https://<team-domain>.slack.com/archives/<channel_id>
+                        //   To slack://channel?team=<team-id>&id=<channel_id>
+                        Some(format!(
+                            "slack://channel?team={}&id={}",
+                            profile_team_id, resource_id
+                        ))
+                    }
+                    _ => None,
+                }
+            });
 
-            let slack_protocol_url = uri_maybe.unwrap_or(unknown.clone());
-            return slack_protocol_url;
-        }
+    return uri_maybe;
+}
 
-        format!("slack://channel?team={}", profile_team_id)
-    } else if url_host_str == format!("{}.slack-gov.com", profile_team_domain) {
-        // https://mycompany.slack-gov.com/...
-        format!("slack://channel?team={}", profile_team_id)
-    } else if url_host_str == format!("{}.enterprise.slack.com", profile_team_domain) {
-        // https://mycompany.enterprise.slack.com/...
-        format!("slack://channel?team={}", profile_team_id)
-    } else if url_host_str == "app.slack.com" {
-        // https://app.slack.com/client/...
-        format!("slack://channel?team={}", profile_team_id)
-    } else {
-        format!("slack://channel?team={}", profile_team_id)
+fn convert_app_client_path(profile_team_id: &str, segments: &Vec<String>) -> Option<String> {
+    // Web client host (team id can also be the Enterprise Grid organization id E...):
+    //    Channel:        https://app.slack.com/client/<team_id>/<channel_id>
+    //    Channel thread: https://app.slack.com/client/<team_id>/<channel_id>/thread/<channel_id>-<thread_ts>
+    //    User:           https://app.slack.com/client/<team_id>/<channel_id>/user_profile/<user_id>
+    //    User:           https://app.slack.com/client/<team_id>/user_profile/<user_id>
+
+    let segments: Vec<&str> = segments
+        .iter()
+        .map(|s| s.as_str())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if segments.first() != Some(&"client") {
+        return None;
+    }
+
+    return match segments.as_slice() {
+        [_, _team_id, "user_profile", user_id, ..]
+        | [_, _team_id, _, "user_profile", user_id, ..] => {
+            // From https://app.slack.com/client/<team_id>/<channel_id>/user_profile/<user_id>
+            //   To slack://team?team=<team-id>&id=<user_id>
+            Some(format!("slack://team?team={}&id={}", profile_team_id, user_id))
+        }
+        [_, _team_id, channel_id, "thread", thread, ..] => {
+            // From https://app.slack.com/client/<team_id>/<channel_id>/thread/<channel_id>-1686336161.925399
+            //   To slack://channel?team=<team-id>&id=<channel_id>&message=1686336161.925399&thread_ts=1686336161.925399
+            let thread_ts = thread
+                .split_once('-')
+                .map(|(_, thread_ts)| thread_ts)
+                .unwrap_or(thread);
+            Some(format!(
+                "slack://channel?team={}&id={}&message={}&thread_ts={}",
+                profile_team_id, channel_id, thread_ts, thread_ts
+            ))
+        }
+        [_, _team_id, channel_id, ..] => {
+            // From https://app.slack.com/client/<team_id>/<channel_id>
+            //   To slack://channel?team=<team-id>&id=<channel_id>
+            Some(format!(
+                "slack://channel?team={}&id={}",
+                profile_team_id, channel_id
+            ))
+        }
+        _ => None,
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(url_str: &str) -> String {
+        let url = Url::parse(url_str).unwrap();
+        return convert_slack_uri("T0123ABCD", "acme", Some("acme-corp"), &url);
+    }
+
+    #[test]
+    fn test_workspace_channel() {
+        assert_eq!(
+            convert("https://acme.slack.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_workspace_thread() {
+        assert_eq!(
+            convert(
+                "https://acme.slack.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399&cid=C05BH52KSC8"
+            ),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1686336166.083089&thread_ts=1686336161.925399"
+        );
+    }
+
+    #[test]
+    fn test_workspace_user() {
+        assert_eq!(
+            convert("https://acme.slack.com/team/U0456EFGH"),
+            "slack://team?team=T0123ABCD&id=U0456EFGH"
+        );
+    }
+
+    #[test]
+    fn test_enterprise_channel() {
+        assert_eq!(
+            convert("https://acme.enterprise.slack.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_enterprise_thread() {
+        assert_eq!(
+            convert(
+                "https://acme.enterprise.slack.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399"
+            ),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1686336166.083089&thread_ts=1686336161.925399"
+        );
+    }
+
+    #[test]
+    fn test_enterprise_user() {
+        assert_eq!(
+            convert("https://acme.enterprise.slack.com/team/U0456EFGH"),
+            "slack://team?team=T0123ABCD&id=U0456EFGH"
+        );
+    }
+
+    #[test]
+    fn test_enterprise_organization_host_channel() {
+        assert_eq!(
+            convert("https://acme-corp.enterprise.slack.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_enterprise_organization_host_thread() {
+        assert_eq!(
+            convert(
+                "https://acme-corp.enterprise.slack.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399"
+            ),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1686336166.083089&thread_ts=1686336161.925399"
+        );
+    }
+
+    #[test]
+    fn test_other_enterprise_organization_host_is_unknown() {
+        assert_eq!(
+            convert("https://other-corp.enterprise.slack.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD"
+        );
+        let url =
+            Url::parse("https://acme-corp.enterprise.slack.com/archives/C05BH52KSC8").unwrap();
+        assert_eq!(
+            convert_slack_uri("T0123ABCD", "acme", None, &url),
+            "slack://channel?team=T0123ABCD"
+        );
+    }
+
+    #[test]
+    fn test_workspace_message() {
+        assert_eq!(
+            convert("https://acme.slack.com/archives/C05BH52KSC8/p1647522989096739"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1647522989.096739"
+        );
+    }
+
+    #[test]
+    fn test_workspace_invalid_message_is_unknown() {
+        assert_eq!(
+            convert("https://acme.slack.com/archives/C05BH52KSC8/p12345"),
+            "slack://channel?team=T0123ABCD"
+        );
+    }
+
+    #[test]
+    fn test_gov_channel() {
+        assert_eq!(
+            convert("https://acme.slack-gov.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_gov_thread() {
+        assert_eq!(
+            convert(
+                "https://acme.slack-gov.com/archives/C05BH52KSC8/p1686336166083089?thread_ts=1686336161.925399"
+            ),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1686336166.083089&thread_ts=1686336161.925399"
+        );
+    }
+
+    #[test]
+    fn test_gov_user() {
+        assert_eq!(
+            convert("https://acme.slack-gov.com/team/U0456EFGH"),
+            "slack://team?team=T0123ABCD&id=U0456EFGH"
+        );
+    }
+
+    #[test]
+    fn test_gov_other_workspace_is_unknown() {
+        assert_eq!(
+            convert("https://other.slack-gov.com/archives/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD"
+        );
+    }
+
+    #[test]
+    fn test_app_client_channel() {
+        assert_eq!(
+            convert("https://app.slack.com/client/T0123ABCD/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_app_client_enterprise_channel() {
+        assert_eq!(
+            convert("https://app.slack.com/client/E0789IJKL/C05BH52KSC8"),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8"
+        );
+    }
+
+    #[test]
+    fn test_app_client_thread() {
+        assert_eq!(
+            convert(
+                "https://app.slack.com/client/T0123ABCD/C05BH52KSC8/thread/C05BH52KSC8-1686336161.925399"
+            ),
+            "slack://channel?team=T0123ABCD&id=C05BH52KSC8&message=1686336161.925399&thread_ts=1686336161.925399"
+        );
+    }
+
+    #[test]
+    fn test_app_client_user() {
+        assert_eq!(
+            convert("https://app.slack.com/client/T0123ABCD/C05BH52KSC8/user_profile/U0456EFGH"),
+            "slack://team?team=T0123ABCD&id=U0456EFGH"
+        );
+        assert_eq!(
+            convert("https://app.slack.com/client/T0123ABCD/user_profile/U0456EFGH"),
+            "slack://team?team=T0123ABCD&id=U0456EFGH"
+        );
+    }
+
+    #[test]
+    fn test_app_client_without_channel() {
+        assert_eq!(
+            convert("https://app.slack.com/client/T0123ABCD"),
+            "slack://channel?team=T0123ABCD"
+        );
+    }
+}
@@ -0,0 +1,11 @@
+use tempfile::TempDir;
+
+// Directory for the fixtures of a test, unique per call, so tests (and test runs) don't collide.
+// It's removed when the returned TempDir is dropped, so keep it around for the whole test.
+pub fn create_temp_dir(test_name: &str) -> TempDir {
+    let prefix = format!("browsers-test-{}-", test_name);
+    return tempfile::Builder::new()
+        .prefix(prefix.as_str())
+        .tempdir()
+        .unwrap();
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use crate::{InstalledBrowserProfile, firefox_profiles_parser};
+
+// Thunderbird uses the same profiles.ini as Firefox, e.g
+// ~/.thunderbird/profiles.ini
+// ~/Library/Thunderbird/profiles.ini
+// %APPDATA%\Thunderbird\profiles.ini
+pub fn find_thunderbird_profiles(
+    thunderbird_profiles_dir: &Path,
+    binary_path: &Path,
+    _app_id: &str,
+) -> Vec<InstalledBrowserProfile> {
+    let profiles_ini_profiles =
+        firefox_profiles_parser::find_profiles_ini_profiles(thunderbird_profiles_dir, binary_path);
+
+    return profiles_ini_profiles
+        .into_iter()
+        .map(|profiles_ini_profile| InstalledBrowserProfile {
+            profile_cli_arg_value: profiles_ini_profile.name.to_string(),
+            profile_cli_container_name: None,
+            profile_name: profiles_ini_profile.name,
+            profile_icon: None,
+            profile_restricted_url_patterns: vec![],
+            profile_email: None,
+            profile_hosted_domain: None,
+            profile_enterprise_domain: None,
+            profile_last_active: None,
+            profile_legacy_cli_arg_value: None,
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_utils;
+
+    const PROFILES_INI: &str = r#"[Profile1]
+Name=Personal
+IsRelative=1
+Path=Profiles/abcd1234.Personal
+
+[Profile0]
+Name=Work
+IsRelative=1
+Path=Profiles/efgh5678.default-release
+Default=1
+
+[Profile2]
+Name=Removed
+IsRelative=1
+Path=Profiles/ijkl9012.Removed
+
+[General]
+StartWithLastProfile=1
+Version=2
+
+[InstallFDC34C9F024745EB]
+Default=Profiles/efgh5678.default-release
+Locked=1
+"#;
+
+    #[test]
+    fn test_find_thunderbird_profiles() {
+        let temp_dir = test_utils::create_temp_dir("thunderbird-profiles");
+        let dir = temp_dir.path().to_path_buf();
+        fs::write(dir.join("profiles.ini"), PROFILES_INI).unwrap();
+        fs::create_dir_all(dir.join("Profiles").join("abcd1234.Personal")).unwrap();
+        fs::create_dir_all(dir.join("Profiles").join("efgh5678.default-release")).unwrap();
+        // "Removed" profile directory is intentionally missing
+
+        let binary_path = Path::new("/usr/lib/thunderbird/thunderbird");
+        let mut profiles = find_thunderbird_profiles(dir.as_path(), binary_path, "thunderbird");
+        profiles.sort_by_key(|p| p.profile_name.clone());
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.profile_name.as_str()).collect();
+        assert_eq!(names, vec!["Personal", "Work"]);
+        assert_eq!(profiles[1].profile_cli_arg_value, "Work");
+        assert_eq!(profiles[1].profile_cli_container_name, None);
+    }
+
+    #[test]
+    fn test_find_thunderbird_profiles_without_profiles_ini() {
+        let temp_dir = test_utils::create_temp_dir("thunderbird-profiles");
+        let dir = temp_dir.path().to_path_buf();
+
+        let binary_path = Path::new("/usr/lib/thunderbird/thunderbird");
+        let profiles = find_thunderbird_profiles(dir.as_path(), binary_path, "thunderbird");
+        assert!(profiles.is_empty());
+    }
+}
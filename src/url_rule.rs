@@ -4,6 +4,8 @@ use globset::{GlobBuilder, GlobMatcher};
 use tracing::debug;
 use url::Url;
 
+use crate::mail_url_parser;
+
 /// [scheme://]hostname[/path][?query][#fragment]
 /// [*://]**[/**][?**][#*]
 #[derive(Debug, PartialEq)]
@@ -59,6 +61,10 @@ impl UrlGlobMatcher {
 
     fn to_target_url(&self, url: &Url) -> TargetUrl {
         let scheme = url.scheme();
+        if url.host_str().is_none() {
+            return Self::to_hostless_target_url(url);
+        }
+
         let host = url
             .host_str()
             .unwrap_or_else(|| panic!("no host found from url: {}", url.as_str()));
@@ -75,6 +81,30 @@ impl UrlGlobMatcher {
         };
     }
 
+    // Links without a host are matched by their address, so the domain is matched
+    // as the hostname and the user as the path, e.g
+    //   "mailto:madis@qminderapp.com?subject=Hi" as "mailto://qminderapp.com/madis?subject=Hi"
+    //   "sip:alice@example.com" as "sip://example.com/alice"
+    //   "tel:+3725551234" as "tel:///+3725551234"
+    //   "magnet:?xt=urn:btih:abc" as "magnet:///?xt=urn:btih:abc"
+    fn to_hostless_target_url(url: &Url) -> TargetUrl {
+        let (hostname, path) = if url.scheme() == "mailto" {
+            mail_url_parser::mailto_first_recipient_parts(url)
+                .map(|(local_part, domain)| (domain, "/".to_string() + local_part.as_str()))
+                .unwrap_or_else(|| ("".to_string(), "/".to_string()))
+        } else {
+            split_address(url.path())
+        };
+
+        return TargetUrl {
+            scheme: url.scheme().to_string(),
+            hostname: hostname,
+            path: path,
+            query: url.query().unwrap_or("").to_string(),
+            fragment: url.fragment().unwrap_or("").to_string(),
+        };
+    }
+
     pub fn url_str_matches(&self, url_str: &str) -> bool {
         let url = Url::from_str(url_str).unwrap_or_else(|_| panic!("not a valid url: {}", url_str));
 
@@ -113,6 +143,23 @@ impl UrlMatcher {
     }
 }
 
+// "alice@example.com;transport=tcp" to ("example.com", "/alice")
+// "+3725551234" to ("", "/+3725551234")
+fn split_address(address: &str) -> (String, String) {
+    let address = address.trim_start_matches('/');
+
+    let user_and_domain_maybe = address
+        .split_once('@')
+        .map(|(user, rest)| (user, rest.split([';', '/', '?']).next().unwrap_or("")))
+        .filter(|(_, domain)| !domain.is_empty());
+
+    return if let Some((user, domain)) = user_and_domain_maybe {
+        (domain.to_lowercase(), "/".to_string() + user)
+    } else {
+        ("".to_string(), "/".to_string() + address)
+    };
+}
+
 struct TargetUrl {
     scheme: String,
     hostname: String,
@@ -404,4 +451,79 @@ mod tests {
             .url_str_matches("https://beginning.of.something.great/v2/matches/everything");
         assert_eq!(matches, false);
     }
+
+    #[test]
+    fn test_url_matches_mailto_recipient_domain() {
+        let url_glob_matcher = to_url_matcher("mailto://work.com").to_glob_matcher();
+        assert_eq!(
+            url_glob_matcher.url_str_matches("mailto:madis@work.com?subject=Hi"),
+            true
+        );
+        assert_eq!(url_glob_matcher.url_str_matches("mailto:madis@home.com"), false);
+        assert_eq!(url_glob_matcher.url_str_matches("https://work.com"), false);
+    }
+
+    #[test]
+    fn test_url_matches_mailto_recipient_subdomain_and_local_part() {
+        assert_eq!(
+            to_url_matcher("mailto://*.work.com/support")
+                .to_glob_matcher()
+                .url_str_matches("mailto:support@eu.work.com"),
+            true
+        );
+
+        assert_eq!(
+            to_url_matcher("mailto://**")
+                .to_glob_matcher()
+                .url_str_matches("mailto:?subject=Hi"),
+            true
+        );
+    }
+
+    #[test]
+    fn test_url_matches_hostless_scheme() {
+        assert_eq!(
+            to_url_matcher("magnet://**")
+                .to_glob_matcher()
+                .url_str_matches("magnet:?xt=urn:btih:c9e15763f722f23e98a29decdfae341b98d53056"),
+            true
+        );
+
+        assert_eq!(
+            to_url_matcher("magnet://**")
+                .to_glob_matcher()
+                .url_str_matches("https://example.com"),
+            false
+        );
+    }
+
+    #[test]
+    fn test_url_matches_hostless_path() {
+        let url_glob_matcher = to_url_matcher("tel:///+372*").to_glob_matcher();
+        assert_eq!(url_glob_matcher.url_str_matches("tel:+3725551234"), true);
+        assert_eq!(url_glob_matcher.url_str_matches("tel:+15551234"), false);
+    }
+
+    #[test]
+    fn test_url_matches_hostless_address_domain() {
+        let url_glob_matcher = to_url_matcher("sip://*.example.com").to_glob_matcher();
+        assert_eq!(
+            url_glob_matcher.url_str_matches("sip:alice@pbx.example.com;transport=tcp"),
+            true
+        );
+        assert_eq!(url_glob_matcher.url_str_matches("sip:alice@example.org"), false);
+    }
+
+    #[test]
+    fn test_split_address() {
+        assert_eq!(
+            split_address("alice@example.com;transport=tcp"),
+            ("example.com".to_string(), "/alice".to_string())
+        );
+        assert_eq!(
+            split_address("+3725551234"),
+            ("".to_string(), "/+3725551234".to_string())
+        );
+        assert_eq!(split_address(""), ("".to_string(), "/".to_string()));
+    }
 }
@@ -1,6 +1,7 @@
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{fs, u32};
 
 use druid::image::imageops::FilterType;
@@ -10,6 +11,9 @@ use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+use crate::custom_apps::CustomAppConfig;
+use crate::google_url_parser::GoogleAuthUserConfig;
+use crate::launch_options::LaunchOptionsConfig;
 #[cfg(target_os = "linux")]
 use crate::linux::linux_utils;
 #[cfg(target_os = "macos")]
@@ -48,22 +52,147 @@ pub fn set_as_default_web_browser() -> bool {
     return true;
 }
 
+// returns false if there was nothing to restore
+#[cfg(target_os = "linux")]
+pub fn restore_previous_default_web_browser() -> bool {
+    return linux_utils::restore_previous_default_web_browser();
+}
+
+// the default browser is changed in System Settings
+#[cfg(target_os = "macos")]
+pub fn restore_previous_default_web_browser() -> bool {
+    return false;
+}
+
+#[cfg(target_os = "windows")]
+pub fn restore_previous_default_web_browser() -> bool {
+    return false;
+}
+
+#[cfg(target_os = "linux")]
+pub fn sync_url_scheme_handlers(schemes: &Vec<String>) {
+    linux_utils::sync_url_scheme_handlers(schemes);
+}
+
+// macOS only handles the schemes declared in Info.plist
+#[cfg(target_os = "macos")]
+pub fn sync_url_scheme_handlers(_schemes: &Vec<String>) {}
+
+#[cfg(target_os = "windows")]
+pub fn sync_url_scheme_handlers(_schemes: &Vec<String>) {}
+
+// schemes which Browsers always handles
+const BUILT_IN_SCHEMES: [&'static str; 3] = ["http", "https", "mailto"];
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct Config {
     hidden_apps: Vec<String>,
+    // also leave out apps whose desktop entry has `NoDisplay=true` (Linux)
+    hide_no_display_apps: bool,
     hidden_profiles: Vec<String>,
     profile_order: Vec<String>,
     default_profile: Option<ProfileAndOptions>,
     rules: Vec<ConfigRule>,
     ui: UIConfig,
     behavior: BehavioralConfig,
+    // additional url schemes to handle, e.g ["tel", "magnet", "sip", "vscode"]
+    schemes: Vec<String>,
+    google_authuser: GoogleAuthUserConfig,
+    // additional instances of installed apps, each with its own user dir
+    app_instances: Vec<AppInstanceConfig>,
+    // accounts of mail apps which scope their links to an account, see `MailAccountConfig`
+    mail_accounts: Vec<MailAccountConfig>,
+    // by app or profile id, see `LaunchOptionsConfig`
+    launch_options: BTreeMap<String, LaunchOptionsConfig>,
+    // commands shown as apps, e.g "Play in mpv", see `CustomAppConfig`
+    custom_apps: Vec<CustomAppConfig>,
+    // environment variables of Browsers itself not to pass on to launched apps (Linux),
+    // e.g ones set by an AppImage; defaults to `BROWSERS_INHERITED_ENV_REMOVE`, and also
+    // `APPIMAGE_INHERITED_ENV_REMOVE` when running as an AppImage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inherited_env_remove: Option<Vec<String>>,
+}
+
+// debugging options of Browsers, which are not meant for launched apps
+const BROWSERS_INHERITED_ENV_REMOVE: [&'static str; 2] = ["RUST_BACKTRACE", "BROWSERS_LOG_LEVEL"];
+
+// runtime of the AppImage (GTK, Python, Qt) which would break or restyle launched apps;
+// only set by the AppImage when `APPIMAGE` or `APPDIR` is, otherwise they're the user's own
+const APPIMAGE_INHERITED_ENV_REMOVE: [&'static str; 15] = [
+    "LD_LIBRARY_PATH",
+    "LD_PRELOAD",
+    "APPDIR",
+    "APPIMAGE",
+    "ARGV0",
+    "OWD",
+    "GDK_PIXBUF_MODULE_FILE",
+    "GTK_PATH",
+    "GTK_EXE_PREFIX",
+    "GTK_DATA_PREFIX",
+    "GIO_MODULE_DIR",
+    "GSETTINGS_SCHEMA_DIR",
+    "PYTHONHOME",
+    "PYTHONPATH",
+    "QT_PLUGIN_PATH",
+];
+
+// An installed app launched with a separate user dir, e.g Chrome with its own
+// `--user-data-dir` for client work; shown as a separate app with its own profiles
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct AppInstanceConfig {
+    // installed app to base the instance on, either its id (e.g "com.google.Chrome",
+    // "google-chrome.desktop") or its executable path (as in `hidden_apps`)
+    pub app: String,
+    // shown after the app name, e.g "Acme" for "Google Chrome (Acme)"
+    pub name: String,
+    // Chromium user data dir, or Firefox profiles root (the dir which has profiles.ini);
+    // "~/" is expanded to the home dir
+    #[serde(alias = "user_data_dir", alias = "profiles_dir")]
+    pub user_dir: String,
+}
+
+impl AppInstanceConfig {
+    pub fn get_user_dir(&self) -> PathBuf {
+        return expand_home_dir(self.user_dir.as_str());
+    }
+}
+
+// An account of a mail app which scopes its links to an account, e.g Mimestream; each
+// account is shown as a profile of the app, which is offered the links of the account
+// (e.g "https://links.mimestream.com/g/<account>/t/<thread>"), and opens other links
+// of the app in the account
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct MailAccountConfig {
+    // installed app, either its id (e.g "com.mimestream.Mimestream") or its executable
+    // path (as in `hidden_apps`)
+    pub app: String,
+    // address of the account, e.g "madis@qminderapp.com"
+    pub account: String,
+}
+
+// expands "~/" to the home dir
+pub fn expand_home_dir(path: &str) -> PathBuf {
+    let path = path.trim();
+    if let Some(home_relative) = path.strip_prefix("~/") {
+        if let Some(home_dir) = dirs::home_dir() {
+            return home_dir.join(home_relative);
+        }
+    }
+    return PathBuf::from(path);
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(default)]
 pub struct BehavioralConfig {
     pub unwrap_urls: bool,
+    // if the app fails to launch, open the link in the default opener (or the next profile)
+    pub fallback_on_launch_failure: bool,
+    // how long to wait for the app to exit with an error (e.g if its profile is locked),
+    // 0 only detects apps which can't be started at all
+    pub launch_failure_grace_period_ms: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -76,6 +205,10 @@ pub struct UIConfig {
     pub quit_on_lost_focus: bool,
 
     pub theme: ConfiguredTheme,
+
+    // order profiles by when they were last used in the app (only Chromium based browsers
+    // track this), instead of the manual order
+    pub sort_by_last_active: bool,
 }
 
 impl Default for UIConfig {
@@ -84,6 +217,7 @@ impl Default for UIConfig {
             show_hotkeys: true,
             quit_on_lost_focus: false,
             theme: ConfiguredTheme::Auto,
+            sort_by_last_active: false,
         }
     }
 }
@@ -99,7 +233,50 @@ pub enum ConfiguredTheme {
 #[serde(default)]
 pub struct ProfileAndOptions {
     pub profile: String,
+    // signed-in account of the profile, e.g "madis@acme.com" or "@acme.com" for any account of
+    // that domain; when set, it is used instead of `profile`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    // Google account to open Google links in, e.g "madis@acme.com",
+    // useful when a profile is signed in to several Google accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authuser: Option<String>,
     pub incognito: bool,
+    // how the link is opened, e.g "NewWindow"; `incognito` is still written for older versions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_mode: Option<LaunchMode>,
+}
+
+impl ProfileAndOptions {
+    pub fn get_launch_mode(&self) -> Option<LaunchMode> {
+        return self
+            .launch_mode
+            .or_else(|| self.incognito.then(|| LaunchMode::Incognito));
+    }
+}
+
+// ways to open a link besides the app default, each app supports only some of them
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Data, PartialEq)]
+pub enum LaunchMode {
+    Incognito,
+    NewWindow,
+    NewTab,
+    // window without browser UI, e.g `--app=<url>` in Chromium
+    AppWindow,
+    Guest,
+}
+
+impl LaunchMode {
+    // selects the launch mode name in the `launch-mode` message of builtin.ftl
+    pub fn get_localization_arg(&self) -> &'static str {
+        return match self {
+            LaunchMode::Incognito => "incognito",
+            LaunchMode::NewWindow => "new-window",
+            LaunchMode::NewTab => "new-tab",
+            LaunchMode::AppWindow => "app-window",
+            LaunchMode::Guest => "guest",
+        };
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -135,6 +312,42 @@ impl Config {
         &self.hidden_profiles
     }
 
+    // replaces profile id everywhere in config, returns true if it was found
+    pub fn migrate_profile_id(&mut self, legacy_profile_id: &str, profile_id: &str) -> bool {
+        let mut migrated = false;
+
+        let profile_ids = self
+            .hidden_profiles
+            .iter_mut()
+            .chain(self.profile_order.iter_mut());
+        for id in profile_ids {
+            if id == legacy_profile_id {
+                *id = profile_id.to_string();
+                migrated = true;
+            }
+        }
+
+        let openers = self
+            .rules
+            .iter_mut()
+            .filter_map(|rule| rule.opener.as_mut())
+            .chain(self.default_profile.as_mut());
+        for opener in openers {
+            if opener.profile == legacy_profile_id {
+                opener.profile = profile_id.to_string();
+                migrated = true;
+            }
+        }
+
+        if let Some(launch_options) = self.launch_options.remove(legacy_profile_id) {
+            self.launch_options
+                .insert(profile_id.to_string(), launch_options);
+            migrated = true;
+        }
+
+        return migrated;
+    }
+
     pub fn restore_profile(&mut self, profile_id: &str) {
         let hidden_profile_index_maybe = self
             .hidden_profiles
@@ -198,6 +411,87 @@ impl Config {
         self.ui = ui_config
     }
 
+    // configured schemes, without the built-in ones
+    pub fn get_schemes(&self) -> Vec<String> {
+        let mut schemes: Vec<String> = Vec::new();
+        for scheme in &self.schemes {
+            let scheme = scheme.trim().trim_end_matches(':').to_lowercase();
+            if scheme.is_empty()
+                || BUILT_IN_SCHEMES.contains(&scheme.as_str())
+                || schemes.contains(&scheme)
+            {
+                continue;
+            }
+            schemes.push(scheme);
+        }
+        return schemes;
+    }
+
+    // built-in and configured schemes
+    pub fn get_handled_schemes(&self) -> Vec<String> {
+        let mut schemes: Vec<String> = BUILT_IN_SCHEMES.iter().map(|s| s.to_string()).collect();
+        schemes.extend(self.get_schemes());
+        return schemes;
+    }
+
+    pub fn get_google_authuser(&self) -> &GoogleAuthUserConfig {
+        return &self.google_authuser;
+    }
+
+    pub fn get_app_instances(&self) -> &Vec<AppInstanceConfig> {
+        return &self.app_instances;
+    }
+
+    pub fn get_mail_accounts(&self) -> &Vec<MailAccountConfig> {
+        return &self.mail_accounts;
+    }
+
+    pub fn get_launch_options(&self) -> &BTreeMap<String, LaunchOptionsConfig> {
+        return &self.launch_options;
+    }
+
+    pub fn set_launch_options(&mut self, launch_options: BTreeMap<String, LaunchOptionsConfig>) {
+        self.launch_options = launch_options;
+    }
+
+    pub fn get_hide_no_display_apps(&self) -> bool {
+        return self.hide_no_display_apps;
+    }
+
+    pub fn get_custom_apps(&self) -> &Vec<CustomAppConfig> {
+        return &self.custom_apps;
+    }
+
+    // only the ones Browsers actually has, so they don't clutter the launch commands
+    pub fn get_inherited_env_remove(&self) -> Vec<String> {
+        if !cfg!(target_os = "linux") {
+            return vec![];
+        }
+
+        let names: Vec<String> = match &self.inherited_env_remove {
+            Some(names) => names.clone(),
+            None => {
+                let is_appimage =
+                    std::env::var_os("APPIMAGE").is_some() || std::env::var_os("APPDIR").is_some();
+                let appimage_names: &[&str] = if is_appimage {
+                    &APPIMAGE_INHERITED_ENV_REMOVE
+                } else {
+                    &[]
+                };
+                BROWSERS_INHERITED_ENV_REMOVE
+                    .iter()
+                    .chain(appimage_names.iter())
+                    .map(|name| name.to_string())
+                    .collect()
+            }
+        };
+        return names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty() && std::env::var_os(name).is_some())
+            .collect();
+    }
+
     pub fn get_behavior(&self) -> &BehavioralConfig {
         return &self.behavior;
     }
@@ -240,13 +534,18 @@ impl OSAppFinder {
         }
     }
 
-    pub fn get_installed_browsers(&self) -> Vec<InstalledBrowser> {
+    pub fn get_installed_browsers(&self, extra_schemes: &Vec<String>) -> Vec<InstalledBrowser> {
         let schemes = vec![
             ("figma", vec!["figma.com", "www.figma.com"]),
             ("linear", vec!["linear.app"]),
+            // mail clients, only offered for mailto: links
+            ("mailto", vec!["mailto://**"]),
             ("mimestream", vec!["links.mimestream.com"]),
             ("notion", vec!["notion.so", "www.notion.so"]),
-            ("slack", vec!["*.slack.com", "*.enterprise.slack.com"]),
+            (
+                "slack",
+                vec!["*.slack.com", "*.enterprise.slack.com", "*.slack-gov.com"],
+            ),
             ("spotify", vec!["open.spotify.com"]),
             ("tg", vec!["t.me"]), // telegram
             (
@@ -265,7 +564,7 @@ impl OSAppFinder {
             ("workflowy", vec!["workflowy.com"]),
             ("https", vec![]),
         ];
-        let schemes_vec: Vec<(String, Vec<String>)> = schemes
+        let mut schemes_vec: Vec<(String, Vec<String>)> = schemes
             .iter()
             .map(|(scheme, domain_patterns)| {
                 (
@@ -275,6 +574,14 @@ impl OSAppFinder {
             })
             .collect();
 
+        // handlers of configured schemes are only offered for links of that scheme
+        for extra_scheme in extra_schemes {
+            let already_probed = schemes_vec.iter().any(|(scheme, _)| scheme == extra_scheme);
+            if !already_probed {
+                schemes_vec.push((extra_scheme.to_string(), vec![format!("{extra_scheme}://**")]));
+            }
+        }
+
         return self.inner.get_installed_browsers(schemes_vec);
     }
 
@@ -323,9 +630,30 @@ impl OSAppFinder {
         }
     }
 
+    // The configured url schemes are cached when they change (i.e config was saved),
+    // so that the installed apps and the url scheme handlers are only refreshed then
+    pub(crate) fn has_cached_schemes(&self, schemes: &Vec<String>) -> bool {
+        let schemes_json_path = paths::get_cache_root_dir().join("url_schemes.json");
+        let cached_schemes: Vec<String> = File::open(schemes_json_path.as_path())
+            .ok()
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+            .unwrap_or_default();
+        return &cached_schemes == schemes;
+    }
+
+    pub(crate) fn save_cached_schemes(&self, schemes: &Vec<String>) {
+        let cache_root_dir = paths::get_cache_root_dir();
+        fs::create_dir_all(cache_root_dir.as_path()).unwrap();
+
+        info!("Configured url schemes changed to {:?}", schemes);
+        let buffer = File::create(cache_root_dir.join("url_schemes.json")).unwrap();
+        serde_json::to_writer_pretty(buffer, schemes).unwrap();
+    }
+
     pub(crate) fn get_installed_browsers_cached(
         &self,
         force_reload: bool,
+        extra_schemes: &Vec<String>,
     ) -> Vec<InstalledBrowser> {
         let cache_root_dir = paths::get_cache_root_dir();
         fs::create_dir_all(cache_root_dir.as_path()).unwrap();
@@ -341,13 +669,59 @@ impl OSAppFinder {
             let installed_browsers_cached = a.unwrap_or_default();
             return installed_browsers_cached;
         } else {
-            let installed_browsers = self.get_installed_browsers();
+            let installed_browsers = self.get_installed_browsers(extra_schemes);
 
             let buffer = File::create(installed_browsers_json_path).unwrap();
             serde_json::to_writer_pretty(buffer, &installed_browsers).unwrap();
             return installed_browsers;
         }
     }
+
+    // app instances are cached with the configs they were created from,
+    // so their profiles are found again only when the configs or the installed apps change
+    pub(crate) fn get_app_instances_cached(
+        &self,
+        force_reload: bool,
+        installed_browsers: &[InstalledBrowser],
+        app_instance_configs: &[AppInstanceConfig],
+    ) -> Vec<InstalledBrowser> {
+        let cache_root_dir = paths::get_cache_root_dir();
+        fs::create_dir_all(cache_root_dir.as_path()).unwrap();
+
+        let app_instances_json_path = cache_root_dir.join("app_instances.json");
+
+        if !force_reload && app_instances_json_path.exists() {
+            let file = File::open(app_instances_json_path.as_path()).unwrap();
+            let reader = BufReader::new(file);
+
+            let result: Result<CachedAppInstances, _> = serde_json::from_reader(reader);
+            if let Ok(cached_app_instances) = result {
+                if cached_app_instances.app_instance_configs == app_instance_configs {
+                    return cached_app_instances.app_instances;
+                }
+            }
+        }
+
+        let app_instances = crate::create_app_instances(
+            installed_browsers,
+            app_instance_configs,
+            self.get_app_repository(),
+        );
+        let cached_app_instances = CachedAppInstances {
+            app_instance_configs: app_instance_configs.to_vec(),
+            app_instances: app_instances,
+        };
+
+        let buffer = File::create(app_instances_json_path).unwrap();
+        serde_json::to_writer_pretty(buffer, &cached_app_instances).unwrap();
+        return cached_app_instances.app_instances;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedAppInstances {
+    app_instance_configs: Vec<AppInstanceConfig>,
+    app_instances: Vec<InstalledBrowser>,
 }
 
 /*const fn create_circular_mask_radius<const N: usize>() -> [[bool; N]; N] {
@@ -454,3 +828,51 @@ pub fn save_as_circular(image_bytes: Vec<u8>, to_image_path: &Path) {
 
     debug!("WROTE TO : {:?}", png_file_path.as_path());
 }
+
+// circle filled with a single color, e.g for profiles which have only a theme color,
+// optionally with a 16x16 glyph ("#" is drawn) of the given color in the middle
+pub fn save_as_colored_circle(
+    rgb: [u8; 3],
+    glyph_maybe: Option<([&str; 16], [u8; 3])>,
+    to_image_path: &Path,
+) {
+    let [r, g, b] = rgb;
+    let mut circle_image = image::RgbaImage::new(CIRCULAR_RADIUS as u32, CIRCULAR_RADIUS as u32);
+
+    for (x, row) in CIRCULAR_MASK_32_LAZY.iter().enumerate() {
+        for (y, mask) in row.iter().enumerate() {
+            let alpha = if *mask { 255 } else { 0 };
+            circle_image.put_pixel(x as u32, y as u32, Rgba([r, g, b, alpha]));
+        }
+    }
+
+    if let Some((glyph, [glyph_r, glyph_g, glyph_b])) = glyph_maybe {
+        // glyph takes half of the circle, each glyph pixel is a square of `scale` pixels
+        let scale = CIRCULAR_RADIUS / 2 / 16;
+        let offset = CIRCULAR_RADIUS / 4;
+        for (glyph_y, glyph_row) in glyph.iter().enumerate() {
+            for (glyph_x, glyph_pixel) in glyph_row.chars().enumerate() {
+                if glyph_pixel != '#' {
+                    continue;
+                }
+                for dx in 0..scale {
+                    for dy in 0..scale {
+                        let x = offset + glyph_x * scale + dx;
+                        let y = offset + glyph_y * scale + dy;
+                        circle_image.put_pixel(
+                            x as u32,
+                            y as u32,
+                            Rgba([glyph_r, glyph_g, glyph_b, 255]),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    circle_image
+        .save_with_format(to_image_path, ImageFormat::Png)
+        .unwrap();
+
+    debug!("WROTE TO : {:?}", to_image_path);
+}
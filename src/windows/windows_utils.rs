@@ -234,10 +234,6 @@ impl OsHelper {
         // We do use path for uniqueness, so it should be fine if there are duplicate names
         let app_id = app_info.name.to_string();
 
-        let supported_app = self
-            .app_repository
-            .get_or_generate(app_id.as_str(), &restricted_domains);
-
         let icon_filename = app_id.to_string() + ".png";
         let full_stored_icon_path = icons_root_dir.join(icon_filename);
         let icon_path_str = full_stored_icon_path.display().to_string();
@@ -275,6 +271,17 @@ impl OsHelper {
             .map(|path_perhaps| Path::new(path_perhaps))
             .unwrap_or(Path::new("unknown"));
 
+        let guessed_app_config_maybe = self.app_repository.guess_app_config(
+            app_id.as_str(),
+            executable_path_best_guess,
+            display_name.as_str(),
+        );
+        let supported_app = self.app_repository.get_or_generate(
+            app_id.as_str(),
+            &restricted_domains,
+            guessed_app_config_maybe.as_ref(),
+        );
+
         let app_config_dir_abs = supported_app.get_app_config_dir_abs(false, false);
 
         let profiles =
@@ -289,6 +296,11 @@ impl OsHelper {
             icon_path: icon_path_str.clone(),
             profiles: profiles,
             restricted_domains: restricted_domains,
+            custom_user_dir: None,
+            custom_app: None,
+            desktop_action_launch_modes: vec![],
+            no_display: false,
+            guessed_app_config: guessed_app_config_maybe,
         };
         return Some(browser);
     }
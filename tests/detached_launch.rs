@@ -0,0 +1,82 @@
+#![cfg(target_os = "linux")]
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use browsers::detached_process;
+use browsers::launch_options::LaunchOptionsConfig;
+use browsers::utils::Config;
+
+// writes its environment and session id, as a browser launched by Browsers would see them
+const FAKE_BROWSER_SCRIPT: &str = r#"#!/bin/sh
+env > "$1/env"
+cut -d ' ' -f 6 /proc/$$/stat > "$1/sid"
+"#;
+
+fn create_fake_browser(test_dir: &Path) -> PathBuf {
+    let script_path = test_dir.join("fake-browser");
+    fs::write(&script_path, FAKE_BROWSER_SCRIPT).unwrap();
+    fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+    return script_path;
+}
+
+// the only test of this binary, as it changes the environment of the process
+#[test]
+fn test_detached_launch_from_appimage() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("browsers-test-detached-launch-")
+        .tempdir()
+        .unwrap();
+    let test_dir = temp_dir.path();
+    let script_path = create_fake_browser(test_dir);
+
+    // as if Browsers itself was run from an AppImage
+    unsafe {
+        env::set_var("APPDIR", "/tmp/.mount_Browsers");
+        env::set_var("PYTHONPATH", "/tmp/.mount_Browsers/usr/lib/python3");
+        env::set_var("GTK_THEME", "Adwaita:dark");
+    }
+    let inherited_env_remove = Config::default().get_inherited_env_remove();
+    assert!(inherited_env_remove.contains(&"APPDIR".to_string()));
+    assert!(inherited_env_remove.contains(&"PYTHONPATH".to_string()));
+
+    let mut launch_options: BTreeMap<String, LaunchOptionsConfig> = BTreeMap::new();
+    launch_options.insert(
+        "/usr/bin/fake-browser".to_string(),
+        LaunchOptionsConfig::from_lines("", "MOZ_ENABLE_WAYLAND=1\nGTK_THEME=Adwaita", "", ""),
+    );
+    let options = LaunchOptionsConfig::resolve(
+        &launch_options,
+        &inherited_env_remove,
+        "/usr/bin/fake-browser",
+        "/usr/bin/fake-browser#",
+    );
+
+    let mut cmd = Command::new(&script_path);
+    cmd.arg(test_dir);
+    options.apply_env_and_working_dir(&mut cmd);
+    detached_process::detach(&mut cmd);
+
+    let mut child = cmd.spawn().unwrap();
+    let pid = child.id();
+    assert!(child.wait().unwrap().success());
+
+    let env = fs::read_to_string(test_dir.join("env")).unwrap();
+    let env_lines: Vec<&str> = env.lines().collect();
+    assert!(!env_lines.iter().any(|line| line.starts_with("APPDIR=")));
+    assert!(!env_lines.iter().any(|line| line.starts_with("PYTHONPATH=")));
+    // set by the app launch options, so it's kept
+    assert!(env_lines.contains(&"GTK_THEME=Adwaita"));
+    assert!(env_lines.contains(&"MOZ_ENABLE_WAYLAND=1"));
+    assert!(env_lines.iter().any(|line| line.starts_with("PATH=")));
+
+    // session leader, so session id is its own process id
+    let sid = fs::read_to_string(test_dir.join("sid")).unwrap();
+    assert_eq!(sid.trim(), pid.to_string());
+    let own_sid = unsafe { libc::getsid(0) };
+    assert_ne!(sid.trim(), own_sid.to_string());
+}